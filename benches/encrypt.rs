@@ -0,0 +1,49 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use enigma_rs::machine::factory;
+
+/// Builds a plaintext of the given length from a repeating German sentence
+fn plaintext(len: usize) -> String {
+    "DASOBERKOMMANDODERWEHRMACHTGIBTBEKANNT"
+        .chars()
+        .cycle()
+        .take(len)
+        .collect()
+}
+
+fn bench_encrypt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encrypt");
+
+    for len in [1_000, 1_000_000] {
+        let text = plaintext(len);
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_function(format!("{} letters", len), |b| {
+            let mut machine = factory::create_custom_machine(
+                ["II", "IV", "V"],
+                ['B', 'L', 'A'],
+                ['B', 'U', 'L'],
+                "B",
+                "AV BS CG DL FU HZ IN KM OW RX",
+            )
+            .expect("Machine should be creatable");
+
+            b.iter(|| {
+                machine.reset();
+                black_box(machine.encrypt(black_box(&text)))
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_encrypt_char(c: &mut Criterion) {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB CD")
+        .expect("Machine should be creatable");
+
+    c.bench_function("encrypt_char", |b| {
+        b.iter(|| black_box(machine.encrypt_char(black_box('E'))))
+    });
+}
+
+criterion_group!(benches, bench_encrypt, bench_encrypt_char);
+criterion_main!(benches);
@@ -0,0 +1,188 @@
+//! Kryptanalyse mit bekanntem Klartext (Crib)
+//!
+//! Dieses Modul sucht ähnlich der Turing-Bombe nach Grundstellungen, unter
+//! denen ein vermuteter Klartext (Crib) auf den Geheimtext abgebildet wird.
+//! Ringstellung und Steckerbrett werden dabei nicht variiert. Außerdem
+//! kann bei unbekanntem Reflektor jede Variante statistisch bewertet werden.
+
+use crate::machine::{factory, EnigmaMachine, MachineConfig};
+use crate::utils::{chi_squared_english, clean_text, index_to_letter};
+
+/// Findet alle Offsets, an denen der Crib im Geheimtext stehen kann
+///
+/// Da die Enigma keinen Buchstaben auf sich selbst abbildet, scheiden alle
+/// Offsets aus, an denen ein Crib-Buchstabe mit dem darunterliegenden
+/// Geheimtextbuchstaben übereinstimmt.
+///
+/// # Arguments
+/// * `ciphertext` - Der Geheimtext
+/// * `crib` - Der vermutete Klartext
+///
+/// # Returns
+/// * Die möglichen Offsets in aufsteigender Reihenfolge
+pub fn possible_crib_offsets(ciphertext: &str, crib: &str) -> Vec<usize> {
+    let ciphertext: Vec<char> = clean_text(ciphertext).chars().collect();
+    let crib: Vec<char> = clean_text(crib).chars().collect();
+
+    if crib.is_empty() || crib.len() > ciphertext.len() {
+        return Vec::new();
+    }
+
+    (0..=ciphertext.len() - crib.len())
+        .filter(|&offset| {
+            crib.iter()
+                .zip(&ciphertext[offset..])
+                .all(|(plain, cipher)| plain != cipher)
+        })
+        .collect()
+}
+
+/// Probiert alle 26³ Grundstellungen für eine Walzenlage aus
+///
+/// Für jede Grundstellung wird die Maschine Zeichen für Zeichen
+/// weitergeschaltet. An jeder Stelle wird mit der Substitution der aktuellen
+/// Walzenstellung geprüft, ob der Crib an einem der möglichen Offsets noch
+/// widerspruchsfrei auf den Geheimtext abgebildet wird.
+///
+/// # Arguments
+/// * `ciphertext` - Der Geheimtext
+/// * `crib` - Der vermutete Klartext
+/// * `rotor_types` - Die Walzenlage (z.B. ["I", "II", "III"])
+/// * `reflector` - Der Reflektortyp
+///
+/// # Returns
+/// * Alle passenden Konfigurationen (Ringstellung AAA, ohne Steckerbrett)
+pub fn brute_force_positions(
+    ciphertext: &str,
+    crib: &str,
+    rotor_types: [&str; 3],
+    reflector: &str,
+) -> Vec<MachineConfig> {
+    let offsets = possible_crib_offsets(ciphertext, crib);
+    let ciphertext: Vec<char> = clean_text(ciphertext).chars().collect();
+    let crib: Vec<char> = clean_text(crib).chars().collect();
+
+    if offsets.is_empty() {
+        info!("Keine möglichen Crib-Positionen gefunden");
+        return Vec::new();
+    }
+
+    let mut machine =
+        match factory::create_custom_machine(rotor_types, ['A'; 3], ['A'; 3], reflector, "") {
+            Ok(machine) => machine,
+            Err(e) => {
+                warn!("Maschine konnte nicht erstellt werden: {}", e);
+                return Vec::new();
+            }
+        };
+
+    let last_needed = offsets[offsets.len() - 1] + crib.len();
+    let mut candidates = Vec::new();
+
+    for start in 0..26 * 26 * 26 {
+        let positions = [start / 676, (start / 26) % 26, start % 26];
+        for (rotor, &position) in machine.rotors.iter_mut().zip(positions.iter()) {
+            rotor.set_position(position);
+        }
+        let config = machine.to_config();
+
+        let mut alive = vec![true; offsets.len()];
+        for (i, &cipher_char) in ciphertext.iter().enumerate().take(last_needed) {
+            // Schaltet die Walzen auf die Stellung für Zeichen i
+            machine.encrypt_char('A');
+
+            for (slot, &offset) in offsets.iter().enumerate() {
+                if alive[slot]
+                    && i >= offset
+                    && i < offset + crib.len()
+                    && machine.transform_char(crib[i - offset]) != Some(cipher_char)
+                {
+                    alive[slot] = false;
+                }
+            }
+
+            if !alive.iter().any(|&a| a) {
+                break;
+            }
+        }
+
+        if alive.iter().any(|&a| a) {
+            info!(
+                "Mögliche Grundstellung gefunden: {}",
+                positions
+                    .iter()
+                    .map(|&p| index_to_letter(p).unwrap_or('A'))
+                    .collect::<String>()
+            );
+            candidates.push(config);
+        }
+    }
+
+    candidates
+}
+
+/// Entschlüsselt einen Geheimtext mit jedem Standardreflektor (A, B, C)
+///
+/// Alle übrigen Einstellungen stammen aus `config`; dessen Reflektortyp
+/// wird ignoriert. Jede Entschlüsselung wird mit dem Chi-Quadrat-Abstand
+/// zur englischen Buchstabenverteilung bewertet, kleinere Werte bedeuten
+/// lesbareren Text. Ungültige Kombinationen werden übersprungen.
+///
+/// # Arguments
+/// * `config` - Die Konfiguration ohne (bzw. mit beliebigem) Reflektor
+/// * `ciphertext` - Der Geheimtext
+///
+/// # Returns
+/// * (Reflektor, Klartext, Chi-Quadrat) je Reflektor, bester Kandidat zuerst
+pub fn try_reflectors(config: &MachineConfig, ciphertext: &str) -> Vec<(String, String, f64)> {
+    rank_reflectors(config, ciphertext, factory::from_config)
+}
+
+/// Wie `try_reflectors`, erlaubt aber mehrfach ausgewählte Rotoren
+///
+/// # Arguments
+/// * `config` - Die Konfiguration ohne (bzw. mit beliebigem) Reflektor
+/// * `ciphertext` - Der Geheimtext
+///
+/// # Returns
+/// * (Reflektor, Klartext, Chi-Quadrat) je Reflektor, bester Kandidat zuerst
+pub fn try_reflectors_allowing_duplicates(
+    config: &MachineConfig,
+    ciphertext: &str,
+) -> Vec<(String, String, f64)> {
+    rank_reflectors(config, ciphertext, factory::from_config_allowing_duplicates)
+}
+
+/// Bewertet jeden Standardreflektor mit der angegebenen Maschinenfabrik
+fn rank_reflectors(
+    config: &MachineConfig,
+    ciphertext: &str,
+    build: fn(&MachineConfig) -> Result<EnigmaMachine, String>,
+) -> Vec<(String, String, f64)> {
+    let ciphertext = clean_text(ciphertext);
+
+    let mut results: Vec<(String, String, f64)> = ["A", "B", "C"]
+        .iter()
+        .filter_map(|&reflector| {
+            let candidate = MachineConfig {
+                reflector_type: reflector.to_string(),
+                ..config.clone()
+            };
+            let mut machine = match build(&candidate) {
+                Ok(machine) => machine,
+                Err(e) => {
+                    warn!("Reflektor {} nicht verwendbar: {}", reflector, e);
+                    return None;
+                }
+            };
+
+            let plaintext = machine.process_exact(&ciphertext);
+            let score = chi_squared_english(&plaintext);
+            debug!("Reflektor {}: Chi-Quadrat {:.2}", reflector, score);
+            Some((reflector.to_string(), plaintext, score))
+        })
+        .collect();
+
+    results.sort_by(|a, b| a.2.total_cmp(&b.2));
+    results
+}
@@ -0,0 +1,24 @@
+//! Cryptographic helpers for analysing Enigma settings
+//!
+//! This module contains small, pure functions that describe mathematical
+//! properties of the machine. They are used to reduce the search space
+//! when brute-forcing ring settings and rotor positions.
+
+/// Returns the effective offset of a rotor for a given position and ring setting
+///
+/// A rotor only ever uses the difference between its position and its ring
+/// setting when substituting a letter (see `Rotor::forward`/`Rotor::backward`).
+/// Two pairs `(position, ring)` and `(position + k, ring + k)` therefore
+/// produce the same substitution for the first character, as long as neither
+/// pair causes a different turnover of the neighbouring rotor. A searcher can
+/// thus test only one pair per offset instead of the full 26×26 space.
+///
+/// # Arguments
+/// * `position` - The rotor position (0-25)
+/// * `ring` - The ring setting (0-25)
+///
+/// # Returns
+/// * The canonical offset `(position - ring) mod 26` (0-25)
+pub fn canonical_offset(position: usize, ring: usize) -> usize {
+    (position % 26 + 26 - ring % 26) % 26
+}
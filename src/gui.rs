@@ -3,8 +3,14 @@
 //! This module contains the graphical user interface with all
 //! configuration options and detailed log display.
 
-use crate::machine::{factory, EnigmaMachine};
-use crate::utils::clean_text;
+use crate::machine::{factory, EnigmaMachine, MachineConfig};
+use crate::plugboard::{Plugboard, DEFAULT_MAX_CONNECTIONS};
+use crate::reflector::{reflectors, Reflector, ReflectorType};
+use crate::rotor::{rotors, RotorType};
+use crate::utils::{
+    clean_text, format_histogram, generate_random_plugboard_cables_with, generate_random_settings,
+    group_text, index_of_coincidence, letter_histogram, letter_to_index, parse_setting,
+};
 use eframe::egui;
 use log::Level;
 use std::collections::VecDeque;
@@ -12,11 +18,25 @@ use std::collections::VecDeque;
 /// Maximum number of log entries in the GUI
 const MAX_LOG_ENTRIES: usize = 1000;
 
+/// File used by the save/load menu entries
+const CONFIG_FILE: &str = "enigma_config.json";
+
+/// Buchstabenreihen von Tastatur, Lampenfeld und Steckerbrett der Enigma I
+const KEYBOARD_ROWS: [&str; 3] = ["QWERTZUIO", "ASDFGHJK", "PYXCVBNML"];
+
+/// Dauer der roten Markierung nach einem ungültigen Steckversuch (Sekunden)
+const PLUG_FLASH_SECONDS: f64 = 0.6;
+
+/// Anzahl der gespeicherten Walzenstellungen für "Schritt zurück"
+const POSITION_HISTORY_LIMIT: usize = 100;
+
 /// Represents a log entry for the GUIll
 ///
 #[derive(Clone)]
 pub struct LogEntry {
     pub level: Level,
+    /// Das Log-Target, z.B. "enigma_rs::turnover" für Überträge
+    pub target: String,
     pub message: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
@@ -29,16 +49,39 @@ pub struct EnigmaApp {
     // GUI-Zustand
     input_text: String,
     output_text: String,
+    output_raw: String,
+    group_output: bool,
 
     // Konfiguration
     rotor_types: [String; 3],
+    allow_duplicate_rotors: bool,
+    /// Ob die aktuelle Maschine mit mehrfach gewählten Rotoren erstellt wurde
+    machine_allows_duplicates: bool,
     rotor_positions: [String; 3],
     ring_settings: [String; 3],
     reflector_type: String,
     plugboard_connections: String,
+    use_custom_reflector: bool,
+    custom_reflector_pairs: [String; 13],
+    keysheet_line: String,
+
+    // Grafisches Steckerbrett
+    plug_selection: Option<char>,
+    plug_flash: Option<(char, f64)>,
+
+    // Schreibmaschinen-Modus
+    typewriter_mode: bool,
+    measure_timing: bool,
+    typewriter_buffer: String,
+    lit_lamp: Option<char>,
+
+    // Verlauf der Walzenstellungen (Schritt zurück / Schritt vor)
+    undo_positions: VecDeque<Vec<char>>,
+    redo_positions: Vec<Vec<char>>,
 
     // Log-Anzeige
     log_entries: VecDeque<LogEntry>,
+    log_sink: Option<LogSink>,
     auto_scroll_log: bool,
     log_filter: String,
 
@@ -65,12 +108,32 @@ impl Default for EnigmaApp {
             machine: None,
             input_text: String::new(),
             output_text: String::new(),
+            output_raw: String::new(),
+            group_output: true,
             rotor_types: ["I".to_string(), "II".to_string(), "III".to_string()],
+            allow_duplicate_rotors: false,
+            machine_allows_duplicates: false,
             rotor_positions: ["A".to_string(), "A".to_string(), "A".to_string()],
             ring_settings: ["A".to_string(), "A".to_string(), "A".to_string()],
             reflector_type: "B".to_string(),
             plugboard_connections: String::new(),
+            use_custom_reflector: false,
+            // Verdrahtung von Reflektor B als Ausgangspunkt
+            custom_reflector_pairs: [
+                "AY", "BR", "CU", "DH", "EQ", "FS", "GL", "IP", "JX", "KN", "MO", "TZ", "VW",
+            ]
+            .map(String::from),
+            keysheet_line: String::new(),
+            plug_selection: None,
+            plug_flash: None,
+            typewriter_mode: false,
+            measure_timing: false,
+            typewriter_buffer: String::new(),
+            lit_lamp: None,
+            undo_positions: VecDeque::new(),
+            redo_positions: Vec::new(),
             log_entries: VecDeque::new(),
+            log_sink: None,
             auto_scroll_log: true,
             log_filter: String::new(),
             show_config: true,
@@ -91,6 +154,41 @@ impl EnigmaApp {
         app
     }
 
+    /// Erstellt die GUI-Anwendung mit dem Puffer eines installierten `GuiLogAppender`
+    ///
+    /// Die Einträge des globalen Loggers (auch `trace!`/`debug!` der Maschine)
+    /// werden bei jedem Frame in die Log-Anzeige übernommen.
+    ///
+    /// # Arguments
+    /// * `log_sink` - Der Puffer aus `GuiLogAppender::get_log_entries`
+    pub fn with_log_sink(log_sink: LogSink) -> Self {
+        let mut app = Self {
+            log_sink: Some(log_sink),
+            ..Self::default()
+        };
+        app.initialize_machine();
+        app
+    }
+
+    /// Übernimmt neue Einträge des globalen Loggers in die Log-Anzeige
+    fn drain_log_sink(&mut self) {
+        let Some(sink) = &self.log_sink else {
+            return;
+        };
+
+        let drained: Vec<LogEntry> = match sink.lock() {
+            Ok(mut entries) => entries.drain(..).collect(),
+            Err(_) => return,
+        };
+
+        for entry in drained {
+            self.log_entries.push_back(entry);
+        }
+        while self.log_entries.len() > MAX_LOG_ENTRIES {
+            self.log_entries.pop_front();
+        }
+    }
+
     /// Wendet ein Rotor-Preset an
     fn apply_rotor_preset(&mut self, preset: &str) {
         match preset {
@@ -142,12 +240,9 @@ impl EnigmaApp {
 
     /// Generates random rotor positions
     fn generate_random_rotor_positions(&mut self) {
-        use rand::Rng;
-        let mut rng = rand::rng();
-
-        for i in 0..3 {
-            let random_letter = (b'A' + rng.random_range(0..26)) as char;
-            self.rotor_positions[i] = random_letter.to_string();
+        let positions = generate_random_settings(3);
+        for (field, position) in self.rotor_positions.iter_mut().zip(positions) {
+            *field = position.to_string();
         }
 
         self.add_log_entry(
@@ -161,12 +256,9 @@ impl EnigmaApp {
 
     /// Generates random ring settings
     fn generate_random_ring_settings(&mut self) {
-        use rand::Rng;
-        let mut rng = rand::rng();
-
-        for i in 0..3 {
-            let random_letter = (b'A' + rng.random_range(0..26)) as char;
-            self.ring_settings[i] = random_letter.to_string();
+        let rings = generate_random_settings(3);
+        for (field, ring) in self.ring_settings.iter_mut().zip(rings) {
+            *field = ring.to_string();
         }
 
         self.add_log_entry(
@@ -182,29 +274,11 @@ impl EnigmaApp {
     fn generate_random_plugboard(&mut self) {
         use rand::Rng;
         let mut rng = rand::rng();
-        let mut connections = Vec::new();
-        let mut used = std::collections::HashSet::new();
 
         // Generiere 5-10 zufällige Verbindungen
         let num_connections = rng.random_range(5..=10);
-
-        for _ in 0..num_connections {
-            let mut first = (b'A' + rng.random_range(0..26)) as char;
-            while used.contains(&first) {
-                first = (b'A' + rng.random_range(0..26)) as char;
-            }
-
-            let mut second = (b'A' + rng.random_range(0..26)) as char;
-            while second == first || used.contains(&second) {
-                second = (b'A' + rng.random_range(0..26)) as char;
-            }
-
-            used.insert(first);
-            used.insert(second);
-            connections.push(format!("{}{}", first, second));
-        }
-
-        self.plugboard_connections = connections.join(" ");
+        self.plugboard_connections =
+            generate_random_plugboard_cables_with(num_connections, &mut rng);
 
         self.add_log_entry(
             Level::Info,
@@ -253,8 +327,25 @@ impl EnigmaApp {
     fn initialize_machine(&mut self) {
         match self.create_machine_from_config() {
             Ok(machine) => {
+                // Änderungen gegenüber der bisherigen Grundstellung protokollieren
+                let changes = self
+                    .machine
+                    .as_ref()
+                    .map(|previous| {
+                        let mut previous = previous.clone();
+                        previous.reset();
+                        previous.to_config().diff(&machine.to_config())
+                    })
+                    .unwrap_or_default();
+
                 self.machine = Some(machine);
+                self.machine_allows_duplicates = self.allow_duplicate_rotors;
+                self.undo_positions.clear();
+                self.redo_positions.clear();
                 self.add_log_entry(Level::Info, "Enigma-Maschine erfolgreich initialisiert");
+                for change in changes {
+                    self.add_log_entry(Level::Info, &format!("Geändert – {}", change));
+                }
             }
             Err(e) => {
                 self.add_log_entry(
@@ -265,37 +356,441 @@ impl EnigmaApp {
         }
     }
 
-    /// Erstellt eine Enigma-Maschine basierend auf der aktuellen Konfiguration
-    fn create_machine_from_config(&self) -> Result<EnigmaMachine, String> {
-        let rotor_positions = [
-            self.rotor_positions[0].chars().next().unwrap_or('A'),
-            self.rotor_positions[1].chars().next().unwrap_or('A'),
-            self.rotor_positions[2].chars().next().unwrap_or('A'),
-        ];
+    /// Übernimmt die gewählten Rotoren in die bestehende Maschine
+    ///
+    /// Positionen und Ringstellungen der Maschine bleiben erhalten. Mehrfach
+    /// gewählte Rotoren sind erlaubt, wenn die Maschine damit erstellt wurde.
+    /// Mit eigenem Reflektor bleibt dessen Verdrahtung aus der Maschine
+    /// erhalten; geänderte Paare im Editor erfordern "Konfiguration anwenden".
+    fn swap_rotors(&mut self) {
+        let Some(machine) = self.machine.as_mut() else {
+            return;
+        };
+        let reflector_type = if self.use_custom_reflector {
+            machine.reflector.name.clone()
+        } else {
+            self.reflector_type.clone()
+        };
 
-        let ring_settings = [
-            self.ring_settings[0].chars().next().unwrap_or('A'),
-            self.ring_settings[1].chars().next().unwrap_or('A'),
-            self.ring_settings[2].chars().next().unwrap_or('A'),
+        let rotor_types = [
+            self.rotor_types[0].as_str(),
+            self.rotor_types[1].as_str(),
+            self.rotor_types[2].as_str(),
         ];
+        let result = if self.machine_allows_duplicates {
+            machine.reconfigure_allowing_duplicates(&rotor_types, &reflector_type)
+        } else {
+            machine.reconfigure(&rotor_types, &reflector_type)
+        };
+        match result {
+            Ok(()) => self.add_log_entry(
+                Level::Info,
+                &format!("Rotoren getauscht: {}", rotor_types.join(" ")),
+            ),
+            Err(e) => self.add_log_entry(
+                Level::Warn,
+                &format!("Rotorwechsel nicht übernommen: {}", e),
+            ),
+        }
+    }
+
+    /// Liest ein Positions- oder Ringfeld (Buchstabe oder Zahl 1-26)
+    ///
+    /// Ein leeres Feld gilt als "A".
+    fn parse_setting_field(field: &str) -> Result<char, String> {
+        if field.trim().is_empty() {
+            Ok('A')
+        } else {
+            parse_setting(field)
+        }
+    }
+
+    /// Prüft, ob alle Positions- und Ringfelder gültig sind
+    fn setting_fields_valid(&self) -> bool {
+        self.rotor_positions
+            .iter()
+            .chain(self.ring_settings.iter())
+            .all(|field| Self::parse_setting_field(field).is_ok())
+    }
+
+    /// Zeigt ein Positions- oder Ringfeld an und markiert ungültige Eingaben
+    ///
+    /// Ein ungültiges Feld wird rot dargestellt; der Tooltip nennt den Grund.
+    fn render_setting_field(ui: &mut egui::Ui, field: &mut String) {
+        let error = Self::parse_setting_field(field).err();
+        let color = if error.is_some() {
+            egui::Color32::from_rgb(220, 20, 60)
+        } else {
+            ui.visuals().text_color()
+        };
+
+        let response = ui.add(
+            egui::TextEdit::singleline(field)
+                .char_limit(2)
+                .desired_width(60.0)
+                .text_color(color),
+        );
+        if let Some(error) = error {
+            response.on_hover_text(format!("{} (erlaubt: A-Z oder 1-26)", error));
+        }
+    }
+
+    /// Rotoren für die drei Rotorfelder der Oberfläche
+    ///
+    /// Alle verfügbaren Rotoren außer den feststehenden Zusatzwalzen der M4,
+    /// die in einer Maschine mit drei Rotoren keinen Platz haben.
+    fn selectable_rotors() -> Vec<&'static str> {
+        rotors::available_rotor_names()
+            .into_iter()
+            .filter(|name| !name.parse::<RotorType>().is_ok_and(|t| t.is_stationary()))
+            .collect()
+    }
+
+    /// Formatiert die historischen Angaben eines Rotors für den Tooltip
+    fn rotor_info_text(info: &rotors::RotorInfo) -> String {
+        format!(
+            "Rotor {} – eingeführt {}\n{}\nVerdrahtung: {}\nKerbe: {}\n{}",
+            info.name, info.introduced, info.service, info.wiring, info.notches, info.note
+        )
+    }
+
+    /// Reflektoren für die Reflektorauswahl der Oberfläche
+    ///
+    /// Alle verfügbaren Reflektoren außer den dünnen M4-Reflektoren.
+    fn selectable_reflectors() -> Vec<&'static str> {
+        reflectors::available_reflector_names()
+            .into_iter()
+            .filter(|name| !name.parse::<ReflectorType>().is_ok_and(|t| t.is_thin()))
+            .collect()
+    }
+
+    /// Erstellt eine Enigma-Maschine basierend auf der aktuellen Konfiguration
+    fn create_machine_from_config(&self) -> Result<EnigmaMachine, String> {
+        let settings = |fields: &[String; 3]| -> Result<String, String> {
+            fields
+                .iter()
+                .map(|field| Self::parse_setting_field(field))
+                .collect()
+        };
 
-        factory::create_custom_machine(
-            [
-                &self.rotor_types[0],
-                &self.rotor_types[1],
-                &self.rotor_types[2],
-            ],
-            rotor_positions,
-            ring_settings,
+        let config = MachineConfig::from_settings(
+            &self.rotor_types.join(","),
+            &settings(&self.rotor_positions)?,
+            &settings(&self.ring_settings)?,
             &self.reflector_type,
             &self.plugboard_connections,
-        )
+        )?;
+        let mut machine = if self.allow_duplicate_rotors {
+            factory::from_config_allowing_duplicates(&config)?
+        } else {
+            factory::from_config(&config)?
+        };
+
+        if self.use_custom_reflector {
+            machine.reflector = Reflector::from_pairs(&self.custom_reflector_pairs.join(" "))?;
+        }
+
+        Ok(machine)
+    }
+
+    /// Prüft jedes Feld des Reflektor-Editors einzeln
+    ///
+    /// # Returns
+    /// * Array mit `true` für jedes ungültige Paar (kein Buchstabenpaar,
+    ///   Fixpunkt oder mehrfach verwendeter Buchstabe)
+    fn invalid_reflector_pairs(&self) -> [bool; 13] {
+        let mut usage = [0usize; 26];
+        for pair in &self.custom_reflector_pairs {
+            for ch in pair.chars() {
+                if let Some(index) = letter_to_index(ch) {
+                    usage[index] += 1;
+                }
+            }
+        }
+
+        let mut invalid = [false; 13];
+        for (i, pair) in self.custom_reflector_pairs.iter().enumerate() {
+            let indices: Vec<Option<usize>> = pair.chars().map(letter_to_index).collect();
+            invalid[i] = match indices.as_slice() {
+                [Some(first), Some(second)] => {
+                    first == second || usage[*first] > 1 || usage[*second] > 1
+                }
+                _ => true,
+            };
+        }
+        invalid
+    }
+
+    /// Rendert den Editor für eine eigene Reflektor-Verdrahtung
+    fn render_reflector_editor(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(
+            &mut self.use_custom_reflector,
+            "Eigene Verdrahtung (13 Paare)",
+        );
+        if !self.use_custom_reflector {
+            return;
+        }
+
+        let invalid = self.invalid_reflector_pairs();
+        ui.horizontal_wrapped(|ui| {
+            for (i, pair) in self.custom_reflector_pairs.iter_mut().enumerate() {
+                let color = if invalid[i] {
+                    egui::Color32::from_rgb(220, 20, 60)
+                } else {
+                    ui.visuals().text_color()
+                };
+                ui.add(
+                    egui::TextEdit::singleline(pair)
+                        .char_limit(2)
+                        .desired_width(28.0)
+                        .text_color(color),
+                );
+            }
+        });
+
+        match Reflector::from_pairs(&self.custom_reflector_pairs.join(" ")) {
+            Ok(_) => {
+                ui.label(
+                    egui::RichText::new("✅ Gültige Involution")
+                        .color(egui::Color32::from_rgb(0, 128, 0)),
+                );
+            }
+            Err(e) => {
+                ui.label(
+                    egui::RichText::new(format!("❌ {}", e))
+                        .color(egui::Color32::from_rgb(220, 20, 60)),
+                );
+            }
+        }
+    }
+
+    /// Verarbeitet einen Klick auf eine Buchse des grafischen Steckerbretts
+    ///
+    /// Ein verbundener Buchstabe wird ausgesteckt; sonst werden zwei
+    /// nacheinander angeklickte Buchstaben verbunden.
+    ///
+    /// # Arguments
+    /// * `plugboard` - Das aus `plugboard_connections` gelesene Steckerbrett
+    /// * `letter` - Der angeklickte Buchstabe
+    /// * `now` - Die aktuelle Zeit der Oberfläche (für die rote Markierung)
+    fn handle_plug_click(&mut self, mut plugboard: Plugboard, letter: char, now: f64) {
+        if let Some(partner) = plugboard.partner_of(letter) {
+            if plugboard.remove_connection(letter).is_ok() {
+                self.add_log_entry(
+                    Level::Info,
+                    &format!("Steckerkabel entfernt: {} <-> {}", letter, partner),
+                );
+            }
+            self.plug_selection = None;
+        } else {
+            match self.plug_selection.take() {
+                None if plugboard.get_connection_count() == plugboard.get_max_connections() => {
+                    // Kein Kabel mehr frei: Hinweis steht über dem Steckerbrett
+                    self.plug_flash = Some((letter, now + PLUG_FLASH_SECONDS));
+                    return;
+                }
+                None => {
+                    self.plug_selection = Some(letter);
+                    return;
+                }
+                Some(selected) if selected == letter => return,
+                Some(selected) => match plugboard.add_connection(selected, letter) {
+                    Ok(()) => self.add_log_entry(
+                        Level::Info,
+                        &format!("Steckerkabel gesteckt: {} <-> {}", selected, letter),
+                    ),
+                    Err(e) => {
+                        self.plug_flash = Some((letter, now + PLUG_FLASH_SECONDS));
+                        self.add_log_entry(
+                            Level::Warn,
+                            &format!("Steckerkabel {} <-> {} abgelehnt: {}", selected, letter, e),
+                        );
+                        return;
+                    }
+                },
+            }
+        }
+
+        self.plugboard_connections = plugboard.get_connections_string();
+    }
+
+    /// Rendert das grafische Steckerbrett mit 26 anklickbaren Buchsen
+    fn render_plugboard_board(&mut self, ui: &mut egui::Ui) {
+        let plugboard = match Plugboard::from_string(&self.plugboard_connections) {
+            Ok(plugboard) => plugboard,
+            Err(e) => {
+                let cables = self.plugboard_connections.split_whitespace().count();
+                let message = if cables > DEFAULT_MAX_CONNECTIONS
+                    && Plugboard::from_string_with_max(&self.plugboard_connections, cables).is_ok()
+                {
+                    format!(
+                        "❌ {} Kabel eingetragen, es gibt aber nur {} – bitte {} entfernen",
+                        cables,
+                        DEFAULT_MAX_CONNECTIONS,
+                        cables - DEFAULT_MAX_CONNECTIONS
+                    )
+                } else {
+                    format!("❌ {}", e)
+                };
+                ui.label(egui::RichText::new(message).color(egui::Color32::from_rgb(220, 20, 60)));
+                return;
+            }
+        };
+
+        let used = plugboard.get_connection_count();
+        let max = plugboard.get_max_connections();
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("{} / {} Kabel benutzt", used, max)).strong());
+            if used == max {
+                ui.label(
+                    egui::RichText::new(
+                        "⚠️ Alle Kabel gesteckt – zum Verbinden erst ein Kabel entfernen",
+                    )
+                    .color(egui::Color32::from_rgb(255, 165, 0)),
+                );
+            }
+        });
+
+        let now = ui.input(|i| i.time);
+        let flashing = match self.plug_flash {
+            Some((letter, until)) if now < until => {
+                ui.ctx().request_repaint();
+                Some(letter)
+            }
+            _ => None,
+        };
+
+        let mut clicked = None;
+        for row in KEYBOARD_ROWS {
+            ui.horizontal(|ui| {
+                for letter in row.chars() {
+                    let partner = plugboard.partner_of(letter);
+                    let connected = partner.is_some();
+                    let label = match partner {
+                        Some(partner) => format!("{}-{}", letter, partner),
+                        None => letter.to_string(),
+                    };
+
+                    let fill = if flashing == Some(letter) {
+                        egui::Color32::from_rgb(220, 20, 60)
+                    } else if self.plug_selection == Some(letter) {
+                        egui::Color32::from_rgb(255, 165, 0)
+                    } else if connected {
+                        egui::Color32::from_rgb(70, 130, 180)
+                    } else {
+                        ui.visuals().widgets.inactive.weak_bg_fill
+                    };
+
+                    let button = egui::Button::new(egui::RichText::new(label).monospace())
+                        .fill(fill)
+                        .min_size(egui::vec2(36.0, 24.0));
+                    if ui.add(button).clicked() {
+                        clicked = Some(letter);
+                    }
+                }
+            });
+        }
+
+        if let Some(letter) = clicked {
+            self.handle_plug_click(plugboard, letter, now);
+        }
+    }
+
+    /// Speichert die aktuelle Maschinenkonfiguration als JSON
+    fn save_configuration(&mut self) {
+        let Some(machine) = &self.machine else {
+            self.add_log_entry(Level::Error, "Enigma-Maschine ist nicht initialisiert");
+            return;
+        };
+
+        let result = machine
+            .to_config()
+            .to_json()
+            .and_then(|json| std::fs::write(CONFIG_FILE, json).map_err(|e| e.to_string()));
+
+        match result {
+            Ok(()) => self.add_log_entry(
+                Level::Info,
+                &format!("Konfiguration gespeichert in {}", CONFIG_FILE),
+            ),
+            Err(e) => self.add_log_entry(
+                Level::Error,
+                &format!("Fehler beim Speichern der Konfiguration: {}", e),
+            ),
+        }
+    }
+
+    /// Prüft, ob eine geladene Konfiguration in der Oberfläche darstellbar ist
+    fn check_loadable(&self, config: MachineConfig) -> Result<MachineConfig, String> {
+        if config.rotor_types.len() != 3 {
+            return Err(format!(
+                "Die Oberfläche unterstützt nur drei Rotoren, die Konfiguration enthält {}",
+                config.rotor_types.len()
+            ));
+        }
+        if self.allow_duplicate_rotors {
+            factory::from_config_allowing_duplicates(&config).map(|_| config)
+        } else {
+            factory::from_config(&config).map(|_| config)
+        }
+    }
+
+    /// Lädt eine Maschinenkonfiguration aus JSON
+    fn load_configuration(&mut self) {
+        let result = std::fs::read_to_string(CONFIG_FILE)
+            .map_err(|e| e.to_string())
+            .and_then(|json| MachineConfig::from_json(&json))
+            .and_then(|config| self.check_loadable(config));
+
+        match result {
+            Ok(config) => {
+                self.apply_config(&config);
+                self.add_log_entry(
+                    Level::Info,
+                    &format!("Konfiguration geladen aus {}", CONFIG_FILE),
+                );
+            }
+            Err(e) => self.add_log_entry(
+                Level::Error,
+                &format!("Fehler beim Laden der Konfiguration: {}", e),
+            ),
+        }
+    }
+
+    /// Lädt den Tagesschlüssel aus einer Zeile eines Schlüsselblatts
+    fn load_keysheet_line(&mut self) {
+        match MachineConfig::from_keysheet_line(&self.keysheet_line)
+            .and_then(|config| self.check_loadable(config))
+        {
+            Ok(config) => {
+                self.apply_config(&config);
+                self.add_log_entry(Level::Info, "Tagesschlüssel aus Schlüsselzeile geladen");
+            }
+            Err(e) => self.add_log_entry(
+                Level::Error,
+                &format!("Fehler beim Laden der Schlüsselzeile: {}", e),
+            ),
+        }
+    }
+
+    /// Übernimmt eine Konfiguration in die Eingabefelder und baut die Maschine neu
+    fn apply_config(&mut self, config: &MachineConfig) {
+        for i in 0..3 {
+            self.rotor_types[i] = config.rotor_types[i].clone();
+            self.rotor_positions[i] = config.rotor_positions[i].to_string();
+            self.ring_settings[i] = config.ring_settings[i].to_string();
+        }
+        self.reflector_type = config.reflector_type.clone();
+        self.plugboard_connections = config.plugboard.clone();
+        self.use_custom_reflector = false;
+        self.initialize_machine();
     }
 
     /// Fügt einen Log-Eintrag hinzu
     fn add_log_entry(&mut self, level: Level, message: &str) {
         let entry = LogEntry {
             level,
+            target: module_path!().to_string(),
             message: message.to_string(),
             timestamp: chrono::Utc::now(),
         };
@@ -349,18 +844,243 @@ impl EnigmaApp {
 
         // Jetzt die Maschine verwenden
         let machine = self.machine.as_mut().unwrap();
-        let result = match self.operation_mode {
-            OperationMode::Encrypt => machine.encrypt(&clean_input),
-            OperationMode::Decrypt => machine.decrypt(&clean_input),
+        let before = machine.get_rotor_positions();
+        let (result, elapsed) = if self.measure_timing {
+            // Ver- und Entschlüsselung sind dieselbe Operation
+            let (result, elapsed) = machine.encrypt_timed(&clean_input);
+            (result, Some(elapsed))
+        } else {
+            let result = match self.operation_mode {
+                OperationMode::Encrypt => machine.encrypt(&clean_input),
+                OperationMode::Decrypt => machine.decrypt(&clean_input),
+            };
+            (result, None)
         };
+        self.record_positions(before);
 
-        self.output_text = result;
+        if let Some(elapsed) = elapsed {
+            let seconds = elapsed.as_secs_f64();
+            let throughput = if seconds > 0.0 {
+                format!("{:.0} Zeichen/s", clean_input.len() as f64 / seconds)
+            } else {
+                "zu schnell zum Messen".to_string()
+            };
+            self.add_log_entry(
+                Level::Info,
+                &format!(
+                    "Laufzeit: {} Zeichen in {:.3} ms ({})",
+                    clean_input.len(),
+                    seconds * 1000.0,
+                    throughput
+                ),
+            );
+        }
+
+        self.output_raw = clean_text(&result);
+        self.refresh_output_display();
         self.add_log_entry(
             Level::Info,
             &format!("Verarbeitung abgeschlossen: '{}'", self.output_text),
         );
     }
 
+    /// Verschlüsselt einen einzelnen Tastendruck im Schreibmaschinen-Modus
+    ///
+    /// Die Maschine dreht dabei um einen Schritt weiter; Taste und Lampe
+    /// werden an Eingabe- und Ausgabefeld angehängt.
+    fn type_letter(&mut self, letter: char) {
+        let Some(machine) = self.machine.as_mut() else {
+            self.add_log_entry(Level::Error, "Enigma-Maschine ist nicht initialisiert");
+            return;
+        };
+
+        let before = machine.get_rotor_positions();
+        let Some(lamp) = machine.encrypt_char(letter) else {
+            return;
+        };
+        let positions: String = machine.get_rotor_positions().into_iter().collect();
+        self.record_positions(before);
+
+        self.input_text.push(letter);
+        self.output_raw.push(lamp);
+        self.refresh_output_display();
+        self.lit_lamp = Some(lamp);
+
+        self.add_log_entry(
+            Level::Debug,
+            &format!("Taste {} -> Lampe {} (Walzen: {})", letter, lamp, positions),
+        );
+    }
+
+    /// Merkt sich die Walzenstellung vor einer Verarbeitung
+    ///
+    /// Eine neue Verarbeitung verwirft die Schritte, die mit "Schritt vor"
+    /// wiederhergestellt werden könnten.
+    fn record_positions(&mut self, positions: Vec<char>) {
+        if self.undo_positions.len() == POSITION_HISTORY_LIMIT {
+            self.undo_positions.pop_front();
+        }
+        self.undo_positions.push_back(positions);
+        self.redo_positions.clear();
+    }
+
+    /// Stellt die Walzen auf die Stellung vor der letzten Verarbeitung zurück
+    fn step_back(&mut self) {
+        let Some(machine) = self.machine.as_mut() else {
+            return;
+        };
+        let Some(previous) = self.undo_positions.pop_back() else {
+            return;
+        };
+
+        self.redo_positions.push(machine.get_rotor_positions());
+        machine.set_rotor_positions(&previous);
+        self.add_log_entry(
+            Level::Info,
+            &format!(
+                "Schritt zurück: Walzen auf {}",
+                previous.iter().collect::<String>()
+            ),
+        );
+    }
+
+    /// Stellt eine mit "Schritt zurück" verlassene Walzenstellung wieder her
+    fn step_forward(&mut self) {
+        let Some(machine) = self.machine.as_mut() else {
+            return;
+        };
+        let Some(next) = self.redo_positions.pop() else {
+            return;
+        };
+
+        self.undo_positions.push_back(machine.get_rotor_positions());
+        machine.set_rotor_positions(&next);
+        self.add_log_entry(
+            Level::Info,
+            &format!(
+                "Schritt vor: Walzen auf {}",
+                next.iter().collect::<String>()
+            ),
+        );
+    }
+
+    /// Rendert den Schreibmaschinen-Modus mit Lampenfeld
+    fn render_typewriter(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("⌨️ Schreibmaschinen-Modus").size(16.0));
+                ui.checkbox(&mut self.typewriter_mode, "Aktiv");
+            });
+            if !self.typewriter_mode {
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Taste:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.typewriter_buffer)
+                        .desired_width(60.0)
+                        .hint_text("A-Z"),
+                );
+            });
+
+            let typed = clean_text(&std::mem::take(&mut self.typewriter_buffer));
+            for letter in typed.chars() {
+                self.type_letter(letter);
+            }
+
+            ui.add_space(5.0);
+            for row in KEYBOARD_ROWS {
+                ui.horizontal(|ui| {
+                    for letter in row.chars() {
+                        let lit = self.lit_lamp == Some(letter);
+                        let (fill, text) = if lit {
+                            (egui::Color32::from_rgb(255, 215, 0), egui::Color32::BLACK)
+                        } else {
+                            (egui::Color32::from_rgb(40, 40, 40), egui::Color32::GRAY)
+                        };
+                        egui::Frame::none()
+                            .fill(fill)
+                            .rounding(12.0)
+                            .inner_margin(egui::Margin::symmetric(8.0, 4.0))
+                            .show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new(letter.to_string())
+                                        .monospace()
+                                        .strong()
+                                        .color(text),
+                                );
+                            });
+                    }
+                });
+            }
+
+            if let Some(machine) = &self.machine {
+                let positions: Vec<String> = machine
+                    .get_rotor_positions()
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect();
+                ui.label(format!("Walzenstellung: {}", positions.join(" ")));
+            }
+        });
+    }
+
+    /// Zeichnet das Walzenfenster eines Rotors wie am Originalgerät
+    ///
+    /// Über und unter dem aktuellen Buchstaben sind die benachbarten
+    /// Buchstaben des Walzenrings angedeutet.
+    fn render_rotor_window(ui: &mut egui::Ui, position: char, color: egui::Color32) {
+        let index = position as u8 - b'A';
+        let previous = (b'A' + (index + 25) % 26) as char;
+        let next = (b'A' + (index + 1) % 26) as char;
+
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgb(40, 40, 40))
+            .rounding(4.0)
+            .inner_margin(egui::Margin::symmetric(10.0, 2.0))
+            .show(ui, |ui| {
+                ui.set_width(28.0);
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        egui::RichText::new(previous.to_string())
+                            .monospace()
+                            .size(10.0)
+                            .color(egui::Color32::DARK_GRAY),
+                    );
+                    egui::Frame::none()
+                        .fill(egui::Color32::from_rgb(240, 230, 200))
+                        .rounding(2.0)
+                        .inner_margin(egui::Margin::symmetric(6.0, 2.0))
+                        .stroke(egui::Stroke::new(1.5, color))
+                        .show(ui, |ui| {
+                            ui.label(
+                                egui::RichText::new(position.to_string())
+                                    .monospace()
+                                    .strong()
+                                    .size(18.0)
+                                    .color(egui::Color32::BLACK),
+                            );
+                        });
+                    ui.label(
+                        egui::RichText::new(next.to_string())
+                            .monospace()
+                            .size(10.0)
+                            .color(egui::Color32::DARK_GRAY),
+                    );
+                });
+            });
+    }
+
+    /// Formatiert das gespeicherte Ergebnis für die Anzeige (gruppiert oder fortlaufend)
+    fn refresh_output_display(&mut self) {
+        self.output_text = if self.group_output {
+            group_text(&self.output_raw, 5)
+        } else {
+            self.output_raw.clone()
+        };
+    }
+
     /// Rendert die Hauptkonfigurationsseite
     fn render_config_panel(&mut self, ui: &mut egui::Ui) {
         egui::ScrollArea::vertical()
@@ -380,6 +1100,7 @@ impl EnigmaApp {
                 ui.add_space(10.0);
 
                 // Rotor-Auswahl
+                let mut rotors_changed = false;
                 ui.group(|ui| {
                     ui.label(egui::RichText::new("🌀 Rotoren").size(16.0));
                     ui.horizontal(|ui| {
@@ -398,18 +1119,48 @@ impl EnigmaApp {
                                 egui::ComboBox::from_id_source(format!("rotor_{}", i))
                                     .selected_text(&self.rotor_types[i])
                                     .show_ui(ui, |ui| {
-                                        for rotor in ["I", "II", "III", "IV", "V"] {
-                                            ui.selectable_value(
+                                        for rotor in Self::selectable_rotors() {
+                                            let mut response = ui.selectable_value(
                                                 &mut self.rotor_types[i],
                                                 rotor.to_string(),
                                                 rotor,
                                             );
+                                            if let Some(info) = rotors::info(rotor) {
+                                                response = response
+                                                    .on_hover_text(Self::rotor_info_text(&info));
+                                            }
+                                            rotors_changed |= response.changed();
                                         }
                                     });
                             });
                         }
                     });
+
+                    ui.checkbox(
+                        &mut self.allow_duplicate_rotors,
+                        "Mehrfach gewählte Rotoren erlauben (nicht historisch)",
+                    );
+                    if let Some(name) = factory::duplicate_rotor(&self.rotor_types) {
+                        let (color, text) = if self.allow_duplicate_rotors {
+                            (
+                                egui::Color32::from_rgb(255, 165, 0),
+                                format!(
+                                    "⚠️ Rotor {} ist mehrfach gewählt – an einer echten Maschine unmöglich",
+                                    name
+                                ),
+                            )
+                        } else {
+                            (
+                                egui::Color32::from_rgb(220, 20, 60),
+                                format!("⚠️ Rotor {} ist mehrfach gewählt", name),
+                            )
+                        };
+                        ui.label(egui::RichText::new(text).color(color));
+                    }
                 });
+                if rotors_changed {
+                    self.swap_rotors();
+                }
 
                 ui.add_space(10.0);
 
@@ -436,11 +1187,7 @@ impl EnigmaApp {
                                         .color(rotor_colors[i])
                                         .size(12.0),
                                 );
-                                let pos_edit =
-                                    egui::TextEdit::singleline(&mut self.rotor_positions[i])
-                                        .char_limit(1)
-                                        .desired_width(60.0);
-                                ui.add(pos_edit);
+                                Self::render_setting_field(ui, &mut self.rotor_positions[i]);
                             });
                         }
                     });
@@ -471,11 +1218,7 @@ impl EnigmaApp {
                                         .color(rotor_colors[i])
                                         .size(12.0),
                                 );
-                                let ring_edit =
-                                    egui::TextEdit::singleline(&mut self.ring_settings[i])
-                                        .char_limit(1)
-                                        .desired_width(60.0);
-                                ui.add(ring_edit);
+                                Self::render_setting_field(ui, &mut self.ring_settings[i]);
                             });
                         }
                     });
@@ -489,7 +1232,7 @@ impl EnigmaApp {
                     egui::ComboBox::from_id_source("reflector")
                         .selected_text(&self.reflector_type)
                         .show_ui(ui, |ui| {
-                            for reflector in ["A", "B", "C"] {
+                            for reflector in Self::selectable_reflectors() {
                                 ui.selectable_value(
                                     &mut self.reflector_type,
                                     reflector.to_string(),
@@ -497,6 +1240,7 @@ impl EnigmaApp {
                                 );
                             }
                         });
+                    self.render_reflector_editor(ui);
                 });
 
                 ui.add_space(10.0);
@@ -519,6 +1263,15 @@ impl EnigmaApp {
                         .color(egui::Color32::GRAY),
                     );
                     ui.text_edit_multiline(&mut self.plugboard_connections);
+                    ui.add_space(5.0);
+                    ui.label(
+                        egui::RichText::new(
+                            "Zwei Buchstaben anklicken zum Verbinden, verbundenen Buchstaben anklicken zum Trennen",
+                        )
+                        .italics()
+                        .color(egui::Color32::GRAY),
+                    );
+                    self.render_plugboard_board(ui);
                 });
 
                 ui.add_space(15.0);
@@ -544,12 +1297,16 @@ impl EnigmaApp {
                     ui.add_space(10.0);
 
                     if ui
-                        .add(
+                        .add_enabled(
+                            self.setting_fields_valid(),
                             egui::Button::new(
                                 egui::RichText::new("✅ Konfiguration anwenden").size(16.0),
                             )
                             .fill(egui::Color32::from_rgb(0, 128, 0)),
                         )
+                        .on_disabled_hover_text(
+                            "Zuerst die rot markierten Positionen und Ringstellungen korrigieren",
+                        )
                         .clicked()
                     {
                         self.initialize_machine();
@@ -638,6 +1395,8 @@ impl EnigmaApp {
                 if ui.button("🗑️ Löschen").clicked() {
                     self.input_text.clear();
                 }
+
+                ui.checkbox(&mut self.measure_timing, "⏱ Laufzeit messen");
             });
         });
 
@@ -684,15 +1443,49 @@ impl EnigmaApp {
                 if ui.button("📋 Kopieren").clicked() {
                     ui.output_mut(|o| o.copied_text = self.output_text.clone());
                 }
+
+                if ui
+                    .checkbox(&mut self.group_output, "Gruppiert anzeigen")
+                    .changed()
+                {
+                    self.refresh_output_display();
+                }
             });
+
+            if !self.output_raw.is_empty() {
+                ui.collapsing("📊 Buchstabenverteilung", |ui| {
+                    ui.label(
+                        egui::RichText::new(format_histogram(
+                            &letter_histogram(&self.output_raw),
+                            30,
+                        ))
+                        .monospace()
+                        .size(11.0),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Koinzidenzindex: {:.4} (Zufallstext ≈ 0,038, Englisch ≈ 0,067)",
+                            index_of_coincidence(&self.output_raw)
+                        ))
+                        .color(egui::Color32::GRAY),
+                    );
+                });
+            }
         });
 
         ui.add_space(15.0);
 
+        self.render_typewriter(ui);
+
+        ui.add_space(15.0);
+
         // Aktuelle Rotorpositionen anzeigen
+        // `Some(false)` = Schritt zurück, `Some(true)` = Schritt vor
+        let mut history_step = None;
         if let Some(machine) = &self.machine {
             ui.group(|ui| {
                 ui.label(egui::RichText::new("🌀 Aktuelle Rotorpositionen").size(16.0));
+                let rotor_types = machine.get_rotor_types();
                 let positions = machine.get_rotor_positions();
                 let ring_settings = machine.get_ring_settings();
 
@@ -706,15 +1499,11 @@ impl EnigmaApp {
 
                         ui.vertical(|ui| {
                             ui.label(
-                                egui::RichText::new(format!("Rotor {}", i + 1))
+                                egui::RichText::new(format!("Rotor {}", rotor_types[i]))
                                     .color(rotor_colors[i])
                                     .size(12.0),
                             );
-                            ui.label(
-                                egui::RichText::new(format!("Pos: {}", positions[i]))
-                                    .color(rotor_colors[i])
-                                    .size(14.0),
-                            );
+                            Self::render_rotor_window(ui, positions[i], rotor_colors[i]);
                             ui.label(
                                 egui::RichText::new(format!("Ring: {}", ring_settings[i]))
                                     .color(egui::Color32::GRAY)
@@ -723,8 +1512,83 @@ impl EnigmaApp {
                         });
                     }
                 });
+
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Tastendrücke seit Zurücksetzen: {}",
+                        machine.get_step_count()
+                    ))
+                    .color(egui::Color32::GRAY)
+                    .size(12.0),
+                );
+                if let Some(steps) = machine.next_turnover_in() {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Mittlerer Rotor dreht in {} Tastendrücken",
+                            steps
+                        ))
+                        .color(egui::Color32::GRAY)
+                        .size(12.0),
+                    );
+                }
+
+                if machine.will_step_middle() {
+                    let text = if machine.will_step_left() {
+                        "⟳ Nächster Tastendruck dreht mittleren und linken Rotor"
+                    } else {
+                        "⟳ Nächster Tastendruck dreht den mittleren Rotor"
+                    };
+                    ui.label(
+                        egui::RichText::new(text)
+                            .color(egui::Color32::from_rgb(255, 165, 0))
+                            .size(12.0),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !self.undo_positions.is_empty(),
+                            egui::Button::new("◀ Schritt zurück"),
+                        )
+                        .clicked()
+                    {
+                        history_step = Some(false);
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.redo_positions.is_empty(),
+                            egui::Button::new("▶ Schritt vor"),
+                        )
+                        .clicked()
+                    {
+                        history_step = Some(true);
+                    }
+                });
+
+                ui.collapsing("🔌 Verdrahtung", |ui| {
+                    egui::Grid::new("wiring_grid").striped(true).show(ui, |ui| {
+                        ui.label("");
+                        ui.monospace("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+                        ui.end_row();
+                        for rotor in &machine.rotors {
+                            ui.label(format!("Rotor {}", rotor.name));
+                            ui.monospace(rotor.wiring_string());
+                            ui.end_row();
+                        }
+                        ui.label(format!("Reflektor {}", machine.reflector.name));
+                        ui.monospace(machine.reflector.wiring_string());
+                        ui.end_row();
+                    });
+                });
             });
         }
+
+        match history_step {
+            Some(false) => self.step_back(),
+            Some(true) => self.step_forward(),
+            None => {}
+        }
     }
 
     /// Rendert das Log-Panel
@@ -766,13 +1630,14 @@ impl EnigmaApp {
                 let filtered_entries: Vec<_> = if self.log_filter.is_empty() {
                     self.log_entries.iter().collect()
                 } else {
+                    // Der Filter greift auch auf das Target, so zeigt z.B.
+                    // "turnover" nur die Überträge der Rotoren
+                    let filter = self.log_filter.to_lowercase();
                     self.log_entries
                         .iter()
                         .filter(|entry| {
-                            entry
-                                .message
-                                .to_lowercase()
-                                .contains(&self.log_filter.to_lowercase())
+                            entry.message.to_lowercase().contains(&filter)
+                                || entry.target.to_lowercase().contains(&filter)
                         })
                         .collect()
                 };
@@ -791,14 +1656,14 @@ impl EnigmaApp {
                     ui.horizontal(|ui| {
                         ui.label(egui::RichText::new(icon).size(12.0));
                         ui.label(
-                            egui::RichText::new(&format!(
+                            egui::RichText::new(format!(
                                 "[{}]",
                                 entry.timestamp.format("%H:%M:%S")
                             ))
                             .color(egui::Color32::GRAY),
                         );
                         ui.label(
-                            egui::RichText::new(&format!("{}: {}", entry.level, entry.message))
+                            egui::RichText::new(format!("{}: {}", entry.level, entry.message))
                                 .color(color),
                         );
                     });
@@ -822,14 +1687,25 @@ impl EnigmaApp {
 
 impl eframe::App for EnigmaApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.drain_log_sink();
+
         // Verbesserte Menüleiste
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("📁 Datei", |ui| {
                     if ui.button("💾 Konfiguration speichern").clicked() {
+                        self.save_configuration();
                         ui.close_menu();
                     }
                     if ui.button("📂 Konfiguration laden").clicked() {
+                        self.load_configuration();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.label("Schlüsselzeile (Walzenlage | Ringstellung | Stecker):");
+                    ui.text_edit_singleline(&mut self.keysheet_line);
+                    if ui.button("📋 Schlüsselzeile laden").clicked() {
+                        self.load_keysheet_line();
                         ui.close_menu();
                     }
                     ui.separator();
@@ -935,6 +1811,16 @@ impl eframe::App for EnigmaApp {
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if let Some(machine) = &self.machine {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Fingerprint: {}",
+                                machine.configuration_fingerprint()
+                            ))
+                            .monospace()
+                            .color(egui::Color32::GRAY),
+                        );
+                        ui.separator();
+
                         let positions = machine.get_rotor_positions();
                         ui.label(
                             egui::RichText::new(format!(
@@ -953,19 +1839,42 @@ impl eframe::App for EnigmaApp {
     }
 }
 
+/// Gemeinsamer Puffer für Log-Einträge zwischen Logger und Oberfläche
+pub type LogSink = std::sync::Arc<std::sync::Mutex<VecDeque<LogEntry>>>;
+
 /// Benutzerdefinierte Log-Appender für die GUI
 pub struct GuiLogAppender {
-    log_entries: std::sync::Arc<std::sync::Mutex<VecDeque<LogEntry>>>,
+    log_entries: LogSink,
+    forward: Option<Box<dyn log::Log>>,
+}
+
+impl Default for GuiLogAppender {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GuiLogAppender {
     pub fn new() -> Self {
         Self {
             log_entries: std::sync::Arc::new(std::sync::Mutex::new(VecDeque::new())),
+            forward: None,
+        }
+    }
+
+    /// Erstellt einen Appender, der jeden Eintrag zusätzlich an einen
+    /// weiteren Logger (z.B. die Konsole) weiterreicht
+    ///
+    /// # Arguments
+    /// * `forward` - Der zusätzliche Logger
+    pub fn with_forward(forward: Box<dyn log::Log>) -> Self {
+        Self {
+            forward: Some(forward),
+            ..Self::new()
         }
     }
 
-    pub fn get_log_entries(&self) -> std::sync::Arc<std::sync::Mutex<VecDeque<LogEntry>>> {
+    pub fn get_log_entries(&self) -> LogSink {
         self.log_entries.clone()
     }
 }
@@ -976,9 +1885,16 @@ impl log::Log for GuiLogAppender {
     }
 
     fn log(&self, record: &log::Record) {
+        if let Some(forward) = &self.forward {
+            if forward.enabled(record.metadata()) {
+                forward.log(record);
+            }
+        }
+
         if self.enabled(record.metadata()) {
             let entry = LogEntry {
                 level: record.level(),
+                target: record.target().to_string(),
                 message: format!("{}", record.args()),
                 timestamp: chrono::Utc::now(),
             };
@@ -992,5 +1908,9 @@ impl log::Log for GuiLogAppender {
         }
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        if let Some(forward) = &self.forward {
+            forward.flush();
+        }
+    }
 }
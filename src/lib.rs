@@ -3,10 +3,30 @@
 //! This library contains all modules for the Enigma simulator
 //! and allows using the functionality in tests and other
 //! applications.
+//!
+//! # Zählweise von Ringstellung und Position
+//!
+//! Intern zählen Ringstellung und Position von 0 an (`Rotor::ring_setting`,
+//! `Rotor::position`: A = 0 ... Z = 25). Nach außen werden sie als Buchstaben
+//! (A-Z) oder, wie auf den historischen Schlüsselblättern, als Zahlen von 1
+//! an angegeben (A = 01 ... Z = 26). Zahlen in Benutzereingaben
+//! (`utils::parse_setting`, CLI, Schlüsselzeilen) sind immer 1-basiert;
+//! `Rotor::get_ring_setting_number` und `Rotor::get_position_number` liefern
+//! dieselbe Zählweise.
+
+#[macro_use]
+mod logging;
 
+pub mod crack;
+pub mod crypto;
+#[cfg(feature = "gui")]
 pub mod gui;
 pub mod machine;
 pub mod plugboard;
+pub mod procedure;
 pub mod reflector;
 pub mod rotor;
+pub mod session;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,44 @@
+//! Logging macros for the core modules
+//!
+//! With the `logging` feature (default) the macros forward to the `log`
+//! crate. Without it they expand to dead code: the arguments are still
+//! type-checked but never evaluated, so the core builds without `log` and
+//! behaves identically apart from the missing log output.
+
+#[cfg(feature = "logging")]
+macro_rules! log_at {
+    ($level:ident, $($arg:tt)*) => {
+        log::$level!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! log_at {
+    ($level:ident, target: $target:expr, $($arg:tt)*) => {
+        if false {
+            let _ = $target;
+            let _ = format_args!($($arg)*);
+        }
+    };
+    ($level:ident, $($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => { log_at!(trace, $($arg)*) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => { log_at!(debug, $($arg)*) };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { log_at!(info, $($arg)*) };
+}
+
+macro_rules! warn {
+    ($($arg:tt)*) => { log_at!(warn, $($arg)*) };
+}
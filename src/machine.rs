@@ -3,259 +3,2093 @@
 //! This module contains the main logic of the Enigma machine that combines all components
 //! (rotors, reflector, plugboard).
 
-use crate::plugboard::Plugboard;
-use crate::reflector::Reflector;
-use crate::rotor::Rotor;
-use crate::utils::{clean_text, letter_to_index};
-use log::{debug, info, trace};
+use crate::plugboard::{Plugboard, FULL_CONNECTIONS, UHR_POSITIONS};
+use crate::reflector::{reflectors, Reflector, ReflectorType};
+use crate::rotor::{rotors, Rotor, RotorType, Scrambler};
+use crate::utils::{
+    clean_text, decode_base64, encode_base64, fnv1a_64, index_to_letter, letter_to_index,
+    parse_settings,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Log-Target der Übertragsmeldungen
+///
+/// Jede Fortschaltung eines Rotors durch eine Kerbe wird unter diesem Target
+/// auf Stufe `info` protokolliert, z.B. für `RUST_LOG=enigma_rs::turnover=info`
+/// oder den Log-Filter der GUI.
+pub const TURNOVER_LOG_TARGET: &str = "enigma_rs::turnover";
+
+/// Rotoranzahl, bis zu der die Fortschaltung ohne Heap-Allokation auskommt
+const MAX_INLINE_ROTORS: usize = 8;
+
+/// Version des Binärformats von `MachineConfig::to_bytes`
+///
+/// Version 1 kannte noch kein Byte für die Enigma Uhr; solche Daten liest
+/// `from_bytes` weiterhin (als Konfiguration ohne Uhr).
+const BINARY_FORMAT_VERSION: u8 = 2;
+
+/// Wert des Uhr-Bytes im Binärformat, wenn keine Uhr gesteckt ist
+const BINARY_NO_UHR: u8 = 0xff;
+
+/// Bytes für die Nummer der Steckerbrettverdrahtung im Binärformat
+const BINARY_PLUGBOARD_BYTES: usize = 7;
+
+/// Serialisierbare Beschreibung einer Maschinenkonfiguration
+///
+/// Enthält nur die Einstellungen, nicht den internen Zustand der Komponenten,
+/// und kann über `factory::from_config` wieder in eine Maschine umgewandelt werden.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineConfig {
+    /// Die Rotortypen (links, mitte, rechts), z.B. ["I", "II", "III"]
+    pub rotor_types: Vec<String>,
+    /// Die Rotorpositionen (links, mitte, rechts)
+    pub rotor_positions: Vec<char>,
+    /// Die Ringstellungen (links, mitte, rechts)
+    pub ring_settings: Vec<char>,
+    /// Der Reflektortyp ("A", "B" oder "C")
+    pub reflector_type: String,
+    /// Die Steckerbrett-Verbindungen (z.B. "AB CD EF")
+    pub plugboard: String,
+    /// Die Fortschaltmechanik; fehlt sie (ältere Konfigurationen), gelten
+    /// die Schaltklinken
+    #[serde(default)]
+    pub stepping_model: SteppingModel,
+    /// Die Stellung der Enigma Uhr (0-39); fehlt sie (ältere
+    /// Konfigurationen), ist keine Uhr gesteckt
+    #[serde(default)]
+    pub uhr_setting: Option<u8>,
+}
+
+impl MachineConfig {
+    /// Serialisiert die Konfiguration als JSON
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - Das JSON-Dokument oder ein Fehler
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Konfiguration konnte nicht serialisiert werden: {}", e))
+    }
+
+    /// Liest eine Konfiguration aus JSON
+    ///
+    /// # Arguments
+    /// * `json` - Das JSON-Dokument
+    ///
+    /// # Returns
+    /// * `Result<MachineConfig, String>` - Die Konfiguration oder ein Fehler
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Ungültige Konfigurationsdatei: {}", e))
+    }
+
+    /// Serialisiert die Konfiguration in ein kompaktes Binärformat
+    ///
+    /// Aufbau (16 Bytes bei drei Rotoren):
+    /// * 1 Byte: Formatversion (obere 4 Bit) und Rotoranzahl (untere 4 Bit)
+    /// * 2 Bytes pro Rotor: Rotornummer (6 Bit), Ringstellung und Position (je 5 Bit)
+    /// * 1 Byte: Reflektornummer
+    /// * 1 Byte: Stellung der Enigma Uhr (0-39), 0xFF ohne Uhr
+    /// * 7 Bytes: Nummer der Steckerbrettverdrahtung
+    ///
+    /// Rotor- und Reflektornummern sind die Stellen in
+    /// `available_rotor_names` bzw. `available_reflector_names`. Da eine
+    /// Bitmaske der gesteckten Buchstaben nicht verrät, welche Buchstaben
+    /// miteinander verbunden sind, wird die Verdrahtung als laufende Nummer
+    /// unter allen möglichen Steckerbrettern gespeichert. Frei verdrahtete
+    /// Rotoren und Reflektoren sowie der UKW-D lassen sich nicht speichern.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>, String>` - Die Bytes oder ein Fehler bei nicht
+    ///   darstellbaren Einstellungen
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        if self.stepping_model != SteppingModel::default() {
+            return Err(format!(
+                "Fortschaltung {:?} lässt sich nicht kompakt speichern",
+                self.stepping_model
+            ));
+        }
+        let count = self.rotor_types.len();
+        if count == 0 || count > 15 {
+            return Err(format!(
+                "{} Rotoren lassen sich nicht kompakt speichern",
+                count
+            ));
+        }
+        if self.rotor_positions.len() != count || self.ring_settings.len() != count {
+            return Err(
+                "Konfiguration muss gleich viele Rotoren, Positionen und Ringstellungen enthalten"
+                    .to_string(),
+            );
+        }
+
+        let mut bytes = Vec::with_capacity(2 * count + 3 + BINARY_PLUGBOARD_BYTES);
+        bytes.push(BINARY_FORMAT_VERSION << 4 | count as u8);
+
+        let rotor_names = rotors::available_rotor_names();
+        for ((rotor_type, &ring), &position) in self
+            .rotor_types
+            .iter()
+            .zip(&self.ring_settings)
+            .zip(&self.rotor_positions)
+        {
+            let name = rotor_type.parse::<RotorType>()?.to_string();
+            let id = rotor_names
+                .iter()
+                .position(|&known| known == name)
+                .ok_or_else(|| {
+                    format!("Rotor {} lässt sich nicht kompakt speichern", rotor_type)
+                })?;
+            let ring = letter_to_index(ring).ok_or(format!("Ungültige Ringstellung: {}", ring))?;
+            let position = letter_to_index(position)
+                .ok_or(format!("Ungültige Rotorposition: {}", position))?;
+
+            let packed = (id as u16) << 10 | (ring as u16) << 5 | position as u16;
+            bytes.extend_from_slice(&packed.to_be_bytes());
+        }
+
+        let name = self.reflector_type.parse::<ReflectorType>()?.to_string();
+        let reflector_id = reflectors::available_reflector_names()
+            .iter()
+            .position(|&known| known == name)
+            .ok_or_else(|| {
+                format!(
+                    "Reflektor {} lässt sich nicht kompakt speichern",
+                    self.reflector_type
+                )
+            })?;
+        bytes.push(reflector_id as u8);
+
+        let mut plugboard = Plugboard::from_string_with_max(&self.plugboard, FULL_CONNECTIONS)?;
+        // Die Nummer beschreibt die Steckerpaare, nicht die Vertauschung durch die Uhr
+        let rank = rank_plugboard(&plugboard.as_permutation());
+        plugboard.set_uhr_setting(self.uhr_setting)?;
+        bytes.push(self.uhr_setting.unwrap_or(BINARY_NO_UHR));
+
+        bytes.extend_from_slice(&rank.to_be_bytes()[8 - BINARY_PLUGBOARD_BYTES..]);
+
+        Ok(bytes)
+    }
+
+    /// Liest eine Konfiguration aus dem Binärformat von `to_bytes`
+    ///
+    /// # Arguments
+    /// * `bytes` - Die Bytes
+    ///
+    /// # Returns
+    /// * `Result<MachineConfig, String>` - Die Konfiguration oder ein Fehler
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let header = *bytes
+            .first()
+            .ok_or_else(|| "Binärkonfiguration ist leer".to_string())?;
+        let version = header >> 4;
+        if version == 0 || version > BINARY_FORMAT_VERSION {
+            return Err(format!(
+                "Unbekannte Version der Binärkonfiguration: {}",
+                version
+            ));
+        }
+        // Version 1 hat noch kein Uhr-Byte
+        let uhr_bytes = usize::from(version >= 2);
+
+        let count = (header & 0x0f) as usize;
+        let expected = 1 + 2 * count + 1 + uhr_bytes + BINARY_PLUGBOARD_BYTES;
+        if count == 0 || bytes.len() != expected {
+            return Err(format!(
+                "Binärkonfiguration mit {} Rotoren muss {} Bytes lang sein, erhalten: {}",
+                count,
+                expected,
+                bytes.len()
+            ));
+        }
+
+        let rotor_names = rotors::available_rotor_names();
+        let letter = |index: u16| index_to_letter(index as usize);
+        let mut rotor_types = Vec::with_capacity(count);
+        let mut ring_settings = Vec::with_capacity(count);
+        let mut rotor_positions = Vec::with_capacity(count);
+        for pair in bytes[1..1 + 2 * count].chunks(2) {
+            let packed = u16::from_be_bytes([pair[0], pair[1]]);
+            let name = rotor_names
+                .get((packed >> 10) as usize)
+                .ok_or_else(|| format!("Unbekannte Rotornummer: {}", packed >> 10))?;
+            let (Some(ring), Some(position)) = (letter(packed >> 5 & 0x1f), letter(packed & 0x1f))
+            else {
+                return Err("Ringstellung oder Position außerhalb von A-Z".to_string());
+            };
+            rotor_types.push(name.to_string());
+            ring_settings.push(ring);
+            rotor_positions.push(position);
+        }
+
+        let reflector_id = bytes[1 + 2 * count] as usize;
+        let reflector_type = reflectors::available_reflector_names()
+            .get(reflector_id)
+            .ok_or_else(|| format!("Unbekannte Reflektornummer: {}", reflector_id))?
+            .to_string();
+
+        let uhr_setting = match bytes[2 + 2 * count..2 + 2 * count + uhr_bytes] {
+            [setting] if setting != BINARY_NO_UHR => {
+                if setting as usize >= UHR_POSITIONS {
+                    return Err(format!("Ungültige Uhr-Stellung: {}", setting));
+                }
+                Some(setting)
+            }
+            _ => None,
+        };
+
+        let mut rank_bytes = [0u8; 8];
+        rank_bytes[8 - BINARY_PLUGBOARD_BYTES..]
+            .copy_from_slice(&bytes[expected - BINARY_PLUGBOARD_BYTES..]);
+        let plugboard = unrank_plugboard(u64::from_be_bytes(rank_bytes))?;
+
+        Ok(MachineConfig {
+            rotor_types,
+            rotor_positions,
+            ring_settings,
+            reflector_type,
+            plugboard,
+            stepping_model: SteppingModel::default(),
+            uhr_setting,
+        })
+    }
+
+    /// Kodiert die Konfiguration als kurzen, URL-tauglichen Text
+    ///
+    /// Das Binärformat von `to_bytes` in URL-sicherem Base64 (22 Zeichen bei
+    /// drei Rotoren), z.B. zum Teilen per Link oder QR-Code.
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - Der Text oder ein Fehler wie bei `to_bytes`
+    pub fn to_base64(&self) -> Result<String, String> {
+        Ok(encode_base64(&self.to_bytes()?))
+    }
+
+    /// Liest eine Konfiguration aus dem Text von `to_base64`
+    ///
+    /// # Arguments
+    /// * `text` - Der Base64-Text
+    ///
+    /// # Returns
+    /// * `Result<MachineConfig, String>` - Die Konfiguration oder ein Fehler
+    pub fn from_base64(text: &str) -> Result<Self, String> {
+        Self::from_bytes(&decode_base64(text)?)
+    }
+
+    /// Berechnet einen Fingerabdruck der Konfiguration samt Grundstellung
+    ///
+    /// Gedacht zum Erkennen doppelter gespeicherter Konfigurationen: Zwei
+    /// Konfigurationen, die gleich verschlüsseln, haben denselben
+    /// Fingerabdruck. Gehasht wird die kanonische Byte-Darstellung aus
+    /// `to_bytes`, sodass unterschiedliche Schreibweisen (Kleinbuchstaben,
+    /// Reihenfolge der Steckerpaare) nicht ins Gewicht fallen. Lässt sich die
+    /// Konfiguration nicht kompakt speichern (frei verdrahtete Walzen, UKW-D,
+    /// Zahnradantrieb), wird eine normalisierte Textform gehasht.
+    ///
+    /// Im Unterschied zu `EnigmaMachine::configuration_fingerprint` (acht
+    /// Hex-Ziffern, ohne Rotorpositionen, bleibt beim Tippen gleich) hängt
+    /// dieser Fingerabdruck von der Grundstellung ab.
+    ///
+    /// # Returns
+    /// * 16 Hex-Ziffern (8 Bytes), z.B. "9c3e5a0b71d2f846"
+    pub fn state_fingerprint(&self) -> String {
+        let canonical = self.to_bytes().unwrap_or_else(|_| {
+            let upper = |settings: &[char]| -> String {
+                settings.iter().map(|c| c.to_ascii_uppercase()).collect()
+            };
+            let rotor_types: Vec<String> = self
+                .rotor_types
+                .iter()
+                .map(|name| {
+                    name.parse::<RotorType>()
+                        .map(|rotor_type| rotor_type.to_string())
+                        .unwrap_or_else(|_| name.trim().to_string())
+                })
+                .collect();
+            let reflector_type = self
+                .reflector_type
+                .parse::<ReflectorType>()
+                .map(|reflector_type| reflector_type.to_string())
+                .unwrap_or_else(|_| self.reflector_type.trim().to_string());
+            let plugboard = Plugboard::from_string(&self.plugboard)
+                .map(|plugboard| plugboard.get_connections_string())
+                .unwrap_or_else(|_| self.plugboard.clone());
+
+            format!(
+                "{}|{}|{}|{}|{}|{:?}|{:?}",
+                rotor_types.join(","),
+                upper(&self.rotor_positions),
+                upper(&self.ring_settings),
+                reflector_type,
+                plugboard,
+                self.stepping_model,
+                self.uhr_setting
+            )
+            .into_bytes()
+        });
+
+        format!("{:016x}", fnv1a_64(&canonical))
+    }
+
+    /// Beschreibt die Unterschiede zu einer anderen Konfiguration
+    ///
+    /// Für jedes abweichende Feld entsteht eine Zeile im Format
+    /// "Feld: alt → neu". Beim Steckerbrett werden hinzugekommene und
+    /// entfernte Paare aufgeführt, die Schreibweise der Paare (Reihenfolge,
+    /// Groß-/Kleinschreibung) spielt dabei keine Rolle.
+    ///
+    /// # Arguments
+    /// * `other` - Die neue Konfiguration
+    ///
+    /// # Returns
+    /// * Eine Zeile pro Unterschied; leer, wenn beide gleich verschlüsseln
+    pub fn diff(&self, other: &MachineConfig) -> Vec<String> {
+        fn join<T: ToString>(values: &[T]) -> String {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+
+        let mut differences = Vec::new();
+        let mut compare = |field: &str, old: String, new: String| {
+            if old != new {
+                differences.push(format!("{}: {} → {}", field, old, new));
+            }
+        };
+        compare("Rotoren", join(&self.rotor_types), join(&other.rotor_types));
+        compare(
+            "Ringstellungen",
+            join(&self.ring_settings),
+            join(&other.ring_settings),
+        );
+        compare(
+            "Positionen",
+            join(&self.rotor_positions),
+            join(&other.rotor_positions),
+        );
+        compare(
+            "Reflektor",
+            self.reflector_type.clone(),
+            other.reflector_type.clone(),
+        );
+        compare(
+            "Fortschaltung",
+            format!("{:?}", self.stepping_model),
+            format!("{:?}", other.stepping_model),
+        );
+        let uhr = |setting: Option<u8>| {
+            setting.map_or_else(|| "keine".to_string(), |value| value.to_string())
+        };
+        compare("Uhr", uhr(self.uhr_setting), uhr(other.uhr_setting));
+
+        let old_pairs = plugboard_pairs(&self.plugboard);
+        let new_pairs = plugboard_pairs(&other.plugboard);
+        let added: Vec<&String> = new_pairs.difference(&old_pairs).collect();
+        let removed: Vec<&String> = old_pairs.difference(&new_pairs).collect();
+        if !added.is_empty() || !removed.is_empty() {
+            let mut parts = Vec::new();
+            if !added.is_empty() {
+                parts.push(format!("hinzugefügt {}", join(&added)));
+            }
+            if !removed.is_empty() {
+                parts.push(format!("entfernt {}", join(&removed)));
+            }
+            differences.push(format!("Steckerbrett: {}", parts.join(", ")));
+        }
+
+        differences
+    }
+
+    /// Liest einen Tagesschlüssel im Format eines Schlüsselblatts
+    ///
+    /// Grammatik (Felder durch `|` getrennt, Leerraum beliebig):
+    ///
+    /// ```text
+    /// [Tag |] Walzenlage | Ringstellung | Steckerverbindungen
+    /// ```
+    ///
+    /// * `Tag` - optionale Tageszahl, wie sie `genkey` ausgibt; wird ignoriert
+    /// * `Walzenlage` - Rotortypen durch Leerzeichen getrennt, z.B. `III I II`
+    /// * `Ringstellung` - eine Angabe pro Rotor, als Zahlen 01-26 (`14 09 22`,
+    ///   1-basiert wie auf dem Schlüsselblatt: 01 = A) oder Buchstaben (`NIV`)
+    /// * `Steckerverbindungen` - Buchstabenpaare, z.B. `AB CD EF`; darf leer sein
+    ///
+    /// Schlüsselblätter enthalten weder Reflektor noch Grundstellung; es
+    /// werden Reflektor B und die Grundstellung "A" für jeden Rotor gesetzt.
+    ///
+    /// # Arguments
+    /// * `line` - Die Zeile, z.B. "III I II | 14 09 22 | AB CD EF GH IJ KL MN OP QR ST"
+    ///
+    /// # Returns
+    /// * `Result<MachineConfig, String>` - Die Konfiguration oder ein Fehler
+    pub fn from_keysheet_line(line: &str) -> Result<Self, String> {
+        let mut fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        if fields.len() == 4 {
+            if fields[0].parse::<u32>().is_err() {
+                return Err(format!("Ungültige Tagesangabe: '{}'", fields[0]));
+            }
+            fields.remove(0);
+        }
+        let [rotors, rings, plugboard] = fields[..] else {
+            return Err(format!(
+                "Schlüsselzeile muss aus Walzenlage | Ringstellung | Steckerverbindungen bestehen: '{}'",
+                line.trim()
+            ));
+        };
+
+        // Anders als in CLI und GUI ist die Ringstellung hier Pflicht
+        if rings.is_empty() {
+            return Err("Ringstellung fehlt in der Schlüsselzeile".to_string());
+        }
+
+        let mut config = Self::from_settings(rotors, "", rings, "B", plugboard)?;
+        config.plugboard = Plugboard::from_string(plugboard)?.get_connections_string();
+        Ok(config)
+    }
+
+    /// Erstellt eine Konfiguration aus den Textformaten von CLI und GUI
+    ///
+    /// Zentrale Auswertung aller Einstellungen als Text, wie sie auf der
+    /// Kommandozeile oder in den Feldern der Oberfläche stehen. Die Angaben
+    /// werden nur gelesen und in Normalform gebracht; ob sich daraus eine
+    /// Maschine bauen lässt, prüft erst `factory::from_config`. Nur das
+    /// Steckerbrett wird schon hier gegen die historische Grenze von
+    /// `DEFAULT_MAX_CONNECTIONS` Kabeln geprüft. Leere
+    /// Positionen oder Ringstellungen gelten als "A"; wer sie verlangt (wie
+    /// `from_keysheet_line`), muss das vorher selbst prüfen.
+    ///
+    /// # Arguments
+    /// * `rotors` - Die Rotortypen, durch Kommas oder Leerzeichen getrennt (z.B. "I,II,III")
+    /// * `positions` - Die Rotorpositionen (z.B. "ABC" oder "1 2 3"; leer = "A" für jeden Rotor)
+    /// * `rings` - Die Ringstellungen im selben Format (leer = "A" für jeden Rotor)
+    /// * `reflector` - Der Reflektortyp (z.B. "B", "D:<Paare>" oder "CUSTOM:<Verdrahtung>")
+    /// * `plugboard` - Die Steckerverbindungen (z.B. "AB CD EF")
+    ///
+    /// # Returns
+    /// * `Result<MachineConfig, String>` - Die Konfiguration oder ein Fehler
+    ///   bei unbekannten Typen oder falscher Anzahl von Angaben
+    pub fn from_settings(
+        rotors: &str,
+        positions: &str,
+        rings: &str,
+        reflector: &str,
+        plugboard: &str,
+    ) -> Result<Self, String> {
+        let rotor_types = rotors
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|rotor| !rotor.is_empty())
+            .map(|rotor| rotor.parse::<RotorType>().map(|rotor| rotor.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if rotor_types.is_empty() {
+            return Err("Walzenlage fehlt".to_string());
+        }
+
+        let count = rotor_types.len();
+        let parse = |field: &str, label: &str| -> Result<Vec<char>, String> {
+            if field.trim().is_empty() {
+                return Ok(vec!['A'; count]);
+            }
+            let settings = parse_settings(field)?;
+            if settings.len() != count {
+                return Err(format!(
+                    "{} '{}' muss {} Angaben enthalten",
+                    label,
+                    field.trim(),
+                    count
+                ));
+            }
+            Ok(settings)
+        };
+
+        Ok(MachineConfig {
+            rotor_positions: parse(positions, "Grundstellung")?,
+            ring_settings: parse(rings, "Ringstellung")?,
+            rotor_types,
+            reflector_type: reflector.trim().parse::<ReflectorType>()?.to_string(),
+            plugboard: Plugboard::from_string(plugboard).map(|_| plugboard.trim().to_string())?,
+            stepping_model: SteppingModel::default(),
+            uhr_setting: None,
+        })
+    }
+}
+
+/// Normalisiert die Steckerpaare eines Verbindungsstrings ("ba" → "AB")
+fn plugboard_pairs(plugboard: &str) -> BTreeSet<String> {
+    plugboard
+        .split_whitespace()
+        .map(|pair| {
+            let mut letters: Vec<char> = pair.chars().map(|c| c.to_ascii_uppercase()).collect();
+            letters.sort_unstable();
+            letters.into_iter().collect()
+        })
+        .collect()
+}
+
+/// Anzahl der Steckerbretter für `n` Buchstaben (Involutionen, I(n) für n = 0..=26)
+///
+/// I(n) = I(n-1) + (n-1)·I(n-2): der erste Buchstabe bleibt frei oder wird
+/// mit einem der übrigen n-1 Buchstaben verbunden.
+fn plugboard_counts() -> [u64; 27] {
+    let mut counts = [1u64; 27];
+    for n in 2..=26 {
+        counts[n] = counts[n - 1] + (n as u64 - 1) * counts[n - 2];
+    }
+    counts
+}
+
+/// Berechnet die laufende Nummer einer Steckerbrettverdrahtung
+///
+/// Umkehrung von `unrank_plugboard`; die Nummern liegen zwischen 0 (kein
+/// Kabel) und I(26)-1 und passen damit in 49 Bit.
+fn rank_plugboard(permutation: &[usize; 26]) -> u64 {
+    let counts = plugboard_counts();
+    let mut remaining: Vec<usize> = (0..26).collect();
+    let mut rank = 0;
+
+    while !remaining.is_empty() {
+        let n = remaining.len();
+        let first = remaining.remove(0);
+        if let Some(k) = remaining
+            .iter()
+            .position(|&letter| letter == permutation[first])
+        {
+            rank += counts[n - 1] + k as u64 * counts[n - 2];
+            remaining.remove(k);
+        }
+    }
+
+    rank
+}
+
+/// Stellt die Steckerverbindungen zu einer laufenden Nummer wieder her
+///
+/// # Returns
+/// * `Result<String, String>` - Die Verbindungen (z.B. "AB CD") oder ein
+///   Fehler bei einer zu großen Nummer
+fn unrank_plugboard(mut rank: u64) -> Result<String, String> {
+    let counts = plugboard_counts();
+    if rank >= counts[26] {
+        return Err(format!("Ungültige Steckerbrettnummer: {}", rank));
+    }
+
+    let mut remaining: Vec<usize> = (0..26).collect();
+    let mut pairs = Vec::new();
+    while !remaining.is_empty() {
+        let n = remaining.len();
+        let first = remaining.remove(0);
+        if rank < counts[n - 1] {
+            continue;
+        }
+        rank -= counts[n - 1];
+        let partner = remaining.remove((rank / counts[n - 2]) as usize);
+        rank %= counts[n - 2];
+        pairs.push(format!(
+            "{}{}",
+            index_to_letter(first).unwrap_or('A'),
+            index_to_letter(partner).unwrap_or('A')
+        ));
+    }
+
+    Ok(pairs.join(" "))
+}
+
+/// Signalweg eines einzelnen Tastendrucks
+///
+/// Die Buchstaben in `forward` und `backward` stehen in der Reihenfolge, in
+/// der das Signal die Rotoren durchläuft (hin von rechts nach links,
+/// zurück von links nach rechts).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionTrace {
+    /// Die gedrückte Taste
+    pub input: char,
+    /// Der Buchstabe nach dem Steckerbrett (vorwärts)
+    pub after_plugboard: char,
+    /// Der Buchstabe nach jedem Rotor auf dem Hinweg
+    pub forward: Vec<char>,
+    /// Der Buchstabe nach dem Reflektor
+    pub reflected: char,
+    /// Der Buchstabe nach jedem Rotor auf dem Rückweg
+    pub backward: Vec<char>,
+    /// Die aufleuchtende Lampe
+    pub output: char,
+    /// Die Rotorpositionen bei diesem Tastendruck (nach dem Weiterdrehen)
+    pub rotor_positions: Vec<char>,
+}
+
+/// Formatiert den Signalweg als eine Zeile, z.B.
+/// "[A A B] Stecker A→A | Walzen A→C→D→F | UKW F→S | Walzen S→S→E→B | Stecker B→B"
+impl std::fmt::Display for EncryptionTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = |start: char, letters: &[char]| {
+            std::iter::once(start)
+                .chain(letters.iter().copied())
+                .map(String::from)
+                .collect::<Vec<_>>()
+                .join("→")
+        };
+        let last_forward = self.forward.last().copied().unwrap_or(self.after_plugboard);
+        let last_backward = self.backward.last().copied().unwrap_or(self.reflected);
+
+        write!(
+            f,
+            "[{}] Stecker {}→{} | Walzen {} | UKW {}→{} | Walzen {} | Stecker {}→{}",
+            self.rotor_positions
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            self.input,
+            self.after_plugboard,
+            path(self.after_plugboard, &self.forward),
+            last_forward,
+            self.reflected,
+            path(self.reflected, &self.backward),
+            last_backward,
+            self.output
+        )
+    }
+}
+
+/// Mechanik, mit der die Rotoren fortgeschaltet werden
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SteppingModel {
+    /// Schaltklinken der Wehrmachtsmaschinen (Enigma I, M3, M4) mit Doppelschritt
+    #[default]
+    PawlRatchet,
+    /// Zahnradantrieb der Abwehr-Enigma G: Jeder Rotor dreht wie ein
+    /// Zählwerk weiter, wenn sein rechter Nachbar eine Kerbe passiert,
+    /// ohne Doppelschritt
+    Gear,
+}
+
+/// Gruppierung der Ausgabe von `encrypt` und `decrypt` nach Waffengattung
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupingStyle {
+    /// Fünfergruppen wie bei Heer und Luftwaffe
+    Army5,
+    /// Vierergruppen wie bei der Kriegsmarine
+    Navy4,
+    /// Fortlaufender Text ohne Gruppierung
+    None,
+}
+
+impl GroupingStyle {
+    /// Gibt die Gruppengröße zurück (`None` = keine Gruppierung)
+    pub fn group_size(&self) -> Option<usize> {
+        match self {
+            GroupingStyle::Army5 => Some(5),
+            GroupingStyle::Navy4 => Some(4),
+            GroupingStyle::None => None,
+        }
+    }
+}
+
+/// Ereignis für einen einzelnen Abschnitt des Signalwegs
+///
+/// Wird an den mit `EnigmaMachine::set_step_observer` registrierten
+/// Beobachter übergeben, damit z.B. eine Oberfläche den Signalweg ohne
+/// den globalen Logger darstellen kann.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepEvent {
+    /// Der Name des Abschnitts (z.B. "Steckerbrett (vorwärts)", "Rotor III (vorwärts)")
+    pub stage: String,
+    /// Der Buchstabe vor diesem Abschnitt
+    pub input: char,
+    /// Der Buchstabe nach diesem Abschnitt
+    pub output: char,
+    /// Die Rotorpositionen bei diesem Tastendruck
+    pub rotor_positions: Vec<char>,
+}
+
+/// Registrierter Beobachter für `StepEvent`s
+struct StepObserver(Box<dyn FnMut(StepEvent) + Send>);
+
+impl std::fmt::Debug for StepObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StepObserver")
+    }
+}
 
 /// Represents a complete Enigma machine
+///
+/// Die Walzen sind standardmäßig `Rotor`; über den Typparameter lassen sich
+/// eigene Walzen einsetzen, die `Scrambler` implementieren, z.B.
+/// `EnigmaMachine<Box<dyn Scrambler>>` für verwandte Rotormaschinen.
 #[derive(Debug)]
-pub struct EnigmaMachine {
-    /// Die drei Rotoren (links, mitte, rechts)
-    pub rotors: [Rotor; 3],
+pub struct EnigmaMachine<S = Rotor> {
+    /// Die Rotoren von links nach rechts (drei bei Enigma I, vier bei M4)
+    pub rotors: Vec<S>,
     /// Der Reflektor
     pub reflector: Reflector,
     /// Das Steckerbrett
     pub plugboard: Plugboard,
+    /// Gruppengröße der Ausgabe (`None` = keine Gruppierung)
+    output_grouping: Option<usize>,
+    /// Unverschlüsselte Kopfgruppe vor der Ausgabe von `encrypt`
+    header_group: Option<String>,
+    /// Die Rotorpositionen bei der Erstellung (für `reset`)
+    initial_positions: Vec<usize>,
+    /// Optionaler Beobachter für jeden Abschnitt des Signalwegs
+    step_observer: Option<StepObserver>,
+    /// Anzahl der Fortschaltungen seit Erstellung oder `reset`
+    step_count: usize,
+    /// Die Fortschaltmechanik
+    stepping_model: SteppingModel,
 }
 
-impl EnigmaMachine {
+/// Übernimmt Werte rechtsbündig in eine Liste der Länge `len`
+///
+/// Überzählige Werte links entfallen, fehlende werden links mit `fill` ergänzt.
+fn align_right<T: Copy>(values: &[T], len: usize, fill: T) -> Vec<T> {
+    let kept = values.len().min(len);
+    let mut aligned = vec![fill; len - kept];
+    aligned.extend_from_slice(&values[values.len() - kept..]);
+    aligned
+}
+
+/// Zwei Maschinen sind gleich, wenn alle Komponenten, die Ausgabegruppierung
+/// samt Kopfgruppe, die Startpositionen und die Fortschaltmechanik
+/// übereinstimmen. Ein registrierter Beobachter und der Schrittzähler
+/// beeinflussen die Verschlüsselung nicht und werden daher nicht verglichen.
+impl<S: PartialEq> PartialEq for EnigmaMachine<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rotors == other.rotors
+            && self.reflector == other.reflector
+            && self.plugboard == other.plugboard
+            && self.output_grouping == other.output_grouping
+            && self.header_group == other.header_group
+            && self.initial_positions == other.initial_positions
+            && self.stepping_model == other.stepping_model
+    }
+}
+
+impl<S: Eq> Eq for EnigmaMachine<S> {}
+
+/// Erstellt eine unabhängige Kopie aller Komponenten und Einstellungen
+///
+/// Ein registrierter Beobachter lässt sich nicht kopieren; die Kopie
+/// startet ohne Beobachter.
+impl<S: Clone> Clone for EnigmaMachine<S> {
+    fn clone(&self) -> Self {
+        EnigmaMachine {
+            rotors: self.rotors.clone(),
+            reflector: self.reflector.clone(),
+            plugboard: self.plugboard.clone(),
+            output_grouping: self.output_grouping,
+            header_group: self.header_group.clone(),
+            initial_positions: self.initial_positions.clone(),
+            step_observer: None,
+            step_count: self.step_count,
+            stepping_model: self.stepping_model,
+        }
+    }
+}
+
+/// Gibt Modell, eine Zeile pro Rotor und eine Zusammenfassung aus
+impl std::fmt::Display for EnigmaMachine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Modell: {}", self.model_label())?;
+
+        for (i, rotor) in self.rotors.iter().enumerate() {
+            writeln!(f, "Rotor {}: {}", i + 1, rotor)?;
+        }
+
+        let join = |chars: Vec<char>| {
+            chars
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        write!(
+            f,
+            "Rotoren: {}\nRingstellungen: {}\nPositionen: {}\nReflektor: {}\nSteckerbrett: {}",
+            self.rotors
+                .iter()
+                .map(|r| r.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            join(self.get_ring_settings()),
+            join(self.get_rotor_positions()),
+            self.reflector,
+            self.plugboard
+        )
+    }
+}
+
+impl<S: Scrambler> EnigmaMachine<S> {
     /// Erstellt eine neue Enigma-Maschine mit den angegebenen Komponenten
     ///
     /// # Arguments
-    /// * `rotors` - Array der drei Rotoren
+    /// * `rotors` - Die Rotoren von links nach rechts
     /// * `reflector` - Der Reflektor
     /// * `plugboard` - Das Steckerbrett
     ///
     /// # Returns
     /// * Eine neue Enigma-Maschine
-    pub fn new(rotors: [Rotor; 3], reflector: Reflector, plugboard: Plugboard) -> Self {
+    pub fn new(rotors: Vec<S>, reflector: Reflector, plugboard: Plugboard) -> Self {
+        let initial_positions = rotors.iter().map(|r| r.position()).collect();
         EnigmaMachine {
             rotors,
             reflector,
             plugboard,
+            output_grouping: Some(5),
+            header_group: None,
+            initial_positions,
+            step_observer: None,
+            step_count: 0,
+            stepping_model: SteppingModel::default(),
+        }
+    }
+
+    /// Setzt die Rotoren auf die Positionen bei der Erstellung zurück
+    ///
+    /// Die Ringstellungen bleiben unverändert, da sie sich im Betrieb nicht ändern.
+    /// Der Schrittzähler wird auf 0 gesetzt.
+    pub fn reset(&mut self) {
+        for (rotor, &position) in self.rotors.iter_mut().zip(self.initial_positions.iter()) {
+            rotor.set_position(position);
+        }
+        self.step_count = 0;
+        info!(
+            "Rotorpositionen zurückgesetzt auf: {}",
+            self.positions_string()
+        );
+    }
+
+    /// Registriert einen Beobachter, der bei jedem Tastendruck für jeden
+    /// Abschnitt des Signalwegs aufgerufen wird
+    ///
+    /// Der Beobachter muss `Send` sein, damit die Maschine weiterhin an
+    /// andere Threads übergeben werden kann.
+    ///
+    /// # Arguments
+    /// * `observer` - Die Rückruffunktion; ersetzt einen bereits registrierten Beobachter
+    pub fn set_step_observer(&mut self, observer: Box<dyn FnMut(StepEvent) + Send>) {
+        self.step_observer = Some(StepObserver(observer));
+    }
+
+    /// Entfernt den registrierten Beobachter
+    pub fn clear_step_observer(&mut self) {
+        self.step_observer = None;
+    }
+
+    /// Meldet den Signalweg eines Tastendrucks an den Beobachter
+    fn notify_step_observer(&mut self, trace: &EncryptionTrace) {
+        let Some(StepObserver(observer)) = self.step_observer.as_mut() else {
+            return;
+        };
+
+        let mut emit = |stage: String, input: char, output: char| {
+            observer(StepEvent {
+                stage,
+                input,
+                output,
+                rotor_positions: trace.rotor_positions.clone(),
+            })
+        };
+
+        emit(
+            "Steckerbrett (vorwärts)".to_string(),
+            trace.input,
+            trace.after_plugboard,
+        );
+
+        let mut signal = trace.after_plugboard;
+        for (rotor, &output) in self.rotors.iter().rev().zip(trace.forward.iter()) {
+            emit(format!("Rotor {} (vorwärts)", rotor.name()), signal, output);
+            signal = output;
+        }
+
+        emit(
+            format!("Reflektor {}", self.reflector.name),
+            signal,
+            trace.reflected,
+        );
+
+        signal = trace.reflected;
+        for (rotor, &output) in self.rotors.iter().zip(trace.backward.iter()) {
+            emit(
+                format!("Rotor {} (rückwärts)", rotor.name()),
+                signal,
+                output,
+            );
+            signal = output;
+        }
+
+        emit("Steckerbrett (rückwärts)".to_string(), signal, trace.output);
+    }
+
+    /// Setzt die Fortschaltmechanik der Rotoren
+    ///
+    /// # Arguments
+    /// * `model` - Die Mechanik (Standard: `SteppingModel::PawlRatchet`)
+    pub fn set_stepping_model(&mut self, model: SteppingModel) {
+        self.stepping_model = model;
+        debug!("Fortschaltmechanik gesetzt auf {:?}", model);
+    }
+
+    /// Gibt die Fortschaltmechanik der Rotoren zurück
+    pub fn get_stepping_model(&self) -> SteppingModel {
+        self.stepping_model
+    }
+
+    /// Setzt die Gruppierung der Ausgabe von `encrypt` und `decrypt`
+    ///
+    /// # Arguments
+    /// * `group_size` - Buchstaben pro Gruppe, oder `None` für fortlaufenden Text
+    pub fn set_output_grouping(&mut self, group_size: Option<usize>) {
+        self.output_grouping = group_size.filter(|&size| size > 0);
+        info!("Ausgabegruppierung gesetzt auf: {:?}", self.output_grouping);
+    }
+
+    /// Setzt die Gruppierung der Ausgabe nach Waffengattung
+    ///
+    /// Kurzform für `set_output_grouping` mit 5, 4 oder ohne Gruppierung.
+    ///
+    /// # Arguments
+    /// * `style` - Die Gruppierung (Standard: `GroupingStyle::Army5`)
+    pub fn set_grouping_style(&mut self, style: GroupingStyle) {
+        self.set_output_grouping(style.group_size());
+    }
+
+    /// Setzt eine Kopfgruppe, die `encrypt` unverschlüsselt voranstellt
+    ///
+    /// Bildet Spruchköpfe nach, bei denen z.B. eine Kenngruppe als erste
+    /// Gruppe vor dem Geheimtext steht. Die Walzen drehen sich für die
+    /// Kopfgruppe nicht. Nur `encrypt` berücksichtigt sie; `decrypt`
+    /// entfernt sie nicht, sie muss vor dem Entschlüsseln abgetrennt werden.
+    ///
+    /// # Arguments
+    /// * `header` - Die Buchstaben der Kopfgruppe, oder `None` zum Entfernen
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Erfolg oder Fehler bei leerer Gruppe oder Nicht-Buchstaben
+    pub fn set_header_group(&mut self, header: Option<&str>) -> Result<(), String> {
+        self.header_group = match header {
+            Some(group) if group.is_empty() || !group.chars().all(|c| c.is_ascii_alphabetic()) => {
+                return Err(format!(
+                    "Kopfgruppe darf nur aus Buchstaben A-Z bestehen: '{}'",
+                    group
+                ));
+            }
+            Some(group) => Some(group.to_ascii_uppercase()),
+            None => None,
+        };
+        info!("Kopfgruppe gesetzt auf: {:?}", self.header_group);
+        Ok(())
+    }
+
+    /// Gibt die aktuelle Gruppierung der Ausgabe zurück
+    ///
+    /// # Returns
+    /// * Buchstaben pro Gruppe, oder `None` für fortlaufenden Text
+    pub fn get_output_grouping(&self) -> Option<usize> {
+        self.output_grouping
+    }
+
+    /// Prüft, ob nach dem Buchstaben mit dem angegebenen Index eine Gruppe endet
+    fn is_group_end(&self, index: usize) -> bool {
+        match self.output_grouping {
+            Some(size) => index % size == size - 1,
+            None => false,
+        }
+    }
+
+    /// Encrypts a single character
+    ///
+    /// Only the letters A-Z (in either case) are wired through the machine.
+    /// Any other character is rejected without advancing the rotors, so raw
+    /// input such as digits, punctuation or umlauts can be passed in safely.
+    ///
+    /// # Arguments
+    /// * `input` - The character to encrypt
+    ///
+    /// # Returns
+    /// * `Some(letter)` - The encrypted letter (uppercase)
+    /// * `None` - If the character is not a letter A-Z
+    pub fn encrypt_char(&mut self, input: char) -> Option<char> {
+        let index = letter_to_index(input)?;
+
+        if self.step_observer.is_some() {
+            return Some(self.press_key_traced(input).output);
         }
+
+        let output = index_to_letter(self.encrypt_index(index as u8) as usize)?;
+        trace!("Verschlüsselung: {} -> {}", input, output);
+        Some(output)
+    }
+
+    /// Zeigt die Substitution eines Zeichens in der aktuellen Walzenstellung
+    ///
+    /// Achtung: Die echte Enigma dreht die Walzen beim Tastendruck, *bevor*
+    /// die Lampe aufleuchtet. `peek_char` dreht nicht und zeigt daher die
+    /// Substitution vor dem Schritt; das Ergebnis weicht in der Regel von dem
+    /// ab, was `encrypt_char` im selben Zustand liefern würde. Nur zur
+    /// Anzeige gedacht (z.B. Vorschau in der Oberfläche).
+    ///
+    /// # Arguments
+    /// * `input` - Das Eingabezeichen (A-Z, Groß- oder Kleinschreibung)
+    ///
+    /// # Returns
+    /// * Der Ausgabebuchstabe (Großbuchstabe) wie bei `transform_char`;
+    ///   andere Zeichen unverändert
+    pub fn peek_char(&self, input: char) -> char {
+        self.transform_char(input).unwrap_or(input)
+    }
+
+    /// Verschlüsselt einen Buchstabenindex (0-25) inklusive Walzenfortschaltung
+    ///
+    /// Schneller Pfad ohne Umwandlung in Zeichen und ohne Aufzeichnung
+    /// des Signalwegs; liefert dieselben Ergebnisse wie `encrypt_char_traced`.
+    fn encrypt_index(&mut self, input: u8) -> u8 {
+        self.step_rotors();
+        self.transform_index(input)
+    }
+
+    /// Sendet einen Buchstabenindex (0-25) ohne Fortschaltung durch die Maschine
+    #[inline]
+    fn transform_index(&self, input: u8) -> u8 {
+        let mut signal = self.plugboard.process_forward_index(input);
+        for rotor in self.rotors.iter().rev() {
+            signal = rotor.forward_index(signal);
+        }
+        signal = self.reflector.reflect_index(signal);
+        for rotor in &self.rotors {
+            signal = rotor.backward_index(signal);
+        }
+        self.plugboard.process_backward_index(signal)
+    }
+
+    /// Encrypts a single character and records the complete signal path
+    ///
+    /// Like `encrypt_char`, characters other than A-Z are rejected before
+    /// the rotors step.
+    ///
+    /// # Arguments
+    /// * `input` - The character to encrypt
+    ///
+    /// # Returns
+    /// * `Some(trace)` - The trace with every intermediate letter of this keypress
+    /// * `None` - If the character is not a letter A-Z
+    pub fn encrypt_char_traced(&mut self, input: char) -> Option<EncryptionTrace> {
+        letter_to_index(input)?;
+        Some(self.press_key_traced(input))
+    }
+
+    /// Steps the rotors and traces a letter already known to be A-Z
+    fn press_key_traced(&mut self, input: char) -> EncryptionTrace {
+        debug!("=== Verschlüsselung von '{}' ===", input);
+
+        // Rotate rotors (before encryption)
+        self.step_rotors();
+
+        let trace = self.trace_signal(input);
+        self.notify_step_observer(&trace);
+
+        debug!(
+            "=== Verschlüsselung abgeschlossen: {} -> {} ===",
+            input, trace.output
+        );
+        trace
+    }
+
+    /// Transforms a character at the current rotor positions without stepping
+    ///
+    /// Runs the full path plugboard → rotors → reflector → rotors → plugboard,
+    /// but leaves the rotors where they are. Useful for checking the wiring
+    /// math against hand-computed values.
+    ///
+    /// # Arguments
+    /// * `input` - The character to transform
+    ///
+    /// # Returns
+    /// * `Some(letter)` - The substituted letter (uppercase)
+    /// * `None` - If the character is not a letter A-Z
+    pub fn transform_char(&self, input: char) -> Option<char> {
+        let index = letter_to_index(input)?;
+        index_to_letter(self.transform_index(index as u8) as usize)
+    }
+
+    /// Computes the complete substitution at the current rotor positions
+    ///
+    /// Like `transform_char`, the rotors are not stepped: entry `i` is the
+    /// output for the letter with index `i` in the current, frozen state.
+    /// Since the machine is reciprocal, the result is always an involution
+    /// without fixed points.
+    ///
+    /// # Returns
+    /// * The output letter for each input letter A-Z
+    pub fn current_alphabet(&self) -> [char; 26] {
+        let mut alphabet = ['A'; 26];
+        for (index, output) in alphabet.iter_mut().enumerate() {
+            *output = (b'A' + self.transform_index(index as u8)) as char;
+        }
+        alphabet
+    }
+
+    /// Sends a character through all components at the current positions
+    ///
+    /// # Arguments
+    /// * `input` - The character to send
+    ///
+    /// # Returns
+    /// * The trace of the signal path
+    fn trace_signal(&self, input: char) -> EncryptionTrace {
+        // 1. Plugboard (forward)
+        let after_plugboard = self.plugboard.process_forward(input);
+        trace!(
+            "Nach Steckerbrett (vorwärts): {} -> {}",
+            input,
+            after_plugboard
+        );
+
+        // 2. Through the rotors (forward, from the right rotor to the left)
+        let mut signal = after_plugboard;
+        let mut forward = Vec::with_capacity(self.rotors.len());
+        for (i, rotor) in self.rotors.iter().enumerate().rev() {
+            signal = rotor.forward(signal);
+            forward.push(signal);
+            trace!("Nach Rotor {} (vorwärts): {}", i + 1, signal);
+        }
+
+        // 3. Reflektor
+        signal = self.reflector.reflect(signal);
+        let reflected = signal;
+        trace!("Nach Reflektor: {}", signal);
+
+        // 4. Through the rotors (backward, from the left rotor to the right)
+        let mut backward = Vec::with_capacity(self.rotors.len());
+        for (i, rotor) in self.rotors.iter().enumerate() {
+            signal = rotor.backward(signal);
+            backward.push(signal);
+            trace!("Nach Rotor {} (rückwärts): {}", i + 1, signal);
+        }
+
+        // 5. Plugboard (backward)
+        let final_output = self.plugboard.process_backward(signal);
+        trace!(
+            "Nach Steckerbrett (rückwärts): {} -> {}",
+            signal,
+            final_output
+        );
+
+        EncryptionTrace {
+            input,
+            after_plugboard,
+            forward,
+            reflected,
+            backward,
+            output: final_output,
+            rotor_positions: self.get_rotor_positions(),
+        }
+    }
+
+    /// Encrypts a complete text
+    ///
+    /// The output is grouped as set with `set_output_grouping` or
+    /// `set_grouping_style`; a header group set with `set_header_group`
+    /// comes first.
+    ///
+    /// # Arguments
+    /// * `text` - The text to encrypt
+    ///
+    /// # Returns
+    /// * The encrypted text
+    pub fn encrypt(&mut self, text: &str) -> String {
+        info!("Starte Verschlüsselung von: '{}'", text);
+        let clean_input = clean_text(text);
+        info!("Bereinigter Input: '{}'", clean_input);
+
+        let mut result = self.encrypt_clean(&clean_input);
+        if let Some(header) = &self.header_group {
+            result.insert_str(0, &format!("{} ", header));
+        }
+
+        info!("Verschlüsselung abgeschlossen: '{}'", result);
+        result
+    }
+
+    /// Verschlüsselt bereinigten Text (nur A-Z) und gruppiert die Ausgabe
+    ///
+    /// Arbeitet direkt auf Buchstabenindizes; nur mit registriertem
+    /// Beobachter wird der Signalweg über `encrypt_char` aufgezeichnet.
+    fn encrypt_clean(&mut self, clean_input: &str) -> String {
+        let group_spaces = self
+            .output_grouping
+            .map_or(0, |size| clean_input.len() / size);
+        let mut result = String::with_capacity(clean_input.len() + group_spaces);
+
+        for (i, byte) in clean_input.bytes().enumerate() {
+            let encrypted = if self.step_observer.is_some() {
+                self.press_key_traced(byte as char).output
+            } else {
+                (b'A' + self.encrypt_index(byte - b'A')) as char
+            };
+            result.push(encrypted);
+
+            if self.is_group_end(i) {
+                result.push(' ');
+            }
+        }
+
+        result
+    }
+
+    /// Encrypts letter indices (0-25) directly, without converting to text
+    ///
+    /// Each index is one keypress, so the rotors step exactly like with
+    /// `process_exact`. Indices of 26 or more are a caller error: they are
+    /// caught by a debug assertion and are not supported in release builds.
+    ///
+    /// # Arguments
+    /// * `input` - The letter indices (A = 0 ... Z = 25)
+    ///
+    /// # Returns
+    /// * The encrypted letter indices
+    pub fn encrypt_indices(&mut self, input: &[u8]) -> Vec<u8> {
+        input
+            .iter()
+            .map(|&index| {
+                debug_assert!(index < 26, "Buchstabenindex {} außerhalb von 0-25", index);
+                if self.step_observer.is_some() {
+                    let output = self.press_key_traced((b'A' + index) as char).output;
+                    output as u8 - b'A'
+                } else {
+                    self.encrypt_index(index)
+                }
+            })
+            .collect()
+    }
+
+    /// Encrypts a text while keeping its formatting
+    ///
+    /// Only letters advance the rotors and are encrypted (keeping their case);
+    /// spaces, digits and punctuation are copied to the output unchanged and
+    /// no 5-letter grouping is applied. Since encryption and decryption are the
+    /// same operation, this method also decrypts such a text.
+    ///
+    /// # Arguments
+    /// * `text` - The text to encrypt
+    ///
+    /// # Returns
+    /// * The encrypted text with the original formatting
+    pub fn encrypt_preserving(&mut self, text: &str) -> String {
+        info!("Starte formaterhaltende Verschlüsselung von: '{}'", text);
+
+        let result = self.encrypt_keeping_non_letters(text);
+
+        info!("Verschlüsselung abgeschlossen: '{}'", result);
+        result
+    }
+
+    /// Encrypts a multi-line document, keeping its line and paragraph structure
+    ///
+    /// Works like `encrypt_preserving`, applied line by line: line breaks
+    /// (`\n` as well as `\r\n`) and empty lines are copied through without
+    /// stepping the rotors, so a file keeps its layout. The rotors continue
+    /// from one line to the next, i.e. the letters of the whole document form
+    /// one message. Decrypting is the same operation from the same start
+    /// position.
+    ///
+    /// # Arguments
+    /// * `text` - The document, e.g. the content of a text file
+    ///
+    /// # Returns
+    /// * The encrypted document with identical line structure
+    pub fn encrypt_document(&mut self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut lines = 0;
+
+        for line in text.split_inclusive('\n') {
+            result.push_str(&self.encrypt_keeping_non_letters(line));
+            lines += 1;
+        }
+
+        info!(
+            "Dokument mit {} Zeilen verschlüsselt, Walzen jetzt {}",
+            lines,
+            self.positions_string()
+        );
+        result
+    }
+
+    /// Verschlüsselt alle Buchstaben (mit ihrer Schreibweise) und übernimmt
+    /// alle anderen Zeichen unverändert
+    fn encrypt_keeping_non_letters(&mut self, text: &str) -> String {
+        text.chars()
+            .map(|ch| match self.encrypt_char(ch) {
+                Some(encrypted) if ch.is_ascii_lowercase() => encrypted.to_ascii_lowercase(),
+                Some(encrypted) => encrypted,
+                None => ch,
+            })
+            .collect()
+    }
+
+    /// Encrypts a text lazily, one letter per call to `next()`
+    ///
+    /// Non-letters are skipped like in `encrypt`, and a space is emitted after
+    /// every group (see `set_output_grouping`), so collecting the iterator
+    /// yields the same string as `encrypt`.
+    ///
+    /// # Arguments
+    /// * `text` - The text to encrypt
+    ///
+    /// # Returns
+    /// * An iterator over the encrypted characters
+    pub fn encrypt_iter<'a>(&'a mut self, text: &'a str) -> impl Iterator<Item = char> + 'a {
+        let mut index = 0;
+        text.chars()
+            .filter_map(move |ch| {
+                let encrypted = self.encrypt_char(ch)?;
+                let separator = if self.is_group_end(index) {
+                    Some(' ')
+                } else {
+                    None
+                };
+                index += 1;
+                Some(std::iter::once(encrypted).chain(separator))
+            })
+            .flatten()
+    }
+
+    /// Encrypts a stream of characters lazily without grouping
+    ///
+    /// Each character is cleaned, stepped and encrypted only when the returned
+    /// iterator is advanced, so large inputs (e.g. from a `BufReader`) never
+    /// have to be held in memory. Non-letters are dropped.
+    ///
+    /// # Arguments
+    /// * `chars` - The characters to encrypt
+    ///
+    /// # Returns
+    /// * An iterator over the encrypted letters
+    pub fn encrypt_chars<'a, I>(&'a mut self, chars: I) -> impl Iterator<Item = char> + 'a
+    where
+        I: Iterator<Item = char> + 'a,
+    {
+        chars.filter_map(move |c| self.encrypt_char(c))
+    }
+
+    /// Entschlüsselt einen Text (gleiche Logik wie Verschlüsselung)
+    ///
+    /// # Arguments
+    /// * `text` - Der zu entschlüsselnde Text
+    ///
+    /// # Returns
+    /// * Der entschlüsselte Text
+    pub fn decrypt(&mut self, text: &str) -> String {
+        info!("Starte Entschlüsselung von: '{}'", text);
+        let clean_input = clean_text(text);
+        info!("Bereinigter Input: '{}'", clean_input);
+
+        let result = self.encrypt_clean(&clean_input); // Gleiche Logik!
+
+        info!("Entschlüsselung abgeschlossen: '{}'", result);
+        result
+    }
+
+    /// Entschlüsselt einen Text wie `decrypt`, prüft ihn aber vorher auf Fehler
+    ///
+    /// Anders als `decrypt` werden Zeichen außer Buchstaben und Leerraum
+    /// nicht stillschweigend entfernt, sondern gemeldet. Außerdem wird jeder
+    /// Buchstabe gemeldet, der auf sich selbst abgebildet würde: Eine intakte
+    /// Enigma kann das nie, ein solcher Buchstabe deutet auf einen
+    /// verstümmelten Geheimtext oder eine fehlerhafte Verdrahtung hin. Im
+    /// Fehlerfall stehen die Walzen wieder wie vor dem Aufruf.
+    ///
+    /// # Arguments
+    /// * `text` - Der Geheimtext, gerne mit Gruppenabständen
+    ///
+    /// # Returns
+    /// * `Ok(plaintext)` - Der entschlüsselte Text wie bei `decrypt`
+    /// * `Err(positions)` - Die Stellen (Zeichenindex in `text`, ab 0) der fehlerhaften Zeichen
+    pub fn decrypt_checked(&mut self, text: &str) -> Result<String, Vec<usize>> {
+        let invalid: Vec<usize> = text
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| !c.is_ascii_alphabetic() && !c.is_whitespace())
+            .map(|(i, _)| i)
+            .collect();
+        if !invalid.is_empty() {
+            warn!("Ungültige Zeichen im Geheimtext an Stelle {:?}", invalid);
+            return Err(invalid);
+        }
+
+        let letter_positions = text
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| c.is_ascii_alphabetic())
+            .map(|(i, _)| i);
+        let clean_input = clean_text(text);
+
+        let mut guard = self.scoped();
+        let result = guard.encrypt_clean(&clean_input);
+        let self_encrypted: Vec<usize> = result
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .zip(clean_input.chars())
+            .zip(letter_positions)
+            .filter(|((output, input), _)| output == input)
+            .map(|(_, position)| position)
+            .collect();
+        if !self_encrypted.is_empty() {
+            warn!(
+                "Buchstaben an Stelle {:?} würden auf sich selbst abgebildet",
+                self_encrypted
+            );
+            return Err(self_encrypted);
+        }
+
+        guard.keep();
+        info!("Geprüfte Entschlüsselung abgeschlossen: '{}'", result);
+        Ok(result)
+    }
+
+    /// Verschlüsselt einen Text wie `encrypt` und misst die Laufzeit
+    ///
+    /// Gemessen wird der gesamte Aufruf von `encrypt` einschließlich
+    /// Bereinigung und Gruppierung. Da Ver- und Entschlüsselung identisch
+    /// sind, eignet sich die Methode auch zum Messen der Entschlüsselung.
+    /// Auf `wasm32` gibt es `std::time::Instant` nicht, daher fehlt die
+    /// Methode dort.
+    ///
+    /// # Arguments
+    /// * `text` - Der zu verschlüsselnde Text
+    ///
+    /// # Returns
+    /// * Der verschlüsselte Text und die benötigte Zeit
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn encrypt_timed(&mut self, text: &str) -> (String, std::time::Duration) {
+        let start = std::time::Instant::now();
+        let result = self.encrypt(text);
+        let elapsed = start.elapsed();
+        debug!("Verschlüsselung dauerte {:?}", elapsed);
+        (result, elapsed)
+    }
+
+    /// Verarbeitet bereits bereinigten Text ohne Gruppierung
+    ///
+    /// Anders als `encrypt`/`decrypt` wird der Text weder bereinigt noch in
+    /// Gruppen aufgeteilt: Jeder Buchstabe A-Z ergibt genau einen Buchstaben
+    /// der Ausgabe, sodass Klartext und Geheimtext Byte für Byte umkehrbar
+    /// sind. Der Aufrufer muss den Text vorher bereinigen (z.B. mit
+    /// `clean_text`); andere Zeichen werden ohne Walzenfortschaltung verworfen.
+    ///
+    /// # Arguments
+    /// * `text` - Der bereinigte Text (nur A-Z)
+    ///
+    /// # Returns
+    /// * Der ver- bzw. entschlüsselte Text ohne Leerzeichen
+    pub fn process_exact(&mut self, text: &str) -> String {
+        text.chars().filter_map(|c| self.encrypt_char(c)).collect()
+    }
+
+    /// Prüft, dass kein Buchstabe des Textes auf sich selbst abgebildet wird
+    ///
+    /// Der Text wird zur Probe verschlüsselt; anschließend werden die
+    /// Rotorpositionen wiederhergestellt. Eine Verletzung deutet auf eine
+    /// fehlerhafte Verdrahtung hin (z.B. ein Reflektor mit Fixpunkt).
+    ///
+    /// # Arguments
+    /// * `text` - Der Probetext
+    ///
+    /// # Returns
+    /// * `true` - Wenn kein Buchstabe sich selbst verschlüsselt
+    /// * `false` - Wenn die Eigenschaft verletzt ist
+    pub fn verify_no_self_encryption(&mut self, text: &str) -> bool {
+        let mut probe = self.scoped();
+
+        for (i, ch) in clean_text(text).chars().enumerate() {
+            if probe.encrypt_char(ch) == Some(ch) {
+                warn!(
+                    "Buchstabe {} an Stelle {} wurde auf sich selbst verschlüsselt",
+                    ch, i
+                );
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Öffnet einen Bereich, nach dem die Walzen in ihre jetzige Stellung zurückkehren
+    ///
+    /// Der zurückgegebene Wächter merkt sich Rotorpositionen und
+    /// Schrittzähler und stellt beide wieder her, sobald er verworfen wird.
+    /// Über ihn stehen alle Methoden der Maschine zur Verfügung, z.B. für
+    /// eine Probeverschlüsselung, die den laufenden Spruch nicht stört.
+    ///
+    /// # Returns
+    /// * Der Wächter, der bis zu seinem Ende die Maschine ausleiht
+    pub fn scoped(&mut self) -> PositionGuard<'_, S> {
+        let positions = self.rotors.iter().map(|r| r.position()).collect();
+        let step_count = self.step_count;
+        PositionGuard {
+            machine: self,
+            positions,
+            step_count,
+            restore: true,
+        }
+    }
+
+    /// Dreht die Rotoren entsprechend der Enigma-Regeln
+    fn step_rotors(&mut self) {
+        // Die übliche Maschine hat höchstens vier Rotoren; dafür genügt ein
+        // Array auf dem Stack statt eines Vektors pro Tastendruck
+        if self.rotors.len() <= MAX_INLINE_ROTORS {
+            let mut turnovers = [false; MAX_INLINE_ROTORS];
+            self.fill_turnovers(&mut turnovers[..self.rotors.len()]);
+            self.apply_turnovers(&turnovers[..self.rotors.len()]);
+            self.log_turnovers(&turnovers[..self.rotors.len()]);
+        } else {
+            let turnovers = self.compute_turnovers();
+            self.apply_turnovers(&turnovers);
+            self.log_turnovers(&turnovers);
+        }
+        self.step_count += 1;
+
+        trace!("Rotorenpositionen: {}", self.positions_string());
+    }
+
+    /// Gibt die Anzahl der Fortschaltungen seit Erstellung oder `reset` zurück
+    ///
+    /// # Returns
+    /// * Die Anzahl der verarbeiteten Tastendrücke
+    pub fn get_step_count(&self) -> usize {
+        self.step_count
+    }
+
+    /// Schaltet die Rotoren `n`-mal weiter, ohne etwas zu verschlüsseln
+    ///
+    /// Entspricht `n` Tastendrücken, deren Ausgabe verworfen wird; nützlich,
+    /// um zu einer bekannten Stelle eines Spruchs vorzuspulen.
+    ///
+    /// # Arguments
+    /// * `n` - Die Anzahl der Schritte
+    pub fn advance(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step_rotors();
+        }
+        debug!("{} Schritte vorgespult: {}", n, self.positions_string());
+    }
+
+    /// Bestimmt, welche Rotoren sich beim nächsten Tastendruck drehen
+    ///
+    /// Bildet die Schaltklinken (Pawls) der Enigma nach, ausschließlich aus
+    /// den aktuellen Positionen und Kerben:
+    /// * Der rechte Rotor dreht sich immer.
+    /// * Jede weitere Klinke liegt auf dem Ring des rechten Nachbarn. Steht
+    ///   dieser an seiner Kerbe, greift sie ein und dreht beide Rotoren. Beim
+    ///   mittleren Rotor der Enigma I führt das zum Doppelschritt.
+    /// * Feststehende Rotoren (z.B. die Zusatzwalze der M4) haben keine
+    ///   Klinke und drehen sich nie.
+    ///
+    /// Mit `SteppingModel::Gear` dreht ein Rotor dagegen nur, wenn sich sein
+    /// rechter Nachbar gerade von einer Kerbe weiterdreht (Zählwerk ohne
+    /// Doppelschritt).
+    ///
+    /// # Returns
+    /// * Vektor mit `true` für jeden Rotor, der sich dreht (von links nach rechts)
+    fn compute_turnovers(&self) -> Vec<bool> {
+        let mut turnovers = vec![false; self.rotors.len()];
+        self.fill_turnovers(&mut turnovers);
+        turnovers
+    }
+
+    /// Schreibt das Ergebnis von `compute_turnovers` in einen vorhandenen Puffer
+    ///
+    /// # Arguments
+    /// * `turnovers` - Puffer mit genau einem Eintrag pro Rotor
+    fn fill_turnovers(&self, turnovers: &mut [bool]) {
+        let count = self.rotors.len();
+        turnovers.fill(false);
+
+        if let Some(rightmost) = turnovers.last_mut() {
+            *rightmost = true;
+        }
+
+        match self.stepping_model {
+            SteppingModel::PawlRatchet => {
+                for i in 0..count.saturating_sub(1) {
+                    if !self.rotors[i].is_stationary() && self.rotors[i + 1].is_at_notch() {
+                        turnovers[i] = true;
+                        turnovers[i + 1] = true;
+                    }
+                }
+            }
+            SteppingModel::Gear => {
+                // Übertrag wie bei einem Zählwerk, von rechts nach links
+                for i in (0..count.saturating_sub(1)).rev() {
+                    turnovers[i] = turnovers[i + 1]
+                        && !self.rotors[i + 1].is_stationary()
+                        && self.rotors[i + 1].is_at_notch();
+                }
+            }
+        }
+
+        for (turns, rotor) in turnovers.iter_mut().zip(self.rotors.iter()) {
+            *turns &= !rotor.is_stationary();
+        }
+    }
+
+    /// Dreht die Rotoren gemäß den berechneten Übertragungen
+    ///
+    /// # Arguments
+    /// * `turnovers` - `true` für jeden zu drehenden Rotor
+    fn apply_turnovers(&mut self, turnovers: &[bool]) {
+        for (rotor, &turns) in self.rotors.iter_mut().zip(turnovers.iter()) {
+            if turns {
+                rotor.step();
+            }
+        }
+    }
+
+    /// Meldet die Rotoren, die eine Kerbe mitgenommen hat
+    ///
+    /// Der rechte Rotor dreht sich bei jedem Tastendruck und wird daher
+    /// nicht gemeldet, nur die Überträge auf die Rotoren links davon.
+    ///
+    /// # Arguments
+    /// * `turnovers` - `true` für jeden gedrehten Rotor
+    fn log_turnovers(&self, turnovers: &[bool]) {
+        let Some((_, carried)) = turnovers.split_last() else {
+            return;
+        };
+        for (index, _) in carried.iter().enumerate().filter(|(_, &turns)| turns) {
+            let rotor = &self.rotors[index];
+            info!(
+                target: TURNOVER_LOG_TARGET,
+                "Übertrag bei Schritt {}: Rotor {} ({}. von links) dreht auf {}",
+                self.step_count + 1,
+                rotor.name(),
+                index + 1,
+                rotor.get_position_char()
+            );
+        }
+    }
+
+    /// Gibt zurück, welche Rotoren sich beim nächsten Tastendruck drehen
+    ///
+    /// # Returns
+    /// * Vektor mit `true` für jeden Rotor, der sich dreht (von links nach rechts)
+    pub fn next_step_turnovers(&self) -> Vec<bool> {
+        self.compute_turnovers()
     }
 
-    /// Encrypts a single character
+    /// Berechnet, nach wie vielen Tastendrücken sich der mittlere Rotor dreht
     ///
-    /// # Arguments
-    /// * `input` - The character to encrypt
+    /// Der mittlere Rotor ist der zweite von rechts. Er dreht sich, sobald
+    /// der rechte Rotor seine Kerbe verlässt, oder beim Doppelschritt, wenn
+    /// er selbst an seiner Kerbe steht.
     ///
     /// # Returns
-    /// * The encrypted character
-    pub fn encrypt_char(&mut self, input: char) -> char {
-        debug!("=== Verschlüsselung von '{}' ===", input);
-
-        // 1. Plugboard (forward)
-        let after_plugboard = self.plugboard.process(input);
-        trace!(
-            "Nach Steckerbrett (vorwärts): {} -> {}",
-            input,
-            after_plugboard
-        );
-
-        // 2. Rotate rotors (before encryption)
-        self.step_rotors();
-
-        // 3. Through the rotors (forward)
-        let mut signal = after_plugboard;
-        for (i, rotor) in self.rotors.iter().enumerate() {
-            signal = rotor.forward(signal);
-            trace!("Nach Rotor {} (vorwärts): {}", i + 1, signal);
+    /// * `Some(n)` - Der n-te Tastendruck dreht den mittleren Rotor (n >= 1)
+    /// * `None` - Bei weniger als zwei Rotoren oder wenn sich der mittlere
+    ///   Rotor nie dreht
+    pub fn next_turnover_in(&self) -> Option<usize> {
+        let count = self.rotors.len();
+        if count < 2 || self.rotors[count - 2].is_stationary() {
+            return None;
         }
 
-        // 4. Reflektor
-        signal = self.reflector.reflect(signal);
-        trace!("Nach Reflektor: {}", signal);
-
-        // 5. Through the rotors (backward)
-        for (i, rotor) in self.rotors.iter().rev().enumerate() {
-            signal = rotor.backward(signal);
-            trace!("Nach Rotor {} (rückwärts): {}", 3 - i, signal);
+        let middle = &self.rotors[count - 2];
+        if self.stepping_model == SteppingModel::PawlRatchet
+            && count >= 3
+            && !self.rotors[count - 3].is_stationary()
+            && middle.is_at_notch()
+        {
+            return Some(1);
         }
 
-        // 6. Plugboard (backward)
-        let final_output = self.plugboard.process(signal);
-        trace!(
-            "Nach Steckerbrett (rückwärts): {} -> {}",
-            signal,
-            final_output
-        );
-
-        debug!(
-            "=== Verschlüsselung abgeschlossen: {} -> {} ===",
-            input, final_output
-        );
-        final_output
+        self.rotors[count - 1]
+            .steps_until_notch()
+            .map(|steps| steps + 1)
     }
 
-    /// Encrypts a complete text
+    /// Prüft, ob sich der mittlere Rotor beim nächsten Tastendruck dreht
     ///
-    /// # Arguments
-    /// * `text` - The text to encrypt
+    /// Der mittlere Rotor ist der zweite von rechts. Berücksichtigt wird
+    /// auch der Doppelschritt, bei dem er sich von seiner eigenen Kerbe aus
+    /// zusammen mit dem linken Rotor weiterdreht.
     ///
     /// # Returns
-    /// * The encrypted text
-    pub fn encrypt(&mut self, text: &str) -> String {
-        info!("Starte Verschlüsselung von: '{}'", text);
-        let clean_input = clean_text(text);
-        info!("Bereinigter Input: '{}'", clean_input);
+    /// * `true` - Wenn der nächste Tastendruck den mittleren Rotor dreht
+    /// * `false` - Sonst, oder bei weniger als zwei Rotoren
+    pub fn will_step_middle(&self) -> bool {
+        self.will_step_from_right(2)
+    }
 
-        let mut result = String::new();
-        for (i, ch) in clean_input.chars().enumerate() {
-            let encrypted = self.encrypt_char(ch);
-            result.push(encrypted);
+    /// Prüft, ob sich der linke Rotor beim nächsten Tastendruck dreht
+    ///
+    /// Der linke Rotor ist der dritte von rechts; die feststehende
+    /// Zusatzwalze der M4 zählt nicht dazu.
+    ///
+    /// # Returns
+    /// * `true` - Wenn der nächste Tastendruck den linken Rotor dreht
+    /// * `false` - Sonst, oder bei weniger als drei Rotoren
+    pub fn will_step_left(&self) -> bool {
+        self.will_step_from_right(3)
+    }
 
-            if i > 0 && i % 5 == 4 {
-                result.push(' ');
-            }
-        }
+    /// Prüft, ob sich der `offset`-te Rotor von rechts (1 = rechts) als Nächstes dreht
+    fn will_step_from_right(&self, offset: usize) -> bool {
+        let count = self.rotors.len();
+        count >= offset && self.compute_turnovers()[count - offset]
+    }
 
-        info!("Verschlüsselung abgeschlossen: '{}'", result);
-        result
+    /// Formatiert die aktuellen Rotorpositionen für das Log (z.B. "A D U")
+    fn positions_string(&self) -> String {
+        self.rotors
+            .iter()
+            .map(|r| r.get_position_char().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 
-    /// Entschlüsselt einen Text (gleiche Logik wie Verschlüsselung)
+    /// Setzt die Rotorpositionen
     ///
     /// # Arguments
-    /// * `text` - Der zu entschlüsselnde Text
+    /// * `positions` - Die Positionen von links nach rechts, eine pro Rotor
+    pub fn set_rotor_positions(&mut self, positions: impl AsRef<[char]>) {
+        for (rotor, &pos) in self.rotors.iter_mut().zip(positions.as_ref()) {
+            if let Err(e) = rotor.set_position_char(pos) {
+                warn!("{}", e);
+            }
+        }
+        info!("Rotorpositionen gesetzt auf: {}", self.positions_string());
+    }
+
+    /// Gibt die aktuellen Rotorpositionen zurück
     ///
     /// # Returns
-    /// * Der entschlüsselte Text
-    pub fn decrypt(&mut self, text: &str) -> String {
-        info!("Starte Entschlüsselung von: '{}'", text);
-        let clean_input = clean_text(text);
-        info!("Bereinigter Input: '{}'", clean_input);
+    /// * Die aktuellen Positionen von links nach rechts
+    pub fn get_rotor_positions(&self) -> Vec<char> {
+        self.rotors.iter().map(|r| r.get_position_char()).collect()
+    }
 
-        let mut result = String::new();
-        for (i, ch) in clean_input.chars().enumerate() {
-            let decrypted = self.encrypt_char(ch); // Gleiche Logik!
-            result.push(decrypted);
+    /// Gibt die Rotortypen der Maschine zurück
+    ///
+    /// # Returns
+    /// * Die Rotornamen von links nach rechts, z.B. ["I", "II", "III"]
+    pub fn get_rotor_types(&self) -> Vec<String> {
+        self.rotors.iter().map(|r| r.name().to_string()).collect()
+    }
 
-            if i > 0 && i % 5 == 4 {
-                result.push(' ');
-            }
+    /// Bestimmt die Modellbezeichnung anhand der eingesetzten Komponenten
+    ///
+    /// # Returns
+    /// * "Enigma M4" bei feststehender Zusatzwalze, "Enigma I / M3" bei drei
+    ///   Rotoren, sonst eine Beschreibung mit der Rotoranzahl
+    pub fn model_label(&self) -> String {
+        match self.rotors.len() {
+            4 if self.rotors[0].is_stationary() => "Enigma M4".to_string(),
+            3 => "Enigma I / M3".to_string(),
+            1 => "Enigma mit 1 Rotor".to_string(),
+            count => format!("Enigma mit {} Rotoren", count),
         }
+    }
+}
 
-        info!("Entschlüsselung abgeschlossen: '{}'", result);
-        result
+/// Stellt beim Verwerfen die Walzenstellung einer Maschine wieder her
+///
+/// Wird von `EnigmaMachine::scoped` erstellt und gibt über `Deref` Zugriff
+/// auf die Maschine.
+#[derive(Debug)]
+pub struct PositionGuard<'a, S: Scrambler = Rotor> {
+    machine: &'a mut EnigmaMachine<S>,
+    positions: Vec<usize>,
+    step_count: usize,
+    restore: bool,
+}
+
+impl<S: Scrambler> PositionGuard<'_, S> {
+    /// Behält die neue Walzenstellung bei, statt sie wiederherzustellen
+    pub fn keep(mut self) {
+        self.restore = false;
     }
+}
 
-    /// Dreht die Rotoren entsprechend der Enigma-Regeln
-    fn step_rotors(&mut self) {
-        // Rechter Rotor dreht sich immer
-        let right_rotor_notched = self.rotors[2].step();
+impl<S: Scrambler> std::ops::Deref for PositionGuard<'_, S> {
+    type Target = EnigmaMachine<S>;
 
-        // Mittlerer Rotor dreht sich, wenn der rechte an der Kerbe ist
-        let middle_rotor_notched = if right_rotor_notched {
-            self.rotors[1].step()
-        } else {
-            // Oder wenn der mittlere selbst an der Kerbe ist (Doppelschritt)
-            if self.rotors[1].position == self.rotors[1].notch {
-                self.rotors[1].step()
-            } else {
-                false
-            }
-        };
+    fn deref(&self) -> &Self::Target {
+        self.machine
+    }
+}
 
-        // Linker Rotor dreht sich, wenn der mittlere an der Kerbe ist
-        if middle_rotor_notched {
-            self.rotors[0].step();
-        }
+impl<S: Scrambler> std::ops::DerefMut for PositionGuard<'_, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.machine
+    }
+}
 
-        trace!(
-            "Rotorenpositionen: {} {} {}",
-            self.rotors[0].get_position_char(),
-            self.rotors[1].get_position_char(),
-            self.rotors[2].get_position_char()
+impl<S: Scrambler> Drop for PositionGuard<'_, S> {
+    fn drop(&mut self) {
+        if !self.restore {
+            return;
+        }
+        for (rotor, &position) in self.machine.rotors.iter_mut().zip(self.positions.iter()) {
+            rotor.set_position(position);
+        }
+        self.machine.step_count = self.step_count;
+        debug!(
+            "Rotorpositionen wiederhergestellt: {}",
+            self.machine.positions_string()
         );
     }
+}
 
-    /// Setzt die Rotorpositionen
+impl EnigmaMachine {
+    /// Erstellt eine unabhängige Kopie mit neuer Grundstellung
+    ///
+    /// Gedacht für Sprüche mit eigenem Spruchschlüssel: Aus einer Maschine
+    /// mit Tagesschlüssel wird pro Spruch eine Kopie abgeleitet, die z.B.
+    /// in einem eigenen Thread verschlüsselt. `reset` führt die Kopie auf
+    /// `start` zurück, ihr Schrittzähler beginnt bei 0.
+    ///
+    /// # Arguments
+    /// * `start` - Die Rotorpositionen von links nach rechts, eine pro Rotor
+    ///
+    /// # Returns
+    /// * Die Kopie in der neuen Grundstellung
+    pub fn clone_at_position(&self, start: impl AsRef<[char]>) -> EnigmaMachine {
+        let mut machine = self.clone();
+        machine.set_rotor_positions(start);
+        machine.initial_positions = machine.rotors.iter().map(|r| r.position).collect();
+        machine.step_count = 0;
+        machine
+    }
+
+    /// Setzt die Ringstellungen
     ///
     /// # Arguments
-    /// * `positions` - Array der drei Positionen (links, mitte, rechts)
-    pub fn set_rotor_positions(&mut self, positions: [char; 3]) {
-        for (i, &pos) in positions.iter().enumerate() {
-            if let Some(index) = letter_to_index(pos) {
-                self.rotors[i].set_position(index);
+    /// * `ring_settings` - Die Ringstellungen von links nach rechts, eine pro Rotor
+    pub fn set_ring_settings(&mut self, ring_settings: impl AsRef<[char]>) {
+        for (rotor, &ring) in self.rotors.iter_mut().zip(ring_settings.as_ref()) {
+            if let Err(e) = rotor.set_ring_setting_char(ring) {
+                warn!("{}", e);
             }
         }
         info!(
-            "Rotorpositionen gesetzt auf: {} {} {}",
-            positions[0], positions[1], positions[2]
+            "Ringstellungen gesetzt auf: {}",
+            self.get_ring_settings()
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
         );
     }
 
-    /// Setzt die Ringstellungen
+    /// Tauscht Rotoren und Reflektor aus, ohne die Maschine neu zu erstellen
+    ///
+    /// Es gelten dieselben Regeln wie in `factory::from_config` (keine
+    /// doppelten Rotoren, M4 mit Zusatzwalze und dünnem Reflektor). Die
+    /// Steckerbrettverbindungen, die Ausgabegruppierung und ein Beobachter
+    /// bleiben erhalten. Positionen, Ringstellungen und Startpositionen
+    /// werden rechtsbündig übernommen, damit die schnelle Walze ihre Stellung
+    /// behält; neu hinzugekommene Rotoren beginnen bei "A". Bei einem Fehler
+    /// bleibt die Maschine unverändert.
     ///
     /// # Arguments
-    /// * `ring_settings` - Array der drei Ringstellungen (links, mitte, rechts)
-    pub fn set_ring_settings(&mut self, ring_settings: [char; 3]) {
-        for (i, &ring) in ring_settings.iter().enumerate() {
-            if let Some(index) = letter_to_index(ring) {
-                self.rotors[i].set_ring_setting(index);
-            }
-        }
+    /// * `rotor_types` - Die neuen Rotortypen von links nach rechts
+    /// * `reflector_type` - Der neue Reflektortyp
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Erfolg oder Fehler bei ungültiger Kombination
+    pub fn reconfigure(
+        &mut self,
+        rotor_types: &[&str],
+        reflector_type: &str,
+    ) -> Result<(), String> {
+        self.rebuild(rotor_types, reflector_type, factory::from_config)
+    }
+
+    /// Wie `reconfigure`, erlaubt aber mehrfach ausgewählte Rotoren
+    ///
+    /// # Arguments
+    /// * `rotor_types` - Die neuen Rotortypen von links nach rechts
+    /// * `reflector_type` - Der neue Reflektortyp
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Erfolg oder Fehler bei ungültiger Kombination
+    pub fn reconfigure_allowing_duplicates(
+        &mut self,
+        rotor_types: &[&str],
+        reflector_type: &str,
+    ) -> Result<(), String> {
+        self.rebuild(
+            rotor_types,
+            reflector_type,
+            factory::from_config_allowing_duplicates,
+        )
+    }
+
+    /// Tauscht Rotoren und Reflektor mit der angegebenen Maschinenfabrik aus
+    fn rebuild(
+        &mut self,
+        rotor_types: &[&str],
+        reflector_type: &str,
+        build: fn(&MachineConfig) -> Result<EnigmaMachine, String>,
+    ) -> Result<(), String> {
+        let count = rotor_types.len();
+        let config = MachineConfig {
+            rotor_types: rotor_types.iter().map(|t| t.to_string()).collect(),
+            rotor_positions: align_right(&self.get_rotor_positions(), count, 'A'),
+            ring_settings: align_right(&self.get_ring_settings(), count, 'A'),
+            reflector_type: reflector_type.to_string(),
+            plugboard: String::new(),
+            stepping_model: self.stepping_model,
+            uhr_setting: None,
+        };
+        let machine = build(&config)?;
+
+        self.initial_positions = align_right(&self.initial_positions, count, 0);
+        self.rotors = machine.rotors;
+        self.reflector = machine.reflector;
         info!(
-            "Ringstellungen gesetzt auf: {} {} {}",
-            ring_settings[0], ring_settings[1], ring_settings[2]
+            "Maschine umgebaut: Rotoren {}, Reflektor {}",
+            rotor_types.join(" "),
+            self.reflector.name
         );
+        Ok(())
     }
 
-    /// Gibt die aktuellen Rotorpositionen zurück
+    /// Übernimmt alle Einstellungen aus den Textformaten von CLI und GUI
+    ///
+    /// Die Angaben werden wie bei `MachineConfig::from_settings` gelesen und
+    /// wie bei `factory::from_config` geprüft. Rotoren, Reflektor und
+    /// Steckerbrett werden ersetzt, die neue Grundstellung gilt auch für
+    /// `reset`. Ausgabegruppierung, Fortschaltmechanik und ein Beobachter
+    /// bleiben erhalten. Bei einem Fehler bleibt die Maschine unverändert.
+    ///
+    /// # Arguments
+    /// * `rotors` - Die Rotortypen, z.B. "I,II,III"
+    /// * `positions` - Die Rotorpositionen, z.B. "ABC" (leer = alle "A")
+    /// * `rings` - Die Ringstellungen, z.B. "01 02 03" (leer = alle "A")
+    /// * `reflector` - Der Reflektortyp, z.B. "B"
+    /// * `plugboard` - Die Steckerverbindungen, z.B. "AB CD"
     ///
     /// # Returns
-    /// * Array der aktuellen Positionen
-    pub fn get_rotor_positions(&self) -> [char; 3] {
-        [
-            self.rotors[0].get_position_char(),
-            self.rotors[1].get_position_char(),
-            self.rotors[2].get_position_char(),
-        ]
+    /// * `Result<(), String>` - Erfolg oder Fehler bei ungültigen Einstellungen
+    pub fn apply_settings(
+        &mut self,
+        rotors: &str,
+        positions: &str,
+        rings: &str,
+        reflector: &str,
+        plugboard: &str,
+    ) -> Result<(), String> {
+        let config = MachineConfig::from_settings(rotors, positions, rings, reflector, plugboard)?;
+        let machine = factory::from_config(&config)?;
+
+        self.rotors = machine.rotors;
+        self.reflector = machine.reflector;
+        self.plugboard = machine.plugboard;
+        self.initial_positions = machine.initial_positions;
+        self.step_count = 0;
+        info!(
+            "Einstellungen übernommen: Rotoren {}, Positionen {}",
+            config.rotor_types.join(" "),
+            self.positions_string()
+        );
+        Ok(())
     }
 
     /// Gibt die aktuellen Ringstellungen zurück
     ///
     /// # Returns
-    /// * Array der aktuellen Ringstellungen
-    pub fn get_ring_settings(&self) -> [char; 3] {
-        [
-            self.rotors[0].get_ring_setting_char(),
-            self.rotors[1].get_ring_setting_char(),
-            self.rotors[2].get_ring_setting_char(),
-        ]
+    /// * Die aktuellen Ringstellungen von links nach rechts
+    pub fn get_ring_settings(&self) -> Vec<char> {
+        self.rotors
+            .iter()
+            .map(|r| r.get_ring_setting_char())
+            .collect()
     }
 
     /// Gibt Informationen über die Maschinenkonfiguration zurück
     ///
+    /// Entspricht der `Display`-Ausgabe der Maschine.
+    ///
     /// # Returns
-    /// * String mit Konfigurationsinformationen
+    /// * String mit Modell, einer Zeile pro Rotor und einer Zusammenfassung
     pub fn get_configuration_info(&self) -> String {
-        format!(
-            "Rotoren: {} {} {}\nRingstellungen: {} {} {}\nPositionen: {} {} {}\nReflektor: {}\nSteckerbrett: {}",
-            self.rotors[0].name,
-            self.rotors[1].name,
-            self.rotors[2].name,
-            self.get_ring_settings()[0],
-            self.get_ring_settings()[1],
-            self.get_ring_settings()[2],
-            self.get_rotor_positions()[0],
-            self.get_rotor_positions()[1],
-            self.get_rotor_positions()[2],
-            self.reflector.name,
+        self.to_string()
+    }
+
+    /// Erfasst die aktuellen Einstellungen als serialisierbare Konfiguration
+    ///
+    /// # Returns
+    /// * Die Konfiguration mit den aktuellen Rotorpositionen
+    pub fn to_config(&self) -> MachineConfig {
+        MachineConfig {
+            rotor_types: self.get_rotor_types(),
+            rotor_positions: self.get_rotor_positions(),
+            ring_settings: self.get_ring_settings(),
+            reflector_type: self.reflector.name.clone(),
+            plugboard: self.plugboard.get_connections_string(),
+            stepping_model: self.stepping_model,
+            uhr_setting: self.plugboard.get_uhr_setting(),
+        }
+    }
+
+    /// Berechnet einen Hash über die Schlüsseleinstellungen der Maschine
+    ///
+    /// Erfasst werden Rotorreihenfolge, Ringstellungen, die Verdrahtung des
+    /// Reflektors, Steckerbrett samt Stellung der Enigma Uhr und
+    /// Fortschaltmechanik. Die Rotorpositionen fließen bewusst nicht ein, da
+    /// sie sich bei jedem Tastendruck ändern.
+    ///
+    /// # Returns
+    /// * Ein stabiler 64-Bit-Hash der Konfiguration
+    pub fn configuration_hash(&self) -> u64 {
+        let names: Vec<&str> = self.rotors.iter().map(|r| r.name.as_str()).collect();
+        let rings: String = self.get_ring_settings().into_iter().collect();
+        let mut canonical = format!(
+            "{}|{}|{}|{}",
+            names.join("|"),
+            rings,
+            self.reflector.wiring_string(),
             self.plugboard.get_connections_string()
-        )
+        );
+        // Uhr und abweichende Mechanik nur anhängen, wenn gesetzt, damit
+        // bestehende Hashes gültig bleiben
+        if let Some(setting) = self.plugboard.get_uhr_setting() {
+            canonical.push_str(&format!("|Uhr {}", setting));
+        }
+        if self.stepping_model != SteppingModel::default() {
+            canonical.push_str(&format!("|{:?}", self.stepping_model));
+        }
+        fnv1a_64(canonical.as_bytes())
+    }
+
+    /// Gibt den Konfigurations-Hash als kurzen Fingerabdruck zurück
+    ///
+    /// # Returns
+    /// * Acht Hex-Ziffern, z.B. "3fa2c01d"
+    pub fn configuration_fingerprint(&self) -> String {
+        let hash = self.configuration_hash();
+        format!("{:08x}", (hash >> 32) ^ (hash & 0xffff_ffff))
     }
 }
 
 /// Factory-Funktionen für häufige Enigma-Konfigurationen
 pub mod factory {
     use super::*;
-    use crate::reflector::reflectors::{reflector_a, reflector_b, reflector_c};
-    use crate::rotor::rotors::{rotor_i, rotor_ii, rotor_iii, rotor_iv, rotor_v};
+    use crate::reflector::reflectors::reflector_b;
+    use crate::rotor::rotors::{rotor_i, rotor_ii, rotor_iii, CUSTOM_PREFIX};
+    use crate::rotor::RotorType;
+
+    /// Wandelt eine Positions- oder Ringeinstellung in einen Index um
+    ///
+    /// Verwendet dieselbe Umrechnung wie `EnigmaMachine::set_rotor_positions`
+    /// und `EnigmaMachine::set_ring_settings`, damit beide Wege identische
+    /// Maschinen ergeben (auch bei Kleinbuchstaben).
+    fn setting_index(setting: char) -> Result<usize, String> {
+        letter_to_index(setting).ok_or_else(|| format!("Ungültige Einstellung: {}", setting))
+    }
 
     /// Erstellt eine Standard-Enigma-Maschine (Rotoren I, II, III, Reflektor B)
     ///
@@ -271,18 +2105,18 @@ pub mod factory {
         ring_settings: [char; 3],
         plugboard_connections: &str,
     ) -> Result<EnigmaMachine, String> {
-        let rotors = [
+        let rotors = vec![
             rotor_i(
-                ring_settings[0] as usize - b'A' as usize,
-                rotor_positions[0] as usize - b'A' as usize,
+                setting_index(ring_settings[0])?,
+                setting_index(rotor_positions[0])?,
             )?,
             rotor_ii(
-                ring_settings[1] as usize - b'A' as usize,
-                rotor_positions[1] as usize - b'A' as usize,
+                setting_index(ring_settings[1])?,
+                setting_index(rotor_positions[1])?,
             )?,
             rotor_iii(
-                ring_settings[2] as usize - b'A' as usize,
-                rotor_positions[2] as usize - b'A' as usize,
+                setting_index(ring_settings[2])?,
+                setting_index(rotor_positions[2])?,
             )?,
         ];
 
@@ -292,58 +2126,330 @@ pub mod factory {
         Ok(EnigmaMachine::new(rotors, reflector, plugboard))
     }
 
+    /// Sucht einen Rotortyp, der mehrfach ausgewählt wurde
+    ///
+    /// Jede Walze liegt nur einmal im Walzenkasten, daher ist z.B.
+    /// "I, I, I" an einer echten Maschine nicht einstellbar.
+    ///
+    /// # Arguments
+    /// * `rotor_types` - Die Rotortypen von links nach rechts
+    ///
+    /// # Returns
+    /// * `Some(name)` - Der erste mehrfach vorkommende Rotortyp
+    /// * `None` - Wenn jeder Rotortyp nur einmal vorkommt
+    pub fn duplicate_rotor<S: AsRef<str>>(rotor_types: &[S]) -> Option<&str> {
+        rotor_types.iter().enumerate().find_map(|(i, rotor_type)| {
+            let name = rotor_type.as_ref();
+            rotor_types[..i]
+                .iter()
+                .any(|earlier| earlier.as_ref() == name)
+                .then_some(name)
+        })
+    }
+
+    /// Lehnt eine Walzenlage mit mehrfach ausgewähltem Rotor ab
+    fn check_unique_rotors<S: AsRef<str>>(rotor_types: &[S]) -> Result<(), String> {
+        match duplicate_rotor(rotor_types) {
+            Some(name) => Err(format!("Rotor {} wurde mehrfach ausgewählt", name)),
+            None => Ok(()),
+        }
+    }
+
     /// Erstellt eine Enigma-Maschine mit benutzerdefinierten Rotoren
     ///
+    /// Die Anzahl der Rotoren ist frei wählbar (z.B. eine Lehrmaschine mit
+    /// einem Rotor); Arrays mit drei Einträgen funktionieren unverändert.
+    /// Jeder Rotortyp darf nur einmal vorkommen; für bewusst nicht-physische
+    /// Walzenlagen gibt es `create_custom_machine_allowing_duplicates`.
+    ///
+    /// # Arguments
+    /// * `rotor_types` - Die Rotortypen von links nach rechts, z.B. ["I", "II", "III"]
+    /// * `rotor_positions` - Die Rotorpositionen, eine pro Rotor
+    /// * `ring_settings` - Die Ringstellungen, eine pro Rotor
+    /// * `reflector_type` - Der Reflektortyp ("A", "B", "C" oder "D:<Paare>")
+    /// * `plugboard_connections` - Die Steckerbrett-Verbindungen
+    ///
+    /// # Returns
+    /// * `Result<EnigmaMachine, String>` - Die erstellte Maschine oder ein Fehler
+    pub fn create_custom_machine<S: AsRef<str>>(
+        rotor_types: impl AsRef<[S]>,
+        rotor_positions: impl AsRef<[char]>,
+        ring_settings: impl AsRef<[char]>,
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<EnigmaMachine, String> {
+        check_unique_rotors(rotor_types.as_ref())?;
+        create_custom_machine_allowing_duplicates(
+            rotor_types,
+            rotor_positions,
+            ring_settings,
+            reflector_type,
+            plugboard_connections,
+        )
+    }
+
+    /// Wie `create_custom_machine`, erlaubt aber mehrfach ausgewählte Rotoren
+    ///
+    /// Für Experimente mit Walzenlagen, die an einer echten Maschine nicht
+    /// möglich wären (z.B. "I, I, I").
+    ///
+    /// # Arguments
+    /// * `rotor_types` - Die Rotortypen von links nach rechts
+    /// * `rotor_positions` - Die Rotorpositionen, eine pro Rotor
+    /// * `ring_settings` - Die Ringstellungen, eine pro Rotor
+    /// * `reflector_type` - Der Reflektortyp ("A", "B", "C" oder "D:<Paare>")
+    /// * `plugboard_connections` - Die Steckerbrett-Verbindungen
+    ///
+    /// # Returns
+    /// * `Result<EnigmaMachine, String>` - Die erstellte Maschine oder ein Fehler
+    pub fn create_custom_machine_allowing_duplicates<S: AsRef<str>>(
+        rotor_types: impl AsRef<[S]>,
+        rotor_positions: impl AsRef<[char]>,
+        ring_settings: impl AsRef<[char]>,
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<EnigmaMachine, String> {
+        let rotor_types = rotor_types.as_ref();
+        if rotor_types.is_empty() {
+            return Err("Mindestens ein Rotor wird benötigt".to_string());
+        }
+
+        let rotor_positions = rotor_positions.as_ref();
+        let ring_settings = ring_settings.as_ref();
+        if rotor_positions.len() != rotor_types.len() || ring_settings.len() != rotor_types.len() {
+            return Err(format!(
+                "{} Rotoren benötigen je {} Positionen und Ringstellungen, erhalten: {} Positionen, {} Ringstellungen",
+                rotor_types.len(),
+                rotor_types.len(),
+                rotor_positions.len(),
+                ring_settings.len()
+            ));
+        }
+
+        let mut rotors = Vec::with_capacity(rotor_types.len());
+        for ((rotor_type, &position), &ring) in
+            rotor_types.iter().zip(rotor_positions).zip(ring_settings)
+        {
+            let rotor_type: RotorType = rotor_type.as_ref().parse()?;
+            if rotor_type.is_stationary() {
+                return Err(format!(
+                    "Zusatzwalze {} ist nur in der M4 verwendbar",
+                    rotor_type
+                ));
+            }
+            rotors.push(rotor_type.create(setting_index(ring)?, setting_index(position)?)?);
+        }
+
+        let reflector_type: ReflectorType = reflector_type.parse()?;
+        if reflector_type.is_thin() {
+            return Err(format!(
+                "Dünner Reflektor {} ist nur in der M4 verwendbar",
+                reflector_type
+            ));
+        }
+        let reflector = reflector_type.create()?;
+
+        let plugboard = Plugboard::from_string(plugboard_connections)?;
+
+        Ok(EnigmaMachine::new(rotors, reflector, plugboard))
+    }
+
+    /// Erstellt eine Enigma-Maschine mit frei gewählten Rotorverdrahtungen
+    ///
+    /// Jeder Rotor wird als Tupel aus Verdrahtung und Kerbenbuchstabe
+    /// angegeben. Die Verdrahtungen müssen Permutationen des Alphabets sein,
+    /// die Kerben Buchstaben von A bis Z.
+    ///
     /// # Arguments
-    /// * `rotor_types` - Array der Rotortypen ["I", "II", "III"]
+    /// * `rotor_wirings` - Die Rotoren [links, mitte, rechts] als (Verdrahtung, Kerbe)
     /// * `rotor_positions` - Die Rotorpositionen
     /// * `ring_settings` - Die Ringstellungen
-    /// * `reflector_type` - Der Reflektortyp ("A", "B", oder "C")
+    /// * `reflector_type` - Der Reflektortyp ("A", "B", "C" oder "D:<Paare>")
     /// * `plugboard_connections` - Die Steckerbrett-Verbindungen
     ///
     /// # Returns
     /// * `Result<EnigmaMachine, String>` - Die erstellte Maschine oder ein Fehler
-    pub fn create_custom_machine(
-        rotor_types: [&str; 3],
+    pub fn create_machine_with_wirings(
+        rotor_wirings: [(&str, char); 3],
         rotor_positions: [char; 3],
         ring_settings: [char; 3],
         reflector_type: &str,
         plugboard_connections: &str,
     ) -> Result<EnigmaMachine, String> {
-        let _rotor_creators = [rotor_i, rotor_ii, rotor_iii, rotor_iv, rotor_v];
+        for (i, &(_, notch)) in rotor_wirings.iter().enumerate() {
+            if !notch.is_ascii_alphabetic() {
+                return Err(format!(
+                    "Kerbe von Rotor {} muss ein Buchstabe A-Z sein: {}",
+                    i + 1,
+                    notch
+                ));
+            }
+        }
+
+        let specs =
+            rotor_wirings.map(|(wiring, notch)| format!("{}{}@{}", CUSTOM_PREFIX, wiring, notch));
+
+        create_custom_machine(
+            specs,
+            rotor_positions,
+            ring_settings,
+            reflector_type,
+            plugboard_connections,
+        )
+    }
+
+    /// Erstellt eine Kriegsmarine-Enigma M4
+    ///
+    /// Die M4 hat links eine feststehende Zusatzwalze (Beta oder Gamma) und
+    /// einen dünnen Reflektor; die drei rechten Rotoren verhalten sich wie bei
+    /// der Enigma I.
+    ///
+    /// # Arguments
+    /// * `rotor_types` - Die Rotortypen, z.B. ["Beta", "I", "II", "III"]
+    /// * `rotor_positions` - Die Rotorpositionen [Zusatzwalze, links, mitte, rechts]
+    /// * `ring_settings` - Die Ringstellungen [Zusatzwalze, links, mitte, rechts]
+    /// * `reflector_type` - Der dünne Reflektor ("B-Thin" oder "C-Thin")
+    /// * `plugboard_connections` - Die Steckerbrett-Verbindungen
+    ///
+    /// # Returns
+    /// * `Result<EnigmaMachine, String>` - Die erstellte Maschine oder ein Fehler
+    pub fn create_m4_machine(
+        rotor_types: [&str; 4],
+        rotor_positions: [char; 4],
+        ring_settings: [char; 4],
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<EnigmaMachine, String> {
+        check_unique_rotors(&rotor_types)?;
+        build_m4_machine(
+            rotor_types,
+            rotor_positions,
+            ring_settings,
+            reflector_type,
+            plugboard_connections,
+        )
+    }
 
+    /// Baut eine M4 ohne Prüfung auf mehrfach ausgewählte Rotoren
+    fn build_m4_machine(
+        rotor_types: [&str; 4],
+        rotor_positions: [char; 4],
+        ring_settings: [char; 4],
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<EnigmaMachine, String> {
         let mut rotors = Vec::new();
-        for rotor_type in rotor_types.iter() {
-            let creator = match *rotor_type {
-                "I" => rotor_i,
-                "II" => rotor_ii,
-                "III" => rotor_iii,
-                "IV" => rotor_iv,
-                "V" => rotor_v,
-                _ => return Err(format!("Unbekannter Rotortyp: {}", rotor_type)),
-            };
+        for (slot, rotor_type) in rotor_types.iter().enumerate() {
+            let rotor_type: RotorType = rotor_type.parse()?;
+            if slot == 0 && !rotor_type.is_stationary() {
+                return Err(format!(
+                    "Zusatzwalze muss Beta oder Gamma sein, nicht {}",
+                    rotor_type
+                ));
+            }
+            if slot > 0 && rotor_type.is_stationary() {
+                return Err(format!(
+                    "Zusatzwalze {} passt nur an die erste Stelle",
+                    rotor_type
+                ));
+            }
 
-            let ring_idx = rotor_positions.len() - 1 - rotors.len();
-            let pos_idx = ring_idx;
-            rotors.push(creator(
-                ring_settings[pos_idx] as usize - b'A' as usize,
-                rotor_positions[pos_idx] as usize - b'A' as usize,
+            rotors.push(rotor_type.create(
+                setting_index(ring_settings[slot])?,
+                setting_index(rotor_positions[slot])?,
             )?);
         }
 
-        let reflector = match reflector_type {
-            "A" => reflector_a(),
-            "B" => reflector_b(),
-            "C" => reflector_c(),
-            _ => return Err(format!("Unbekannter Reflektortyp: {}", reflector_type)),
-        }?;
+        let reflector_type: ReflectorType = reflector_type.parse()?;
+        if !reflector_type.is_thin() {
+            return Err(format!(
+                "M4 benötigt einen dünnen Reflektor (B-Thin oder C-Thin), nicht {}",
+                reflector_type
+            ));
+        }
+        let reflector = reflector_type.create()?;
 
         let plugboard = Plugboard::from_string(plugboard_connections)?;
 
-        Ok(EnigmaMachine::new(
-            [rotors[0].clone(), rotors[1].clone(), rotors[2].clone()],
-            reflector,
-            plugboard,
-        ))
+        Ok(EnigmaMachine::new(rotors, reflector, plugboard))
+    }
+
+    /// Erstellt eine Enigma-Maschine aus einer gespeicherten Konfiguration
+    ///
+    /// Das Steckerbrett darf bis zu 13 Kabel haben, damit sich jede mit
+    /// `to_config` gespeicherte Maschine wieder laden lässt. Die historische
+    /// Grenze von 10 Kabeln prüfen die Eingabeformate (`from_settings`,
+    /// `from_keysheet_line`) und die übrigen Factory-Funktionen.
+    ///
+    /// # Arguments
+    /// * `config` - Die Konfiguration
+    ///
+    /// # Returns
+    /// * `Result<EnigmaMachine, String>` - Die erstellte Maschine oder ein Fehler
+    pub fn from_config(config: &MachineConfig) -> Result<EnigmaMachine, String> {
+        check_unique_rotors(&config.rotor_types)?;
+        from_config_allowing_duplicates(config)
+    }
+
+    /// Wie `from_config`, erlaubt aber mehrfach ausgewählte Rotoren
+    ///
+    /// # Arguments
+    /// * `config` - Die Konfiguration
+    ///
+    /// # Returns
+    /// * `Result<EnigmaMachine, String>` - Die erstellte Maschine oder ein Fehler
+    pub fn from_config_allowing_duplicates(
+        config: &MachineConfig,
+    ) -> Result<EnigmaMachine, String> {
+        let count = config.rotor_types.len();
+        if config.rotor_positions.len() != count || config.ring_settings.len() != count {
+            return Err(
+                "Konfiguration muss gleich viele Rotoren, Positionen und Ringstellungen enthalten"
+                    .to_string(),
+            );
+        }
+
+        let has_greek_rotor = matches!(
+            config.rotor_types.first().map(String::as_str),
+            Some("Beta") | Some("Gamma")
+        );
+
+        let mut machine = if count == 4 && has_greek_rotor {
+            build_m4_machine(
+                [
+                    &config.rotor_types[0],
+                    &config.rotor_types[1],
+                    &config.rotor_types[2],
+                    &config.rotor_types[3],
+                ],
+                [
+                    config.rotor_positions[0],
+                    config.rotor_positions[1],
+                    config.rotor_positions[2],
+                    config.rotor_positions[3],
+                ],
+                [
+                    config.ring_settings[0],
+                    config.ring_settings[1],
+                    config.ring_settings[2],
+                    config.ring_settings[3],
+                ],
+                &config.reflector_type,
+                "",
+            )?
+        } else {
+            create_custom_machine_allowing_duplicates(
+                &config.rotor_types,
+                &config.rotor_positions,
+                &config.ring_settings,
+                &config.reflector_type,
+                "",
+            )?
+        };
+
+        machine.plugboard = Plugboard::from_string_with_max(&config.plugboard, FULL_CONNECTIONS)?;
+        machine.plugboard.set_uhr_setting(config.uhr_setting)?;
+        machine.set_stepping_model(config.stepping_model);
+        Ok(machine)
     }
 }
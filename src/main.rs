@@ -4,13 +4,20 @@
 //! a graphical user interface and a command-line interface.
 
 // Use modules from the library
+use enigma_rs::crack::{try_reflectors, try_reflectors_allowing_duplicates};
 use enigma_rs::gui;
-use enigma_rs::machine::factory;
-use enigma_rs::utils::clean_text;
-
-use clap::{Parser, Subcommand};
-use env_logger::{Builder, Env};
-use log::{error, info};
+use enigma_rs::machine::{factory, EnigmaMachine, MachineConfig};
+use enigma_rs::utils::{clean_text, generate_random_plugboard_cables_with, group_text};
+
+use clap::{Args, Parser, Subcommand};
+use env_logger::{Builder, Env, Target};
+use log::{error, info, LevelFilter};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Cursor, Read, Write};
+use std::path::PathBuf;
 
 /// CLI arguments for the Enigma simulator
 #[derive(Parser)]
@@ -26,165 +33,452 @@ struct Cli {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Also write the log to this file (appended, the file is never rotated or truncated)
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
     /// Subcommands
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+/// Machine settings shared by all subcommands
+#[derive(Args)]
+struct MachineArgs {
+    /// Rotor positions (e.g. "ABC" or "1 2 3"; default: "A" for every rotor)
+    #[arg(short = 'P', long)]
+    positions: Option<String>,
+
+    /// Ring settings (e.g. "ABC" or "01-02-03"; default: "A" for every rotor)
+    #[arg(short, long)]
+    rings: Option<String>,
+
+    /// Plugboard connections (e.g. "AB CD EF")
+    #[arg(short, long)]
+    plugboard: Option<String>,
+
+    /// Rotor types from left to right (e.g. "I,II,III", "Beta,I,II,III" or "CUSTOM:<wiring>@<notch>,II,III")
+    #[arg(short = 'R', long, default_value = "I,II,III")]
+    rotors: String,
+
+    /// Reflector type ("A", "B", "C", "D:<12 pairs>" for the rewirable UKW-D or "CUSTOM:<26 letters>")
+    #[arg(short = 'F', long, default_value = "B")]
+    reflector: String,
+
+    /// Allow the same rotor type more than once (not possible on a real machine)
+    #[arg(long)]
+    allow_duplicate_rotors: bool,
+}
+
+/// Input and output options of the encrypt and decrypt commands
+#[derive(Args)]
+struct IoArgs {
+    /// The text to process ("-" or omitted reads from stdin)
+    text: Option<String>,
+
+    /// Read the text from a file instead of the command line
+    #[arg(short, long, conflicts_with = "text")]
+    input: Option<PathBuf>,
+
+    /// Write the result to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Output format options of the encrypt and decrypt commands
+///
+/// By default the text is cleaned to A-Z and the result is split into
+/// 5-letter groups. `--no-group` keeps the cleaned result as one block.
+/// `--keep-format` skips cleaning altogether: spaces, punctuation and case
+/// are copied through and never grouped, so `--no-group` has no effect then.
+#[derive(Args)]
+struct FormatArgs {
+    /// Do not split the result into 5-letter groups
+    #[arg(long)]
+    no_group: bool,
+
+    /// Keep the original spacing, punctuation and case (implies --no-group)
+    #[arg(long)]
+    keep_format: bool,
+
+    /// Print the signal path of every letter to stderr
+    #[arg(long)]
+    trace: bool,
+}
+
 /// CLI subcommands
 #[derive(Subcommand)]
 enum Commands {
     /// Encrypts a text
     Encrypt {
-        /// The text to encrypt
-        text: String,
-
-        /// Rotor positions (e.g. "ABC")
-        #[arg(short = 'P', long, default_value = "AAA")]
-        positions: String,
+        #[command(flatten)]
+        io: IoArgs,
 
-        /// Ring settings (e.g. "ABC")
-        #[arg(short, long, default_value = "AAA")]
-        rings: String,
+        #[command(flatten)]
+        format: FormatArgs,
 
-        /// Plugboard connections (e.g. "AB CD EF")
-        #[arg(short, long)]
-        plugboard: Option<String>,
-
-        /// Rotor types (e.g. "I,II,III")
-        #[arg(short = 'R', long, default_value = "I,II,III")]
-        rotors: String,
-
-        /// Reflector type
-        #[arg(short = 'F', long, default_value = "B")]
-        reflector: String,
+        #[command(flatten)]
+        machine: MachineArgs,
     },
 
     /// Decrypts a text
     Decrypt {
-        /// The text to decrypt
-        text: String,
+        #[command(flatten)]
+        io: IoArgs,
+
+        #[command(flatten)]
+        format: FormatArgs,
 
-        /// Rotor positions (e.g. "ABC")
-        #[arg(short = 'P', long, default_value = "AAA")]
-        positions: String,
+        /// Try reflectors A, B and C and keep the most English-like result (ignores --reflector)
+        #[arg(long, conflicts_with_all = ["keep_format", "trace"])]
+        guess_reflector: bool,
 
-        /// Ring settings (e.g. "ABC")
-        #[arg(short, long, default_value = "AAA")]
-        rings: String,
+        #[command(flatten)]
+        machine: MachineArgs,
+    },
+
+    /// Decrypts a ciphertext and checks it against the expected plaintext
+    ///
+    /// Exits with status 0 on a match and 1 otherwise.
+    Verify {
+        /// The ciphertext
+        ciphertext: String,
 
-        /// Plugboard connections (e.g. "AB CD EF")
+        /// The expected plaintext (compared after cleaning to A-Z)
         #[arg(short, long)]
-        plugboard: Option<String>,
+        expected: String,
+
+        #[command(flatten)]
+        machine: MachineArgs,
+    },
 
-        /// Rotor types (e.g. "I,II,III")
-        #[arg(short = 'R', long, default_value = "I,II,III")]
-        rotors: String,
+    /// Prints the machine configuration and its fingerprint
+    Info {
+        #[command(flatten)]
+        machine: MachineArgs,
+    },
+
+    /// Generates random daily keys in key-sheet format
+    Genkey {
+        /// Number of days to generate
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: usize,
 
-        /// Reflector type
-        #[arg(short = 'F', long, default_value = "B")]
-        reflector: String,
+        /// Seed for a reproducible key sheet
+        #[arg(long)]
+        seed: Option<u64>,
     },
 }
 
+/// Collects the shared CLI settings into a machine configuration
+fn build_config(args: &MachineArgs) -> Result<MachineConfig, String> {
+    MachineConfig::from_settings(
+        &args.rotors,
+        args.positions.as_deref().unwrap_or_default(),
+        args.rings.as_deref().unwrap_or_default(),
+        &args.reflector,
+        args.plugboard.as_deref().unwrap_or_default(),
+    )
+}
+
+/// Builds a machine from the shared CLI settings
+fn build_machine(args: &MachineArgs) -> Result<EnigmaMachine, String> {
+    let config = build_config(args)?;
+
+    if args.allow_duplicate_rotors {
+        factory::from_config_allowing_duplicates(&config)
+    } else {
+        factory::from_config(&config)
+    }
+}
+
+/// Prints the signal path of every letter of the text to stderr
+///
+/// Works on a scoped machine, so the rotors are back at their start
+/// positions afterwards and the actual result is computed as usual.
+fn print_trace(machine: &mut EnigmaMachine, text: &str) {
+    let mut probe = machine.scoped();
+    for trace in clean_text(text)
+        .chars()
+        .filter_map(|letter| probe.encrypt_char_traced(letter))
+    {
+        eprintln!("{}", trace);
+    }
+}
+
 /// CLI handler for encryption
 fn handle_encrypt(
-    text: String,
-    positions: String,
-    rings: String,
-    plugboard: Option<String>,
-    rotors: String,
-    reflector: String,
+    mut input: impl Read,
+    output: Option<&mut dyn Write>,
+    args: &MachineArgs,
+    format: &FormatArgs,
 ) -> Result<(), String> {
     info!("Starting CLI encryption");
 
-    let rotor_positions = parse_positions(&positions)?;
-    let ring_settings = parse_positions(&rings)?;
-    let rotor_types = parse_rotors(&rotors)?;
-    let plugboard_connections = plugboard.unwrap_or_default();
+    let mut machine = build_machine(args)?;
 
-    let mut machine = factory::create_custom_machine(
-        rotor_types,
-        rotor_positions,
-        ring_settings,
-        &reflector,
-        &plugboard_connections,
-    )?;
-
-    let clean_input = clean_text(&text);
-    info!("Encrypting: '{}'", clean_input);
-
-    let result = machine.encrypt(&clean_input);
-    println!("Result: {}", result);
-
-    Ok(())
+    let text = read_text(&mut input)?;
+    if format.trace {
+        print_trace(&mut machine, &text);
+    }
+    let result = if format.keep_format {
+        info!("Encrypting with original format: '{}'", text);
+        machine.encrypt_document(&text)
+    } else {
+        let clean_input = clean_text(&text);
+        info!("Encrypting: '{}'", clean_input);
+        if format.no_group {
+            machine.set_output_grouping(None);
+        }
+        machine.encrypt(&clean_input)
+    };
+    write_result(&result, output)
 }
 
 /// CLI handler for decryption
 fn handle_decrypt(
-    text: String,
-    positions: String,
-    rings: String,
-    plugboard: Option<String>,
-    rotors: String,
-    reflector: String,
+    mut input: impl Read,
+    output: Option<&mut dyn Write>,
+    args: &MachineArgs,
+    format: &FormatArgs,
+    guess_reflector: bool,
 ) -> Result<(), String> {
     info!("Starting CLI decryption");
 
-    let rotor_positions = parse_positions(&positions)?;
-    let ring_settings = parse_positions(&rings)?;
-    let rotor_types = parse_rotors(&rotors)?;
-    let plugboard_connections = plugboard.unwrap_or_default();
+    if guess_reflector {
+        let text = read_text(&mut input)?;
+        return handle_guess_reflector(&text, output, args, format);
+    }
+
+    let mut machine = build_machine(args)?;
 
-    let mut machine = factory::create_custom_machine(
-        rotor_types,
-        rotor_positions,
-        ring_settings,
-        &reflector,
-        &plugboard_connections,
-    )?;
+    let text = read_text(&mut input)?;
+    if format.trace {
+        print_trace(&mut machine, &text);
+    }
+    let result = if format.keep_format {
+        info!("Decrypting with original format: '{}'", text);
+        machine.encrypt_document(&text)
+    } else {
+        let clean_input = clean_text(&text);
+        info!("Decrypting: '{}'", clean_input);
+        if format.no_group {
+            machine.set_output_grouping(None);
+        }
+        machine.decrypt(&clean_input)
+    };
+    write_result(&result, output)
+}
 
-    let clean_input = clean_text(&text);
-    info!("Decrypting: '{}'", clean_input);
+/// Decrypts with every standard reflector and writes the most English-like result
+///
+/// The ranking of all reflectors is logged first, best candidate on top, so
+/// that only the plaintext ends up on stdout.
+fn handle_guess_reflector(
+    text: &str,
+    output: Option<&mut dyn Write>,
+    args: &MachineArgs,
+    format: &FormatArgs,
+) -> Result<(), String> {
+    let config = build_config(args)?;
+    let candidates = if args.allow_duplicate_rotors {
+        try_reflectors_allowing_duplicates(&config, text)
+    } else {
+        try_reflectors(&config, text)
+    };
+    let Some((best, plaintext, _)) = candidates.first() else {
+        return Err("No reflector could be used with these settings".to_string());
+    };
 
-    let result = machine.decrypt(&clean_input);
-    println!("Result: {}", result);
+    info!("Reflector | Chi-squared");
+    for (reflector, _, score) in &candidates {
+        info!("{:>9} | {:.2}", reflector, score);
+    }
+    info!("Most likely reflector: {}", best);
 
-    Ok(())
+    let result = if format.no_group {
+        plaintext.clone()
+    } else {
+        group_text(plaintext, 5)
+    };
+    write_result(&result, output)
 }
 
-/// Parses rotor positions from a string
-fn parse_positions(positions: &str) -> Result<[char; 3], String> {
-    if positions.len() != 3 {
-        return Err("Position string must be exactly 3 characters long".to_string());
+/// Opens the input source selected on the command line
+///
+/// A positional text is used directly, `--input` opens a file and
+/// `-` or a missing text reads from stdin.
+fn open_input(io: &IoArgs) -> Result<Box<dyn Read>, String> {
+    if let Some(path) = &io.input {
+        let file = File::open(path)
+            .map_err(|e| format!("Cannot open input file {}: {}", path.display(), e))?;
+        return Ok(Box::new(file));
     }
 
-    let chars: Vec<char> = positions.chars().collect();
-    if !chars.iter().all(|&c| c.is_ascii_alphabetic()) {
-        return Err("Position string may only contain letters".to_string());
+    match io.text.as_deref() {
+        None | Some("-") => Ok(Box::new(io::stdin())),
+        Some(text) => Ok(Box::new(Cursor::new(text.to_string().into_bytes()))),
     }
+}
+
+/// Opens the output file selected with `--output`, if any
+fn open_output(io: &IoArgs) -> Result<Option<File>, String> {
+    io.output
+        .as_ref()
+        .map(|path| {
+            File::create(path)
+                .map_err(|e| format!("Cannot create output file {}: {}", path.display(), e))
+        })
+        .transpose()
+}
 
-    Ok([chars[0], chars[1], chars[2]])
+/// Log target that writes every record to stderr and to a log file
+struct TeeWriter {
+    file: File,
 }
 
-/// Parses rotor types from a string
-fn parse_rotors(rotors: &str) -> Result<[&str; 3], String> {
-    let parts: Vec<&str> = rotors.split(',').collect();
-    if parts.len() != 3 {
-        return Err("Rotor string must contain exactly 3 types (comma-separated)".to_string());
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write_all(buf)?;
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()?;
+        self.file.flush()
     }
+}
+
+/// Opens the log file selected with `--log-file` for appending
+fn open_log_file(path: &PathBuf) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Cannot open log file {}: {}", path.display(), e))
+}
+
+/// Reads the complete text from a reader
+fn read_text(input: &mut impl Read) -> Result<String, String> {
+    let mut text = String::new();
+    input
+        .read_to_string(&mut text)
+        .map_err(|e| format!("Cannot read input: {}", e))?;
+    Ok(text)
+}
 
-    for &rotor in &parts {
-        if !["I", "II", "III", "IV", "V"].contains(&rotor) {
-            return Err(format!("Invalid rotor type: {}", rotor));
+/// Writes the result to the output file or prints it to stdout
+fn write_result(result: &str, output: Option<&mut dyn Write>) -> Result<(), String> {
+    match output {
+        Some(writer) => {
+            writeln!(writer, "{}", result).map_err(|e| format!("Cannot write output: {}", e))
+        }
+        None => {
+            println!("Result: {}", result);
+            Ok(())
         }
     }
+}
+
+/// CLI handler for verification
+///
+/// Returns whether the decryption matches. On a mismatch both texts are
+/// printed with a marker line under every differing letter.
+fn handle_verify(ciphertext: &str, expected: &str, args: &MachineArgs) -> Result<bool, String> {
+    let mut machine = build_machine(args)?;
+    machine.set_output_grouping(None);
+
+    let actual = machine.decrypt(&clean_text(ciphertext));
+    let expected = clean_text(expected);
+    if actual == expected {
+        println!("OK: ciphertext decrypts to the expected plaintext");
+        return Ok(true);
+    }
+
+    let length = actual.len().max(expected.len());
+    let markers: String = (0..length)
+        .map(|i| {
+            if actual.as_bytes().get(i) == expected.as_bytes().get(i) {
+                ' '
+            } else {
+                '^'
+            }
+        })
+        .collect();
+    let mismatches = markers.chars().filter(|&c| c == '^').count();
+
+    println!("MISMATCH: {} of {} letters differ", mismatches, length);
+    println!("Expected: {}", expected);
+    println!("Actual:   {}", actual);
+    println!("          {}", markers.trim_end());
+    Ok(false)
+}
+
+/// CLI handler for the configuration report
+fn handle_info(args: &MachineArgs) -> Result<(), String> {
+    let machine = build_machine(args)?;
+
+    let rings: Vec<String> = machine
+        .rotors
+        .iter()
+        .map(|rotor| format!("{:02}", rotor.get_ring_setting_number()))
+        .collect();
+
+    println!("{}", machine);
+    println!("Ring settings (01-26): {}", rings.join(" "));
+    println!("Fingerprint: {}", machine.configuration_fingerprint());
+
+    Ok(())
+}
+
+/// Number of plugboard cables issued with a daily key
+const KEY_SHEET_CABLES: usize = 10;
+
+/// Creates one random key-sheet line
+///
+/// Picks three different rotors out of I-V, random ring settings (printed
+/// as numbers 01-26 like on the historical sheets) and a 10-cable plugboard.
+fn random_key_line(day: usize, rng: &mut impl Rng) -> String {
+    let mut rotors = ["I", "II", "III", "IV", "V"];
+    rotors.shuffle(rng);
+
+    let rings: Vec<String> = (0..3)
+        .map(|_| format!("{:02}", rng.random_range(1..=26)))
+        .collect();
+
+    format!(
+        "{:>3} | {:<12} | {} | {}",
+        day,
+        rotors[..3].join(" "),
+        rings.join(" "),
+        generate_random_plugboard_cables_with(KEY_SHEET_CABLES, rng)
+    )
+}
+
+/// CLI handler for key generation
+///
+/// With a seed the same key sheet is printed on every run.
+fn handle_genkey(count: usize, seed: Option<u64>) -> Result<(), String> {
+    if count == 0 {
+        return Err("Count must be at least 1".to_string());
+    }
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+
+    println!("Day | Rotors       | Rings    | Plugboard");
+    for day in 1..=count {
+        println!("{}", random_key_line(day, &mut rng));
+    }
 
-    Ok([parts[0], parts[1], parts[2]])
+    Ok(())
 }
 
 /// Starts the GUI application
-fn start_gui() -> Result<(), eframe::Error> {
+fn start_gui(log_sink: Option<gui::LogSink>) -> Result<(), eframe::Error> {
     info!("Starting GUI application");
 
     let options = eframe::NativeOptions {
@@ -195,7 +489,12 @@ fn start_gui() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Enigma Simulator",
         options,
-        Box::new(|_cc| Box::new(gui::EnigmaApp::new())),
+        Box::new(|_cc| {
+            Box::new(match log_sink {
+                Some(sink) => gui::EnigmaApp::with_log_sink(sink),
+                None => gui::EnigmaApp::new(),
+            })
+        }),
     )
 }
 
@@ -205,9 +504,20 @@ fn main() {
 
     // Initialize logger
     let env = Env::default().filter_or("RUST_LOG", if cli.verbose { "debug" } else { "info" });
-    Builder::from_env(env)
+    let mut builder = Builder::from_env(env);
+    if let Some(path) = &cli.log_file {
+        match open_log_file(path) {
+            Ok(file) => {
+                builder.target(Target::Pipe(Box::new(TeeWriter { file })));
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let console_logger = builder
         .format(|buf, record| {
-            use std::io::Write;
             writeln!(
                 buf,
                 "[{}] {}: {}",
@@ -216,42 +526,92 @@ fn main() {
                 record.args()
             )
         })
-        .init();
+        .build();
+    let cli_mode = cli.cli || cli.command.is_some();
+
+    // In GUI mode the log panel receives every record, down to trace; the
+    // forwarded console logger still applies its own filter
+    let log_sink = if cli_mode {
+        log::set_max_level(console_logger.filter());
+        log::set_boxed_logger(Box::new(console_logger)).ok();
+        None
+    } else {
+        let appender = gui::GuiLogAppender::with_forward(Box::new(console_logger));
+        let sink = appender.get_log_entries();
+        log::set_max_level(LevelFilter::Trace);
+        log::set_boxed_logger(Box::new(appender)).ok().map(|_| sink)
+    };
 
     info!("Enigma simulator started");
 
     // Start GUI or CLI
-    if cli.cli || cli.command.is_some() {
+    if cli_mode {
         // CLI mode
         match cli.command {
             Some(Commands::Encrypt {
-                text,
-                positions,
-                rings,
-                plugboard,
-                rotors,
-                reflector,
+                io,
+                format,
+                machine,
             }) => {
-                if let Err(e) = handle_encrypt(text, positions, rings, plugboard, rotors, reflector)
-                {
+                let result = open_input(&io).and_then(|input| {
+                    let mut output = open_output(&io)?;
+                    handle_encrypt(
+                        input,
+                        output.as_mut().map(|file| file as &mut dyn Write),
+                        &machine,
+                        &format,
+                    )
+                });
+                if let Err(e) = result {
                     error!("Encryption error: {}", e);
                     std::process::exit(1);
                 }
             }
             Some(Commands::Decrypt {
-                text,
-                positions,
-                rings,
-                plugboard,
-                rotors,
-                reflector,
+                io,
+                format,
+                guess_reflector,
+                machine,
             }) => {
-                if let Err(e) = handle_decrypt(text, positions, rings, plugboard, rotors, reflector)
-                {
+                let result = open_input(&io).and_then(|input| {
+                    let mut output = open_output(&io)?;
+                    handle_decrypt(
+                        input,
+                        output.as_mut().map(|file| file as &mut dyn Write),
+                        &machine,
+                        &format,
+                        guess_reflector,
+                    )
+                });
+                if let Err(e) = result {
                     error!("Decryption error: {}", e);
                     std::process::exit(1);
                 }
             }
+            Some(Commands::Verify {
+                ciphertext,
+                expected,
+                machine,
+            }) => match handle_verify(&ciphertext, &expected, &machine) {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    error!("Verification error: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            Some(Commands::Info { machine }) => {
+                if let Err(e) = handle_info(&machine) {
+                    error!("Configuration error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Some(Commands::Genkey { count, seed }) => {
+                if let Err(e) = handle_genkey(count, seed) {
+                    error!("Key generation error: {}", e);
+                    std::process::exit(1);
+                }
+            }
             None => {
                 println!("No command specified. Use --help for help.");
                 std::process::exit(1);
@@ -259,7 +619,7 @@ fn main() {
         }
     } else {
         // GUI mode (default)
-        if let Err(e) = start_gui() {
+        if let Err(e) = start_gui(log_sink) {
             error!("GUI error: {}", e);
             std::process::exit(1);
         }
@@ -4,7 +4,29 @@
 //! processing by the rotors.
 
 use crate::utils::{index_to_letter, letter_to_index};
-use log::trace;
+
+/// Number of cables used in operational practice (default limit)
+pub const DEFAULT_MAX_CONNECTIONS: usize = 10;
+
+/// Number of cables that connect every letter in pairs
+pub const FULL_CONNECTIONS: usize = 13;
+
+/// Number of cables the Enigma Uhr accepts
+pub const UHR_CABLES: usize = 10;
+
+/// Number of dial positions of the Enigma Uhr
+pub const UHR_POSITIONS: usize = 40;
+
+/// Wiring of the Uhr's rotating disc (contact -> contact)
+///
+/// The a-plug of cable `n` uses the contacts `4n` (from the keyboard) and
+/// `4n + 1` (towards the rotors). The contacts of the b-plugs are derived from
+/// the disc at dial position 00, so that this position behaves exactly like
+/// the ordinary plugboard.
+const UHR_WIRING: [usize; UHR_POSITIONS] = [
+    6, 31, 4, 29, 18, 39, 16, 25, 30, 23, 28, 1, 38, 11, 36, 37, 26, 27, 24, 21, 14, 3, 12, 17, 2,
+    7, 0, 33, 10, 35, 8, 5, 22, 19, 20, 13, 34, 15, 32, 9,
+];
 
 /// Represents the Enigma plugboard
 #[derive(Debug, Clone)]
@@ -13,6 +35,27 @@ pub struct Plugboard {
     connections: [Option<usize>; 26],
     /// Die Anzahl der aktiven Verbindungen
     pub connection_count: usize,
+    /// Die maximale Anzahl an Verbindungen (Kabeln)
+    max_connections: usize,
+    /// Die Stellung der Enigma Uhr (0-39), oder `None` ohne Uhr
+    uhr_setting: Option<u8>,
+}
+
+/// Zwei Steckerbretter sind gleich, wenn sie gleich verdrahtet sind und
+/// dieselbe Uhr-Stellung haben. Die erlaubte Kabelanzahl beeinflusst die
+/// Verschlüsselung nicht und wird daher nicht verglichen.
+impl PartialEq for Plugboard {
+    fn eq(&self, other: &Self) -> bool {
+        self.connections == other.connections && self.uhr_setting == other.uhr_setting
+    }
+}
+
+impl Eq for Plugboard {}
+
+impl Default for Plugboard {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Plugboard {
@@ -21,12 +64,40 @@ impl Plugboard {
     /// # Returns
     /// * Ein neues Steckerbrett ohne Verbindungen
     pub fn new() -> Self {
+        Self::empty(DEFAULT_MAX_CONNECTIONS)
+    }
+
+    /// Erstellt ein leeres Steckerbrett ohne Prüfung der Kabelanzahl
+    fn empty(max_connections: usize) -> Self {
         Plugboard {
             connections: [None; 26],
             connection_count: 0,
+            max_connections,
+            uhr_setting: None,
         }
     }
 
+    /// Erstellt ein leeres Steckerbrett mit einer eigenen Kabelanzahl
+    ///
+    /// Historisch wurden meist 10 Kabel gesteckt; mit 13 lassen sich alle
+    /// Buchstaben paarweise verbinden.
+    ///
+    /// # Arguments
+    /// * `max_connections` - Die maximale Anzahl an Verbindungen (höchstens 13)
+    ///
+    /// # Returns
+    /// * `Result<Plugboard, String>` - Ein neues Steckerbrett ohne
+    ///   Verbindungen oder ein Fehler bei mehr als 13 Kabeln
+    pub fn with_max_connections(max_connections: usize) -> Result<Self, String> {
+        if max_connections > FULL_CONNECTIONS {
+            return Err(format!(
+                "Höchstens {} Kabel möglich, angefragt: {}",
+                FULL_CONNECTIONS, max_connections
+            ));
+        }
+        Ok(Self::empty(max_connections))
+    }
+
     /// Erstellt ein Steckerbrett aus einem Verbindungsstring
     ///
     /// # Arguments
@@ -35,31 +106,70 @@ impl Plugboard {
     /// # Returns
     /// * `Result<Plugboard, String>` - Das erstellte Steckerbrett oder ein Fehler
     pub fn from_string(connections: &str) -> Result<Self, String> {
-        let mut plugboard = Plugboard::new();
+        Self::from_string_with_max(connections, DEFAULT_MAX_CONNECTIONS)
+    }
+
+    /// Erstellt ein Steckerbrett aus einem Verbindungsstring mit eigener Kabelanzahl
+    ///
+    /// Die Paare dürfen durch beliebigen Leerraum getrennt sein, auch durch
+    /// Zeilenumbrüche (z.B. aus einem mehrzeiligen Eingabefeld). Ein leerer
+    /// oder nur aus Leerraum bestehender String ergibt ein leeres Steckerbrett.
+    ///
+    /// # Arguments
+    /// * `connections` - String mit Verbindungen (z.B. "AB CD EF")
+    /// * `max_connections` - Die maximale Anzahl an Verbindungen
+    ///
+    /// # Returns
+    /// * `Result<Plugboard, String>` - Das erstellte Steckerbrett oder ein Fehler
+    pub fn from_string_with_max(connections: &str, max_connections: usize) -> Result<Self, String> {
+        let mut plugboard = Plugboard::with_max_connections(max_connections)?;
 
         if connections.trim().is_empty() {
             return Ok(plugboard);
         }
 
         for connection in connections.split_whitespace() {
-            if connection.len() != 2 {
+            let chars: Vec<char> = connection.chars().collect();
+
+            if !chars.iter().all(|c| c.is_ascii_alphabetic()) {
                 return Err(format!(
-                    "Verbindung '{}' muss genau 2 Zeichen lang sein",
+                    "Verbindung '{}' darf nur Buchstaben enthalten",
                     connection
                 ));
             }
 
-            let chars: Vec<char> = connection.chars().collect();
-            let first = chars[0];
-            let second = chars[1];
-
-            if !first.is_ascii_alphabetic() || !second.is_ascii_alphabetic() {
+            if chars.len() != 2 {
+                let hint = if chars.len() % 2 == 1 {
+                    "ungerade Anzahl Buchstaben"
+                } else {
+                    "mehrere Paare ohne Trennung"
+                };
                 return Err(format!(
-                    "Verbindung '{}' darf nur Buchstaben enthalten",
-                    connection
+                    "Verbindung '{}' muss genau 2 Buchstaben lang sein ({}); Paare durch Leerzeichen trennen, z.B. \"AB CD\"",
+                    connection, hint
                 ));
             }
 
+            plugboard.add_connection(chars[0], chars[1])?;
+        }
+
+        Ok(plugboard)
+    }
+
+    /// Erstellt ein Steckerbrett aus einer Liste von Buchstabenpaaren
+    ///
+    /// Jedes Paar wird wie bei `add_connection` geprüft (keine doppelten
+    /// Buchstaben, keine Verbindung mit sich selbst, höchstens 10 Kabel).
+    ///
+    /// # Arguments
+    /// * `pairs` - Die Paare, z.B. `&[('A', 'B'), ('C', 'D')]`
+    ///
+    /// # Returns
+    /// * `Result<Plugboard, String>` - Das erstellte Steckerbrett oder ein Fehler
+    pub fn from_pairs(pairs: &[(char, char)]) -> Result<Self, String> {
+        let mut plugboard = Plugboard::new();
+
+        for &(first, second) in pairs {
             plugboard.add_connection(first, second)?;
         }
 
@@ -75,15 +185,16 @@ impl Plugboard {
     /// # Returns
     /// * `Result<(), String>` - Erfolg oder Fehler
     pub fn add_connection(&mut self, first: char, second: char) -> Result<(), String> {
-        if first == second {
-            return Err("Ein Buchstabe kann nicht mit sich selbst verbunden werden".to_string());
-        }
-
         let first_index =
             letter_to_index(first).ok_or_else(|| format!("Ungültiger Buchstabe: {}", first))?;
         let second_index =
             letter_to_index(second).ok_or_else(|| format!("Ungültiger Buchstabe: {}", second))?;
 
+        // Compare indices so that e.g. 'a' and 'A' count as the same letter
+        if first_index == second_index {
+            return Err("Ein Buchstabe kann nicht mit sich selbst verbunden werden".to_string());
+        }
+
         // Check if either letter is already connected
         if self.connections[first_index].is_some() {
             return Err(format!("Buchstabe {} ist bereits verbunden", first));
@@ -92,6 +203,13 @@ impl Plugboard {
             return Err(format!("Buchstabe {} ist bereits verbunden", second));
         }
 
+        if self.connection_count >= self.max_connections {
+            return Err(format!(
+                "Maximal {} Verbindungen erlaubt",
+                self.max_connections
+            ));
+        }
+
         // Add the connection
         self.connections[first_index] = Some(second_index);
         self.connections[second_index] = Some(first_index);
@@ -129,6 +247,38 @@ impl Plugboard {
         }
     }
 
+    /// Schaltet eine Verbindung zwischen zwei Buchstaben um
+    ///
+    /// Sind beide Buchstaben frei, werden sie verbunden; sind sie miteinander
+    /// verbunden, wird die Verbindung entfernt.
+    ///
+    /// # Arguments
+    /// * `first` - Der erste Buchstabe
+    /// * `second` - Der zweite Buchstabe
+    ///
+    /// # Returns
+    /// * `Ok(true)` - Die Verbindung besteht jetzt
+    /// * `Ok(false)` - Die Verbindung wurde entfernt
+    /// * `Err` - Ein Buchstabe ist ungültig oder mit einem anderen Buchstaben verbunden
+    pub fn toggle_connection(&mut self, first: char, second: char) -> Result<bool, String> {
+        let first_index =
+            letter_to_index(first).ok_or_else(|| format!("Ungültiger Buchstabe: {}", first))?;
+        let second_index =
+            letter_to_index(second).ok_or_else(|| format!("Ungültiger Buchstabe: {}", second))?;
+
+        if first_index == second_index {
+            return Err("Ein Buchstabe kann nicht mit sich selbst verbunden werden".to_string());
+        }
+
+        if self.connections[first_index] == Some(second_index) {
+            self.remove_connection(first)?;
+            return Ok(false);
+        }
+
+        self.add_connection(first, second)?;
+        Ok(true)
+    }
+
     /// Verarbeitet ein Zeichen durch das Steckerbrett
     ///
     /// # Arguments
@@ -149,6 +299,249 @@ impl Plugboard {
         }
     }
 
+    /// Verarbeitet ein Zeichen auf dem Hinweg (Tastatur -> Rotoren)
+    ///
+    /// Ohne Enigma Uhr entspricht das `process`.
+    ///
+    /// # Arguments
+    /// * `input` - Das Eingabezeichen
+    ///
+    /// # Returns
+    /// * Das verarbeitete Zeichen
+    pub fn process_forward(&self, input: char) -> char {
+        match self.uhr_setting {
+            Some(setting) => self.uhr_forward(input, setting as usize),
+            None => self.process(input),
+        }
+    }
+
+    /// Indexbasierte Variante von `process_forward` (0-25)
+    ///
+    /// Ohne Enigma Uhr ist das ein einfacher Tabellenzugriff.
+    #[inline]
+    pub fn process_forward_index(&self, input: u8) -> u8 {
+        match self.uhr_setting {
+            None => self.connections[input as usize].map_or(input, |target| target as u8),
+            Some(_) => Self::letter_as_index(self.process_forward(Self::index_as_letter(input))),
+        }
+    }
+
+    /// Indexbasierte Variante von `process_backward` (0-25)
+    #[inline]
+    pub fn process_backward_index(&self, input: u8) -> u8 {
+        match self.uhr_setting {
+            None => self.connections[input as usize].map_or(input, |target| target as u8),
+            Some(_) => Self::letter_as_index(self.process_backward(Self::index_as_letter(input))),
+        }
+    }
+
+    /// Wandelt einen Index (0-25) für die zeichenbasierten Uhr-Funktionen um
+    fn index_as_letter(index: u8) -> char {
+        index_to_letter(index as usize).unwrap_or('A')
+    }
+
+    /// Wandelt das Ergebnis der zeichenbasierten Uhr-Funktionen zurück
+    fn letter_as_index(letter: char) -> u8 {
+        letter_to_index(letter).unwrap_or(0) as u8
+    }
+
+    /// Verarbeitet ein Zeichen auf dem Rückweg (Rotoren -> Lampen)
+    ///
+    /// Mit Enigma Uhr ist das die Umkehrung von `process_forward`, die sich
+    /// bei den meisten Stellungen vom Hinweg unterscheidet.
+    ///
+    /// # Arguments
+    /// * `input` - Das Eingabezeichen
+    ///
+    /// # Returns
+    /// * Das verarbeitete Zeichen
+    pub fn process_backward(&self, input: char) -> char {
+        match self.uhr_setting {
+            Some(setting) => {
+                let target = input.to_ascii_uppercase();
+                (b'A'..=b'Z')
+                    .map(|b| b as char)
+                    .find(|&candidate| self.uhr_forward(candidate, setting as usize) == target)
+                    .unwrap_or(input)
+            }
+            None => self.process(input),
+        }
+    }
+
+    /// Schaltet die Enigma Uhr ein oder aus
+    ///
+    /// Die Uhr ersetzt die zehn Kabel des Steckerbretts durch eine drehbare
+    /// Scheibe mit 40 Stellungen. Die Kabel werden in der Reihenfolge von
+    /// `get_connections_string` nummeriert; der erste Buchstabe jedes Paars
+    /// steckt im a-Stecker, der zweite im b-Stecker. In Stellung 0 verhält
+    /// sich die Uhr wie ein normales Steckerbrett, in allen anderen
+    /// Stellungen ist die Vertauschung nicht mehr wechselseitig.
+    ///
+    /// # Arguments
+    /// * `setting` - Die Stellung (0-39), oder `None` zum Ausschalten
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Erfolg oder Fehler
+    pub fn set_uhr_setting(&mut self, setting: Option<u8>) -> Result<(), String> {
+        if let Some(value) = setting {
+            if value as usize >= UHR_POSITIONS {
+                return Err(format!(
+                    "Uhr-Stellung muss zwischen 0 und {} liegen",
+                    UHR_POSITIONS - 1
+                ));
+            }
+            if self.connection_count > UHR_CABLES {
+                return Err(format!(
+                    "Die Enigma Uhr unterstützt höchstens {} Verbindungen",
+                    UHR_CABLES
+                ));
+            }
+        }
+
+        self.uhr_setting = setting;
+        debug!("Uhr-Stellung gesetzt auf {:?}", setting);
+        Ok(())
+    }
+
+    /// Gibt die Stellung der Enigma Uhr zurück
+    ///
+    /// # Returns
+    /// * Die Stellung (0-39), oder `None` ohne Uhr
+    pub fn get_uhr_setting(&self) -> Option<u8> {
+        self.uhr_setting
+    }
+
+    /// Berechnet den Hinweg durch die Enigma Uhr
+    ///
+    /// Sind weniger als zehn Kabel gesteckt, wird die Scheibe so oft
+    /// durchlaufen, bis das Signal an einem gesteckten Kabel ankommt.
+    fn uhr_forward(&self, input: char, setting: usize) -> char {
+        let Some(input_index) = letter_to_index(input) else {
+            return input;
+        };
+
+        let mut cables = self.cable_pairs();
+        // Kabel jenseits der zehn Uhr-Stecker wirken wie normale Steckerkabel
+        cables.truncate(UHR_CABLES);
+
+        let Some((cable, is_a_plug)) =
+            cables
+                .iter()
+                .enumerate()
+                .find_map(|(n, &(a, b))| match input_index {
+                    i if i == a => Some((n, true)),
+                    i if i == b => Some((n, false)),
+                    _ => None,
+                })
+        else {
+            return self.process(input);
+        };
+
+        let mut current = cable;
+        loop {
+            let next = Self::uhr_cable_target(current, is_a_plug, setting);
+            if next < cables.len() {
+                let (a, b) = cables[next];
+                // Ein a-Stecker führt immer zu einem b-Stecker und umgekehrt
+                let output = if is_a_plug { b } else { a };
+                return index_to_letter(output).unwrap_or(input);
+            }
+            current = next;
+        }
+    }
+
+    /// Gibt an, an welchem Kabel ein Signal die Uhr-Scheibe verlässt
+    fn uhr_cable_target(cable: usize, from_a_plug: bool, setting: usize) -> usize {
+        let entry = if from_a_plug {
+            4 * cable
+        } else {
+            // Kontakt des b-Steckers, der in Stellung 0 zum a-Stecker `cable` führt
+            UHR_WIRING
+                .iter()
+                .position(|&target| target == 4 * cable + 1)
+                .unwrap_or(0)
+        };
+
+        let exit = (UHR_WIRING[(entry + setting) % UHR_POSITIONS] + UHR_POSITIONS - setting)
+            % UHR_POSITIONS;
+
+        if from_a_plug {
+            // Zielkontakt ist der Rotor-Kontakt eines b-Steckers
+            (0..UHR_CABLES)
+                .find(|&n| UHR_WIRING[4 * n] == exit)
+                .unwrap_or(0)
+        } else {
+            // Zielkontakt ist der Rotor-Kontakt 4n + 1 eines a-Steckers
+            exit / 4
+        }
+    }
+
+    /// Gibt die gesteckten Kabel als Indexpaare in Uhr-Nummerierung zurück
+    fn cable_pairs(&self) -> Vec<(usize, usize)> {
+        self.connections
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &connection)| match connection {
+                Some(target) if i < target => Some((i, target)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns all active connections as letter pairs
+    ///
+    /// Each cable is listed once, with the alphabetically smaller letter first.
+    ///
+    /// # Returns
+    /// * Die Verbindungen, z.B. `[('A', 'B'), ('C', 'D')]`
+    pub fn connections(&self) -> Vec<(char, char)> {
+        self.cable_pairs()
+            .into_iter()
+            .map(|(a, b)| {
+                (
+                    index_to_letter(a).unwrap_or('A'),
+                    index_to_letter(b).unwrap_or('A'),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the plugboard as a permutation of the letter indices
+    ///
+    /// Entry `i` is the index the letter with index `i` is sent to on the way
+    /// from the keyboard to the rotors; unconnected letters map to themselves.
+    /// With the Enigma Uhr switched on, the way back uses the inverse of
+    /// this permutation.
+    ///
+    /// # Returns
+    /// * Die Abbildung A-Z als Indizes 0-25
+    pub fn as_permutation(&self) -> [usize; 26] {
+        std::array::from_fn(|i| self.process_forward_index(i as u8) as usize)
+    }
+
+    /// Checks that the wiring is a valid involution
+    ///
+    /// Every connected letter must point to a different letter that points
+    /// back to it, and the cable count must match the number of pairs.
+    ///
+    /// # Returns
+    /// * `true` - If the connections are consistent
+    /// * `false` - If a connection is one-sided, a self-connection or miscounted
+    pub fn is_valid_involution(&self) -> bool {
+        let symmetric =
+            self.connections
+                .iter()
+                .enumerate()
+                .all(|(i, &connection)| match connection {
+                    Some(target) => {
+                        target < 26 && target != i && self.connections[target] == Some(i)
+                    }
+                    None => true,
+                });
+
+        symmetric && self.cable_pairs().len() == self.connection_count
+    }
+
     /// Returns all active connections as a string
     ///
     /// # Returns
@@ -180,6 +573,14 @@ impl Plugboard {
         self.connection_count
     }
 
+    /// Returns the maximum number of connections
+    ///
+    /// # Returns
+    /// * Die maximale Anzahl an Verbindungen
+    pub fn get_max_connections(&self) -> usize {
+        self.max_connections
+    }
+
     /// Checks if a letter is connected
     ///
     /// # Arguments
@@ -196,6 +597,32 @@ impl Plugboard {
         }
     }
 
+    /// Returns the letter a letter is plugged to
+    ///
+    /// # Arguments
+    /// * `letter` - The letter to look up (A-Z, Groß- oder Kleinschreibung)
+    ///
+    /// # Returns
+    /// * `Some(partner)` - Der verbundene Buchstabe
+    /// * `None` - Wenn der Buchstabe nicht gesteckt oder ungültig ist
+    pub fn partner_of(&self, letter: char) -> Option<char> {
+        let index = letter_to_index(letter)?;
+        self.connections[index].and_then(index_to_letter)
+    }
+
+    /// Returns all letters without a cable
+    ///
+    /// # Returns
+    /// * Die freien Buchstaben in alphabetischer Reihenfolge
+    pub fn unconnected_letters(&self) -> Vec<char> {
+        self.connections
+            .iter()
+            .enumerate()
+            .filter(|(_, connection)| connection.is_none())
+            .filter_map(|(index, _)| index_to_letter(index))
+            .collect()
+    }
+
     /// Clears all connections
     pub fn clear(&mut self) {
         self.connections = [None; 26];
@@ -203,3 +630,10 @@ impl Plugboard {
         trace!("Steckerbrett geleert");
     }
 }
+
+/// Gibt die Verbindungen aus (z.B. "AB CD EF")
+impl std::fmt::Display for Plugboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.get_connections_string())
+    }
+}
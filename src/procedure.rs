@@ -0,0 +1,186 @@
+//! Vollständiger Funkspruch nach dem Verfahren mit verdoppeltem Spruchschlüssel
+//!
+//! Bis 1940 wählte der Funker für jeden Spruch einen zufälligen
+//! Spruchschlüssel, verschlüsselte ihn zweimal hintereinander in der
+//! Grundstellung des Tagesschlüssels und stellte das Ergebnis dem Spruch
+//! voran. Der eigentliche Text wurde anschließend mit dem Spruchschlüssel
+//! verschlüsselt und in Fünfergruppen übermittelt.
+//!
+//! Als erste Textgruppe stand unverschlüsselt die Kenngruppe: zwei
+//! beliebige Füllbuchstaben und die drei Buchstaben der Kenngruppe vom
+//! Schlüsselblatt. Daran erkannte der Empfänger, welcher Schlüssel galt.
+
+use crate::machine::{factory, MachineConfig};
+use crate::session::{encrypt_batch, IndicatorProcedure};
+use crate::utils::{clean_text, generate_random_settings_with};
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+/// Anzahl der Buchstaben einer Kenngruppe auf dem Schlüsselblatt
+pub const KENNGRUPPE_LETTERS: usize = 3;
+
+/// Anzahl der Füllbuchstaben vor der Kenngruppe
+const FILLER_LETTERS: usize = 2;
+
+/// Ein übermittelbarer Funkspruch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedMessage {
+    /// Der verdoppelt verschlüsselte Spruchschlüssel, z.B. "PEVXKM"
+    pub indicator: String,
+    /// Die unverschlüsselte erste Textgruppe: zwei Füllbuchstaben und die
+    /// Kenngruppe, z.B. "XYKTM" für die Kenngruppe "KTM"
+    pub kenngruppe: String,
+    /// Der Geheimtext in Fünfergruppen
+    pub body: String,
+}
+
+impl FormattedMessage {
+    /// Gibt die Kenngruppe ohne Füllbuchstaben zurück
+    ///
+    /// # Returns
+    /// * Die drei Buchstaben vom Schlüsselblatt, z.B. "KTM"
+    pub fn discriminant(&self) -> &str {
+        &self.kenngruppe[self.kenngruppe.len().saturating_sub(KENNGRUPPE_LETTERS)..]
+    }
+}
+
+/// Formatiert den Spruch wie gesendet: Indikator, Kenngruppe, dann die Textgruppen
+impl fmt::Display for FormattedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.indicator, self.kenngruppe)?;
+        if !self.body.is_empty() {
+            write!(f, " {}", self.body)?;
+        }
+        Ok(())
+    }
+}
+
+/// Liest einen empfangenen Spruch: die erste Gruppe ist der Indikator, die
+/// zweite die Kenngruppe, alle weiteren Gruppen bilden den Text
+impl FromStr for FormattedMessage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut groups = s.split_whitespace();
+        let indicator = groups
+            .next()
+            .map(clean_text)
+            .filter(|indicator| !indicator.is_empty())
+            .ok_or_else(|| "Funkspruch enthält keinen Indikator".to_string())?;
+
+        let kenngruppe = groups
+            .next()
+            .ok_or_else(|| "Funkspruch enthält keine Kenngruppe".to_string())?;
+        if kenngruppe.len() != FILLER_LETTERS + KENNGRUPPE_LETTERS
+            || !kenngruppe.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return Err(format!(
+                "Kenngruppe '{}' muss aus {} Buchstaben bestehen",
+                kenngruppe,
+                FILLER_LETTERS + KENNGRUPPE_LETTERS
+            ));
+        }
+
+        Ok(FormattedMessage {
+            indicator,
+            kenngruppe: kenngruppe.to_ascii_uppercase(),
+            body: groups.collect::<Vec<_>>().join(" "),
+        })
+    }
+}
+
+/// Verschlüsselt einen Klartext als vollständigen Funkspruch
+///
+/// Wählt einen zufälligen Spruchschlüssel (ein Buchstabe pro Rotor),
+/// verschlüsselt ihn verdoppelt in der Grundstellung des Tagesschlüssels
+/// und verschlüsselt den Text anschließend mit dem Spruchschlüssel. Vor den
+/// Text kommt die Kenngruppe mit zwei zufälligen Füllbuchstaben.
+///
+/// # Arguments
+/// * `daily` - Der Tagesschlüssel; `rotor_positions` ist die Grundstellung
+/// * `kenngruppe` - Die drei Buchstaben der Kenngruppe vom Schlüsselblatt
+/// * `plaintext` - Der Klartext (wird bereinigt)
+/// * `rng` - Die Zufallsquelle für Spruchschlüssel und Füllbuchstaben
+///
+/// # Returns
+/// * `Result<FormattedMessage, String>` - Der Spruch oder ein Fehler bei
+///   ungültigem Tagesschlüssel oder ungültiger Kenngruppe
+pub fn encode_message<R: Rng + ?Sized>(
+    daily: &MachineConfig,
+    kenngruppe: &str,
+    plaintext: &str,
+    rng: &mut R,
+) -> Result<FormattedMessage, String> {
+    if kenngruppe.len() != KENNGRUPPE_LETTERS
+        || !kenngruppe.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        return Err(format!(
+            "Kenngruppe '{}' muss aus {} Buchstaben bestehen",
+            kenngruppe, KENNGRUPPE_LETTERS
+        ));
+    }
+    let filler: String = generate_random_settings_with(FILLER_LETTERS, rng)
+        .into_iter()
+        .collect();
+
+    let message_key: String = generate_random_settings_with(daily.rotor_types.len(), rng)
+        .into_iter()
+        .collect();
+    debug!("Spruchschlüssel gewählt: {}", message_key);
+
+    let encrypted = encrypt_batch(
+        daily,
+        &[(message_key.as_str(), plaintext)],
+        IndicatorProcedure::DoubledKey,
+    )?
+    .pop()
+    .ok_or_else(|| "Funkspruch konnte nicht verschlüsselt werden".to_string())?;
+
+    Ok(FormattedMessage {
+        indicator: encrypted.indicator.unwrap_or_default(),
+        kenngruppe: format!("{}{}", filler, kenngruppe.to_ascii_uppercase()),
+        body: encrypted.ciphertext.trim_end().to_string(),
+    })
+}
+
+/// Entschlüsselt einen Funkspruch mit verdoppeltem Spruchschlüssel
+///
+/// Der Indikator wird in der Grundstellung entschlüsselt; beide Hälften
+/// müssen denselben Spruchschlüssel ergeben, sonst ist der Tagesschlüssel
+/// falsch oder der Indikator verstümmelt.
+///
+/// # Arguments
+/// * `daily` - Der Tagesschlüssel; `rotor_positions` ist die Grundstellung
+/// * `message` - Der empfangene Spruch
+///
+/// # Returns
+/// * `Result<String, String>` - Der Klartext ohne Gruppierung oder ein Fehler
+pub fn decode_message(daily: &MachineConfig, message: &FormattedMessage) -> Result<String, String> {
+    let mut machine = factory::from_config(daily)?;
+    let rotor_count = machine.rotors.len();
+
+    let indicator = clean_text(&message.indicator);
+    if indicator.len() != 2 * rotor_count {
+        return Err(format!(
+            "Indikator '{}' muss aus {} Buchstaben bestehen",
+            message.indicator,
+            2 * rotor_count
+        ));
+    }
+
+    let doubled_key = machine.process_exact(&indicator);
+    let (first, second) = doubled_key.split_at(rotor_count);
+    if first != second {
+        return Err(format!(
+            "Spruchschlüssel nicht eindeutig: {} / {} (falscher Tagesschlüssel?)",
+            first, second
+        ));
+    }
+    debug!("Spruchschlüssel entschlüsselt: {}", first);
+
+    let key: Vec<char> = first.chars().collect();
+    machine.set_rotor_positions(&key);
+    machine.set_output_grouping(None);
+    Ok(machine.decrypt(&message.body))
+}
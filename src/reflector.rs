@@ -1,23 +1,32 @@
 //! Reflector implementation for the Enigma machine
 //!
 //! The reflector is a fixed element that routes the signal back to the rotors.
-//! It implements a fixed permutation of the alphabet.
+//! It implements a fixed permutation of the alphabet. Settable reflectors of
+//! later models can additionally be turned like a rotor.
 
 use crate::utils::{index_to_letter, letter_to_index};
-use log::trace;
+use std::fmt;
+use std::str::FromStr;
 
 /// Represents the Enigma reflector
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Reflector {
     /// Die Verdrahtung des Reflektors
     pub wiring: [usize; 26],
     /// Der Name des Reflektors (z.B. "A", "B", "C")
     pub name: String,
+    /// Die Ringstellung (0 bei den festen Reflektoren A, B und C)
+    pub ring_setting: usize,
+    /// Die aktuelle Position (0 bei den festen Reflektoren A, B und C)
+    pub position: usize,
 }
 
 impl Reflector {
     /// Erstellt einen neuen Reflektor mit der angegebenen Verdrahtung
     ///
+    /// Die Verdrahtung muss eine Involution sein: Führt A nach Y, muss auch
+    /// Y nach A führen, sonst wäre die Maschine nicht selbstinvers.
+    ///
     /// # Arguments
     /// * `wiring` - Die Verdrahtung als String (z.B. "EJMZALYXVBWFCRQUONTSPIKHGD")
     /// * `name` - Der Name des Reflektors
@@ -42,10 +51,96 @@ impl Reflector {
             return Err("Verdrahtung muss eine gültige Permutation sein (jeder Buchstabe muss genau einmal als Ziel auftreten)".to_string());
         }
 
-        Ok(Reflector {
+        let reflector = Reflector {
             wiring: wiring_array,
             name: name.to_string(),
-        })
+            ring_setting: 0,
+            position: 0,
+        };
+
+        if !reflector.is_involution() {
+            return Err("Verdrahtung muss eine Involution sein (jede Verbindung muss in beide Richtungen gelten)".to_string());
+        }
+
+        Ok(reflector)
+    }
+
+    /// Prüft, ob die Verdrahtung ihre eigene Umkehrung ist
+    ///
+    /// Nur dann bildet der Reflektor Buchstabenpaare und die Maschine
+    /// entschlüsselt mit denselben Einstellungen, mit denen sie verschlüsselt.
+    /// Fixpunkte werden hier nicht ausgeschlossen.
+    ///
+    /// # Returns
+    /// * `true` - Wenn für jeden Buchstaben `wiring[wiring[i]] == i` gilt
+    /// * `false` - Sonst
+    pub fn is_involution(&self) -> bool {
+        self.wiring
+            .iter()
+            .enumerate()
+            .all(|(i, &target)| self.wiring.get(target) == Some(&i))
+    }
+
+    /// Erstellt einen Reflektor aus 13 Buchstabenpaaren
+    ///
+    /// Jeder Buchstabe muss in genau einem Paar vorkommen, damit die
+    /// Verdrahtung eine Involution ohne Fixpunkte ist. Der Reflektor heißt
+    /// wie bei `reflectors::reflector_custom` "CUSTOM:<Verdrahtung>", sodass
+    /// eine gespeicherte Konfiguration ihn wieder erzeugen kann.
+    ///
+    /// # Arguments
+    /// * `pairs` - Die Paare, durch Leerzeichen getrennt (z.B. "AY BR CU ...")
+    ///
+    /// # Returns
+    /// * `Result<Reflector, String>` - Der erstellte Reflektor oder ein Fehler
+    pub fn from_pairs(pairs: &str) -> Result<Self, String> {
+        let mut partners: [Option<usize>; 26] = [None; 26];
+
+        for pair in pairs.split_whitespace() {
+            let chars: Vec<char> = pair.chars().collect();
+            if chars.len() != 2 {
+                return Err(format!("Paar '{}' muss genau 2 Buchstaben lang sein", pair));
+            }
+
+            let first = letter_to_index(chars[0])
+                .ok_or_else(|| format!("Ungültiger Buchstabe in Paar '{}'", pair))?;
+            let second = letter_to_index(chars[1])
+                .ok_or_else(|| format!("Ungültiger Buchstabe in Paar '{}'", pair))?;
+
+            if first == second {
+                return Err(format!(
+                    "Paar '{}' verbindet einen Buchstaben mit sich selbst (Fixpunkt)",
+                    pair
+                ));
+            }
+
+            for index in [first, second] {
+                if partners[index].is_some() {
+                    return Err(format!(
+                        "Buchstabe {} wird mehrfach verwendet",
+                        index_to_letter(index).unwrap_or('A')
+                    ));
+                }
+            }
+
+            partners[first] = Some(second);
+            partners[second] = Some(first);
+        }
+
+        let mut wiring = String::with_capacity(26);
+        for (i, partner) in partners.iter().enumerate() {
+            match partner {
+                Some(target) => wiring.push(index_to_letter(*target).unwrap_or('A')),
+                None => {
+                    return Err(format!(
+                        "Buchstabe {} ist keinem Paar zugeordnet",
+                        index_to_letter(i).unwrap_or('A')
+                    ))
+                }
+            }
+        }
+
+        reflectors::reflector_custom(&wiring)
     }
 
     /// Reflektiert ein Zeichen
@@ -56,8 +151,8 @@ impl Reflector {
     /// # Returns
     /// * Das reflektierte Zeichen
     pub fn reflect(&self, input: char) -> char {
-        let input_index = letter_to_index(input).unwrap_or(0);
-        let output_index = self.wiring[input_index];
+        let input_index = letter_to_index(input).unwrap_or(0) as u8;
+        let output_index = self.reflect_index(input_index) as usize;
 
         trace!(
             "Reflektor {}: {} -> {}",
@@ -69,6 +164,67 @@ impl Reflector {
         index_to_letter(output_index).unwrap_or('A')
     }
 
+    /// Reflektiert einen Buchstabenindex (0-25)
+    ///
+    /// Indexbasierte Variante von `reflect` für den schnellen Verschlüsselungspfad.
+    ///
+    /// # Arguments
+    /// * `input` - Der Eingabeindex (0-25)
+    ///
+    /// # Returns
+    /// * Der reflektierte Index (0-25)
+    #[inline]
+    pub fn reflect_index(&self, input: u8) -> u8 {
+        let shift = (self.position + 26 - self.ring_setting) % 26;
+        let entry = (input as usize + shift) % 26;
+        ((self.wiring[entry] + 26 - shift) % 26) as u8
+    }
+
+    /// Dreht einen einstellbaren Reflektor um eine Position weiter
+    ///
+    /// Die Maschine ruft diese Methode nicht selbst auf; die festen
+    /// Reflektoren A, B und C bleiben daher in Position 0, solange sie nicht
+    /// ausdrücklich gedreht werden.
+    pub fn step(&mut self) {
+        self.position = (self.position + 1) % 26;
+        trace!(
+            "Reflektor {} gedreht: neue Position {}",
+            self.name,
+            self.position
+        );
+    }
+
+    /// Setzt die Position des Reflektors
+    ///
+    /// # Arguments
+    /// * `position` - Die neue Position (0-25)
+    pub fn set_position(&mut self, position: usize) {
+        if position < 26 {
+            self.position = position;
+        }
+    }
+
+    /// Setzt die Ringstellung des Reflektors
+    ///
+    /// # Arguments
+    /// * `ring_setting` - Die neue Ringstellung (0-25)
+    pub fn set_ring_setting(&mut self, ring_setting: usize) {
+        if ring_setting < 26 {
+            self.ring_setting = ring_setting;
+        }
+    }
+
+    /// Gibt die Verdrahtung als Buchstabenfolge zurück
+    ///
+    /// # Returns
+    /// * Die Verdrahtung, z.B. "YRUHQSLDPXNGOKMIEBFZCWVJAT" für Reflektor B
+    pub fn wiring_string(&self) -> String {
+        self.wiring
+            .iter()
+            .map(|&target| index_to_letter(target).unwrap_or('A'))
+            .collect()
+    }
+
     /// Checks whether the wiring is a valid permutation
     ///
     /// # Arguments
@@ -91,19 +247,24 @@ impl Reflector {
         }
 
         // Check that each index appears exactly once as a source
-        for i in 0..26 {
-            if !targets[i] {
-                return false;
-            }
-        }
+        targets.iter().all(|&hit| hit)
+    }
+}
 
-        true
+/// Gibt den Namen des Reflektors aus
+impl fmt::Display for Reflector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
     }
 }
 
 /// Vordefinierte historische Reflektoren
 pub mod reflectors {
     use super::Reflector;
+    use crate::utils::index_to_letter;
+
+    /// Konstruktor eines vordefinierten Reflektors
+    pub type ReflectorCreator = fn() -> Result<Reflector, String>;
 
     /// Erstellt Reflektor A
     pub fn reflector_a() -> Result<Reflector, String> {
@@ -120,12 +281,199 @@ pub mod reflectors {
         Reflector::new("FVPJIAOYEDRZXWGCTKUQSBNMHL", "C")
     }
 
+    /// Erstellt den dünnen Reflektor B (M4, nur mit Zusatzwalze)
+    pub fn reflector_b_thin() -> Result<Reflector, String> {
+        Reflector::new("ENKQAUYWJICOPBLMDXZVFTHRGS", "B-Thin")
+    }
+
+    /// Erstellt den dünnen Reflektor C (M4, nur mit Zusatzwalze)
+    pub fn reflector_c_thin() -> Result<Reflector, String> {
+        Reflector::new("RDOBJNTKVEHMLFCWZAXGYIPSUQ", "C-Thin")
+    }
+
+    /// Präfix des umsteckbaren Reflektors D in Typangaben ("D:<Paare>")
+    pub const UKW_D_PREFIX: &str = "D:";
+
+    /// Erstellt den umsteckbaren Reflektor D (UKW-D)
+    ///
+    /// Beim UKW-D sind J und Y fest miteinander verbunden; die übrigen 24
+    /// Buchstaben werden im Feld als 12 Paare gesteckt. Der Name enthält die
+    /// vollständige Angabe, damit eine gespeicherte Konfiguration den
+    /// Reflektor wieder erzeugen kann.
+    ///
+    /// # Arguments
+    /// * `pairs` - 12 Paare ohne J und Y, durch Leerzeichen getrennt
+    ///
+    /// # Returns
+    /// * `Result<Reflector, String>` - Der erstellte Reflektor oder ein Fehler
+    pub fn reflector_d(pairs: &str) -> Result<Reflector, String> {
+        let pairs: Vec<String> = pairs
+            .split_whitespace()
+            .map(|pair| pair.to_ascii_uppercase())
+            .collect();
+
+        if pairs.len() != 12 {
+            return Err(format!(
+                "UKW-D benötigt genau 12 Paare, nicht {}",
+                pairs.len()
+            ));
+        }
+
+        if let Some(pair) = pairs.iter().find(|pair| pair.contains(['J', 'Y'])) {
+            return Err(format!(
+                "Paar '{}' ist ungültig: J und Y sind beim UKW-D fest verbunden",
+                pair
+            ));
+        }
+
+        let joined = pairs.join(" ");
+        let mut reflector = Reflector::from_pairs(&format!("{} JY", joined))?;
+        reflector.name = format!("{}{}", UKW_D_PREFIX, joined);
+        Ok(reflector)
+    }
+
+    /// Präfix für Reflektoren mit frei gewählter Verdrahtung ("CUSTOM:<26 Buchstaben>")
+    pub const CUSTOM_PREFIX: &str = "CUSTOM:";
+
+    /// Erstellt einen Reflektor mit frei gewählter Verdrahtung
+    ///
+    /// Die Verdrahtung muss eine Involution ohne Fixpunkte sein, wie sie
+    /// ein echter Reflektor mit 13 Drahtbrücken besitzt. Der Name enthält
+    /// die vollständige Angabe, damit eine gespeicherte Konfiguration den
+    /// Reflektor wieder erzeugen kann.
+    ///
+    /// # Arguments
+    /// * `wiring` - Die Verdrahtung (26 Buchstaben)
+    ///
+    /// # Returns
+    /// * `Result<Reflector, String>` - Der erstellte Reflektor oder ein Fehler
+    pub fn reflector_custom(wiring: &str) -> Result<Reflector, String> {
+        let wiring = wiring.to_ascii_uppercase();
+        let reflector = Reflector::new(&wiring, &format!("{}{}", CUSTOM_PREFIX, wiring))?;
+
+        if let Some(letter) = reflector
+            .wiring
+            .iter()
+            .enumerate()
+            .find(|&(i, &target)| i == target)
+            .and_then(|(i, _)| index_to_letter(i))
+        {
+            return Err(format!(
+                "Reflektor verbindet {} mit sich selbst (Fixpunkt)",
+                letter
+            ));
+        }
+
+        Ok(reflector)
+    }
+
+    /// Returns the names of all available reflectors
+    ///
+    /// In the same order as `available_reflectors`, e.g. for selection lists.
+    pub fn available_reflector_names() -> Vec<&'static str> {
+        available_reflectors()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
     /// Returns all available reflectors
-    pub fn available_reflectors() -> Vec<(&'static str, fn() -> Result<Reflector, String>)> {
+    pub fn available_reflectors() -> Vec<(&'static str, ReflectorCreator)> {
         vec![
-            ("A", reflector_a as fn() -> Result<Reflector, String>),
-            ("B", reflector_b as fn() -> Result<Reflector, String>),
-            ("C", reflector_c as fn() -> Result<Reflector, String>),
+            ("A", reflector_a as ReflectorCreator),
+            ("B", reflector_b as ReflectorCreator),
+            ("C", reflector_c as ReflectorCreator),
+            ("B-Thin", reflector_b_thin as ReflectorCreator),
+            ("C-Thin", reflector_c_thin as ReflectorCreator),
         ]
     }
 }
+
+/// Auswahl eines Reflektors anhand seines Namens
+///
+/// Zentraler Parser für Reflektorangaben in CLI, GUI und Factory: "A", "B",
+/// "C", die dünnen M4-Reflektoren "B-Thin" und "C-Thin" sowie der
+/// umsteckbare UKW-D als "D:<12 Paare>" und frei verdrahtete Reflektoren
+/// als "CUSTOM:<26 Buchstaben>".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReflectorType {
+    A,
+    B,
+    C,
+    /// Dünner Reflektor B (M4)
+    BThin,
+    /// Dünner Reflektor C (M4)
+    CThin,
+    /// Umsteckbarer Reflektor D mit 12 Paaren ohne J und Y
+    D(String),
+    /// Frei gewählte Verdrahtung (26 Buchstaben, Involution ohne Fixpunkte)
+    Custom(String),
+}
+
+impl ReflectorType {
+    /// Checks whether the reflector is a thin M4 reflector
+    pub fn is_thin(&self) -> bool {
+        matches!(self, ReflectorType::BThin | ReflectorType::CThin)
+    }
+
+    /// Erstellt den Reflektor dieses Typs
+    ///
+    /// # Returns
+    /// * `Result<Reflector, String>` - Der erstellte Reflektor oder ein Fehler
+    pub fn create(&self) -> Result<Reflector, String> {
+        use reflectors::*;
+
+        match self {
+            ReflectorType::A => reflector_a(),
+            ReflectorType::B => reflector_b(),
+            ReflectorType::C => reflector_c(),
+            ReflectorType::BThin => reflector_b_thin(),
+            ReflectorType::CThin => reflector_c_thin(),
+            ReflectorType::D(pairs) => reflector_d(pairs),
+            ReflectorType::Custom(wiring) => reflector_custom(wiring),
+        }
+    }
+}
+
+impl FromStr for ReflectorType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "A" => Ok(ReflectorType::A),
+            "B" => Ok(ReflectorType::B),
+            "C" => Ok(ReflectorType::C),
+            "B-Thin" => Ok(ReflectorType::BThin),
+            "C-Thin" => Ok(ReflectorType::CThin),
+            spec if spec.starts_with(reflectors::UKW_D_PREFIX) => {
+                // Die Paare sofort prüfen und in die Form des Reflektornamens bringen
+                let reflector = reflectors::reflector_d(&spec[reflectors::UKW_D_PREFIX.len()..])?;
+                Ok(ReflectorType::D(
+                    reflector.name[reflectors::UKW_D_PREFIX.len()..].to_string(),
+                ))
+            }
+            spec if spec.starts_with(reflectors::CUSTOM_PREFIX) => {
+                let reflector =
+                    reflectors::reflector_custom(&spec[reflectors::CUSTOM_PREFIX.len()..])?;
+                Ok(ReflectorType::Custom(reflector.wiring_string()))
+            }
+            other => Err(format!("Unbekannter Reflektortyp: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for ReflectorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReflectorType::A => write!(f, "A"),
+            ReflectorType::B => write!(f, "B"),
+            ReflectorType::C => write!(f, "C"),
+            ReflectorType::BThin => write!(f, "B-Thin"),
+            ReflectorType::CThin => write!(f, "C-Thin"),
+            ReflectorType::D(pairs) => write!(f, "{}{}", reflectors::UKW_D_PREFIX, pairs),
+            ReflectorType::Custom(wiring) => {
+                write!(f, "{}{}", reflectors::CUSTOM_PREFIX, wiring)
+            }
+        }
+    }
+}
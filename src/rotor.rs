@@ -4,10 +4,11 @@
 //! Each rotor has a wiring, a ring setting, and a position.
 
 use crate::utils::{index_to_letter, letter_to_index};
-use log::{debug, trace};
+use std::fmt;
+use std::str::FromStr;
 
 /// Represents a single Enigma rotor
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rotor {
     /// The rotor's wiring (substitution of A-Z)
     pub wiring: [usize; 26],
@@ -17,10 +18,12 @@ pub struct Rotor {
     pub ring_setting: usize,
     /// The current position
     pub position: usize,
-    /// The letter at the notch (for advancement)
-    pub notch: usize,
+    /// The notch positions (for advancement); rotors VI-VIII have two
+    pub notches: Vec<usize>,
     /// The name of the rotor (e.g. "I", "II", "III")
     pub name: String,
+    /// Whether the rotor never steps (e.g. the M4 Greek rotors Beta/Gamma)
+    pub stationary: bool,
 }
 
 impl Rotor {
@@ -28,7 +31,7 @@ impl Rotor {
     ///
     /// # Arguments
     /// * `wiring` - The wiring as a string (e.g. "EKMFLGDQVZNTOWYHXUSPAIBRCJ")
-    /// * `notch` - The notch letter
+    /// * `notches` - The notch letters (e.g. "Q", or "ZM" for two notches)
     /// * `name` - The name of the rotor
     /// * `ring_setting` - The ring setting (0-25)
     /// * `position` - The position (0-25)
@@ -37,7 +40,7 @@ impl Rotor {
     /// * `Result<Rotor, String>` - The created rotor or an error
     pub fn new(
         wiring: &str,
-        notch: char,
+        notches: &str,
         name: &str,
         ring_setting: usize,
         position: usize,
@@ -50,15 +53,29 @@ impl Rotor {
             return Err("Ring setting and position must be between 0 and 25".to_string());
         }
 
-        let notch_index = letter_to_index(notch)
-            .ok_or_else(|| format!("Ungültiger Kerbenbuchstabe: {}", notch))?;
+        let notch_indices = notches
+            .chars()
+            .map(|notch| {
+                letter_to_index(notch)
+                    .ok_or_else(|| format!("Ungültiger Kerbenbuchstabe: {}", notch))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         let mut wiring_array = [0; 26];
         let mut reverse_wiring = [0; 26];
 
+        let mut used = [false; 26];
+
         for (i, ch) in wiring.chars().enumerate() {
             let target = letter_to_index(ch)
                 .ok_or_else(|| format!("Ungültiges Zeichen in Verdrahtung: {}", ch))?;
+            if used[target] {
+                return Err(format!(
+                    "Verdrahtung muss eine gültige Permutation sein ({} kommt mehrfach vor)",
+                    ch.to_ascii_uppercase()
+                ));
+            }
+            used[target] = true;
             wiring_array[i] = target;
             reverse_wiring[target] = i;
         }
@@ -68,8 +85,9 @@ impl Rotor {
             reverse_wiring,
             ring_setting,
             position,
-            notch: notch_index,
+            notches: notch_indices,
             name: name.to_string(),
+            stationary: false,
         })
     }
 
@@ -81,12 +99,8 @@ impl Rotor {
     /// # Returns
     /// * The encrypted character
     pub fn forward(&self, input: char) -> char {
-        let input_index = letter_to_index(input).unwrap_or(0) as i32;
-        let position = self.position as i32;
-        let ring_setting = self.ring_setting as i32;
-        let adjusted_input = ((input_index + position - ring_setting + 26) % 26) as usize;
-        let output_index =
-            ((self.wiring[adjusted_input] as i32 + ring_setting - position + 26) % 26) as usize;
+        let input_index = letter_to_index(input).unwrap_or(0) as u8;
+        let output_index = self.forward_index(input_index) as usize;
 
         trace!(
             "Rotor {} forward: {} -> {} (pos: {}, ring: {})",
@@ -100,6 +114,22 @@ impl Rotor {
         index_to_letter(output_index).unwrap_or('A')
     }
 
+    /// Encrypts a letter index (0-25) in forward direction
+    ///
+    /// Index-based sibling of `forward` for the encryption hot path.
+    ///
+    /// # Arguments
+    /// * `input` - The input index (0-25)
+    ///
+    /// # Returns
+    /// * The output index (0-25)
+    #[inline]
+    pub fn forward_index(&self, input: u8) -> u8 {
+        let shift = self.shift();
+        let entry = (input as usize + shift) % 26;
+        ((self.wiring[entry] + 26 - shift) % 26) as u8
+    }
+
     /// Encrypts a character in backward direction
     ///
     /// # Arguments
@@ -108,13 +138,8 @@ impl Rotor {
     /// # Returns
     /// * The encrypted character
     pub fn backward(&self, input: char) -> char {
-        let input_index = letter_to_index(input).unwrap_or(0) as i32;
-        let position = self.position as i32;
-        let ring_setting = self.ring_setting as i32;
-        let adjusted_input = ((input_index + position - ring_setting + 26) % 26) as usize;
-        let output_index = ((self.reverse_wiring[adjusted_input] as i32 + ring_setting - position
-            + 26)
-            % 26) as usize;
+        let input_index = letter_to_index(input).unwrap_or(0) as u8;
+        let output_index = self.backward_index(input_index) as usize;
 
         trace!(
             "Rotor {} backward: {} -> {} (pos: {}, ring: {})",
@@ -128,13 +153,35 @@ impl Rotor {
         index_to_letter(output_index).unwrap_or('A')
     }
 
+    /// Encrypts a letter index (0-25) in backward direction
+    ///
+    /// Index-based sibling of `backward` for the encryption hot path.
+    ///
+    /// # Arguments
+    /// * `input` - The input index (0-25)
+    ///
+    /// # Returns
+    /// * The output index (0-25)
+    #[inline]
+    pub fn backward_index(&self, input: u8) -> u8 {
+        let shift = self.shift();
+        let entry = (input as usize + shift) % 26;
+        ((self.reverse_wiring[entry] + 26 - shift) % 26) as u8
+    }
+
+    /// Offset between the contacts and the wiring core (position minus ring setting)
+    #[inline]
+    fn shift(&self) -> usize {
+        (self.position + 26 - self.ring_setting) % 26
+    }
+
     /// Dreht den Rotor um eine Position weiter
     ///
     /// # Returns
     /// * `true` - If the rotor has passed the notch (trigger advancement)
     /// * `false` - Normale Drehung
     pub fn step(&mut self) -> bool {
-        let was_at_notch = self.position == self.notch;
+        let was_at_notch = self.is_at_notch();
         self.position = (self.position + 1) % 26;
 
         debug!(
@@ -144,6 +191,15 @@ impl Rotor {
         was_at_notch
     }
 
+    /// Checks whether the rotor currently stands at its notch
+    ///
+    /// # Returns
+    /// * `true` - If the next step will carry over to the neighbouring rotor
+    /// * `false` - Otherwise
+    pub fn is_at_notch(&self) -> bool {
+        self.notches.contains(&self.position)
+    }
+
     /// Setzt die Position des Rotors
     ///
     /// # Arguments
@@ -169,6 +225,82 @@ impl Rotor {
         }
     }
 
+    /// Setzt die Position des Rotors anhand eines Buchstabens
+    ///
+    /// # Arguments
+    /// * `position` - Der Positionsbuchstabe (A-Z, Groß- oder Kleinschreibung)
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Erfolg oder Fehler bei ungültigem Zeichen
+    pub fn set_position_char(&mut self, position: char) -> Result<(), String> {
+        let index = letter_to_index(position)
+            .ok_or_else(|| format!("Ungültige Rotorposition: {}", position))?;
+        self.set_position(index);
+        Ok(())
+    }
+
+    /// Setzt die Ringstellung des Rotors anhand eines Buchstabens
+    ///
+    /// # Arguments
+    /// * `ring_setting` - Der Buchstabe der Ringstellung (A-Z, Groß- oder Kleinschreibung)
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Erfolg oder Fehler bei ungültigem Zeichen
+    pub fn set_ring_setting_char(&mut self, ring_setting: char) -> Result<(), String> {
+        let index = letter_to_index(ring_setting)
+            .ok_or_else(|| format!("Ungültige Ringstellung: {}", ring_setting))?;
+        self.set_ring_setting(index);
+        Ok(())
+    }
+
+    /// Ersetzt die Kerben des Rotors durch einen einzelnen Kerbenbuchstaben
+    ///
+    /// Ändern sich dadurch die Kerben, heißt der Rotor anschließend
+    /// `CUSTOM:<Verdrahtung>@<Kerbe>`, auch wenn er ein historischer Rotor
+    /// war. So enthalten gespeicherte Konfigurationen und der
+    /// Konfigurations-Hash die neue Kerbe. Feststehende Rotoren haben keine
+    /// Kerbe und werden abgelehnt.
+    ///
+    /// # Arguments
+    /// * `notch` - Der Kerbenbuchstabe (A-Z, Groß- oder Kleinschreibung)
+    ///
+    /// # Returns
+    /// * `Result<Rotor, String>` - Der Rotor mit neuer Kerbe oder ein Fehler
+    pub fn with_notch_char(mut self, notch: char) -> Result<Self, String> {
+        if self.stationary {
+            return Err(format!(
+                "Rotor {} dreht sich nicht und hat keine Kerbe",
+                self.name
+            ));
+        }
+        let index = letter_to_index(notch)
+            .ok_or_else(|| format!("Ungültiger Kerbenbuchstabe: {}", notch))?;
+        if self.notches != [index] {
+            self.notches = vec![index];
+            self.name = format!(
+                "{}{}@{}",
+                rotors::CUSTOM_PREFIX,
+                self.wiring_string(),
+                notch.to_ascii_uppercase()
+            );
+        }
+        Ok(self)
+    }
+
+    /// Returns the wiring as a letter string
+    ///
+    /// The wiring is shown at ring setting A, independent of position and
+    /// ring setting, so it matches the published rotor tables.
+    ///
+    /// # Returns
+    /// * Die Verdrahtung, z.B. "EKMFLGDQVZNTOWYHXUSPAIBRCJ" für Rotor I
+    pub fn wiring_string(&self) -> String {
+        self.wiring
+            .iter()
+            .map(|&target| index_to_letter(target).unwrap_or('A'))
+            .collect()
+    }
+
     /// Returns the current position as a letter
     ///
     /// # Returns
@@ -177,6 +309,31 @@ impl Rotor {
         index_to_letter(self.position).unwrap_or('A')
     }
 
+    /// Returns the letter of the next notch as seen in the rotor window
+    ///
+    /// Rotors VI-VIII have two notches; the one reached first from the
+    /// current position is returned.
+    ///
+    /// # Returns
+    /// * `Some(letter)` - Der Buchstabe der nächsten Kerbe
+    /// * `None` - If the rotor has no notch (e.g. Beta/Gamma)
+    pub fn get_notch_char(&self) -> Option<char> {
+        let steps = self.steps_until_notch()?;
+        index_to_letter((self.position + steps) % 26)
+    }
+
+    /// Counts the steps until the rotor stands at a notch
+    ///
+    /// # Returns
+    /// * `Some(steps)` - 0 if the rotor is at a notch now, otherwise 1-25
+    /// * `None` - If the rotor has no notch
+    pub fn steps_until_notch(&self) -> Option<usize> {
+        self.notches
+            .iter()
+            .map(|&notch| (notch + 26 - self.position) % 26)
+            .min()
+    }
+
     /// Returns the ring setting as a letter
     ///
     /// # Returns
@@ -184,17 +341,225 @@ impl Rotor {
     pub fn get_ring_setting_char(&self) -> char {
         index_to_letter(self.ring_setting).unwrap_or('A')
     }
+
+    /// Returns the ring setting as printed on key sheets
+    ///
+    /// Key sheets count from 1 (A = 01), while the `ring_setting` field
+    /// counts from 0 (A = 0).
+    ///
+    /// # Returns
+    /// * Die Ringstellung als Zahl 1-26
+    pub fn get_ring_setting_number(&self) -> u8 {
+        self.ring_setting as u8 + 1
+    }
+
+    /// Returns the position as a number counted from 1 (A = 1)
+    ///
+    /// # Returns
+    /// * Die Position als Zahl 1-26
+    pub fn get_position_number(&self) -> u8 {
+        self.position as u8 + 1
+    }
+}
+
+/// Formats the rotor as "NAME (Ring X, Position Y)"
+impl fmt::Display for Rotor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (Ring {}, Position {})",
+            self.name,
+            self.get_ring_setting_char(),
+            self.get_position_char()
+        )
+    }
+}
+
+/// Gemeinsame Schnittstelle aller Walzen im Signalweg einer Rotormaschine
+///
+/// `Rotor` ist die Standardimplementierung für die Enigma. Eigene Walzen,
+/// z.B. für die Typex mit mehreren Kerben und feststehenden Walzen, können
+/// über diese Schnittstelle in eine `EnigmaMachine` eingesetzt werden; für
+/// gemischte Walzensätze eignet sich `EnigmaMachine<Box<dyn Scrambler>>`.
+/// Nur `forward`, `backward` und `step` müssen implementiert werden; die
+/// übrigen Methoden beschreiben eine Walze ohne Stellung und ohne Kerbe.
+pub trait Scrambler: fmt::Debug + Send {
+    /// Verschlüsselt ein Zeichen in Vorwärtsrichtung (rechts nach links)
+    fn forward(&self, input: char) -> char;
+
+    /// Verschlüsselt ein Zeichen in Rückwärtsrichtung (links nach rechts)
+    fn backward(&self, input: char) -> char;
+
+    /// Dreht die Walze um eine Position weiter
+    ///
+    /// # Returns
+    /// * `true` - Wenn die Walze dabei ihre Kerbe verlassen hat
+    /// * `false` - Normale Drehung
+    fn step(&mut self) -> bool;
+
+    /// Der Name der Walze für Log und Anzeige
+    fn name(&self) -> &str {
+        "Scrambler"
+    }
+
+    /// Verschlüsselt einen Buchstabenindex (0-25) in Vorwärtsrichtung
+    fn forward_index(&self, input: u8) -> u8 {
+        letter_to_index(self.forward((b'A' + input) as char)).unwrap_or(0) as u8
+    }
+
+    /// Verschlüsselt einen Buchstabenindex (0-25) in Rückwärtsrichtung
+    fn backward_index(&self, input: u8) -> u8 {
+        letter_to_index(self.backward((b'A' + input) as char)).unwrap_or(0) as u8
+    }
+
+    /// Prüft, ob die Walze an einer Kerbe steht
+    fn is_at_notch(&self) -> bool {
+        false
+    }
+
+    /// Prüft, ob die Walze feststeht und nie von einer Klinke gedreht wird
+    fn is_stationary(&self) -> bool {
+        false
+    }
+
+    /// Die aktuelle Position (0-25)
+    fn position(&self) -> usize {
+        0
+    }
+
+    /// Setzt die Position (0-25)
+    fn set_position(&mut self, _position: usize) {}
+
+    /// Zählt die Schritte bis zur nächsten Kerbe (`None` ohne Kerbe)
+    fn steps_until_notch(&self) -> Option<usize> {
+        None
+    }
+
+    /// Gibt die aktuelle Position als Buchstaben zurück
+    fn get_position_char(&self) -> char {
+        index_to_letter(self.position()).unwrap_or('A')
+    }
+
+    /// Setzt die Position anhand eines Buchstabens
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Erfolg oder Fehler bei ungültigem Zeichen
+    fn set_position_char(&mut self, position: char) -> Result<(), String> {
+        let index = letter_to_index(position)
+            .ok_or_else(|| format!("Ungültige Rotorposition: {}", position))?;
+        self.set_position(index);
+        Ok(())
+    }
+}
+
+impl Scrambler for Rotor {
+    fn forward(&self, input: char) -> char {
+        Rotor::forward(self, input)
+    }
+
+    fn backward(&self, input: char) -> char {
+        Rotor::backward(self, input)
+    }
+
+    fn step(&mut self) -> bool {
+        Rotor::step(self)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    fn forward_index(&self, input: u8) -> u8 {
+        Rotor::forward_index(self, input)
+    }
+
+    #[inline]
+    fn backward_index(&self, input: u8) -> u8 {
+        Rotor::backward_index(self, input)
+    }
+
+    fn is_at_notch(&self) -> bool {
+        Rotor::is_at_notch(self)
+    }
+
+    fn is_stationary(&self) -> bool {
+        self.stationary
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    fn set_position(&mut self, position: usize) {
+        Rotor::set_position(self, position)
+    }
+
+    fn steps_until_notch(&self) -> Option<usize> {
+        Rotor::steps_until_notch(self)
+    }
+}
+
+/// Ermöglicht Maschinen mit gemischten Walzen (`Box<dyn Scrambler>`)
+impl<S: Scrambler + ?Sized> Scrambler for Box<S> {
+    fn forward(&self, input: char) -> char {
+        (**self).forward(input)
+    }
+
+    fn backward(&self, input: char) -> char {
+        (**self).backward(input)
+    }
+
+    fn step(&mut self) -> bool {
+        (**self).step()
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn forward_index(&self, input: u8) -> u8 {
+        (**self).forward_index(input)
+    }
+
+    fn backward_index(&self, input: u8) -> u8 {
+        (**self).backward_index(input)
+    }
+
+    fn is_at_notch(&self) -> bool {
+        (**self).is_at_notch()
+    }
+
+    fn is_stationary(&self) -> bool {
+        (**self).is_stationary()
+    }
+
+    fn position(&self) -> usize {
+        (**self).position()
+    }
+
+    fn set_position(&mut self, position: usize) {
+        (**self).set_position(position)
+    }
+
+    fn steps_until_notch(&self) -> Option<usize> {
+        (**self).steps_until_notch()
+    }
 }
 
 /// Vordefinierte historische Rotoren
 pub mod rotors {
     use super::Rotor;
+    use crate::utils::index_to_letter;
+
+    /// Konstruktor eines vordefinierten Rotors (Ringstellung, Position)
+    pub type RotorCreator = fn(usize, usize) -> Result<Rotor, String>;
 
     /// Erstellt Rotor I
     pub fn rotor_i(ring_setting: usize, position: usize) -> Result<Rotor, String> {
         Rotor::new(
             "EKMFLGDQVZNTOWYHXUSPAIBRCJ",
-            'Q',
+            "Q",
             "I",
             ring_setting,
             position,
@@ -205,7 +570,7 @@ pub mod rotors {
     pub fn rotor_ii(ring_setting: usize, position: usize) -> Result<Rotor, String> {
         Rotor::new(
             "AJDKSIRUXBLHWTMCQGZNPYFVOE",
-            'E',
+            "E",
             "II",
             ring_setting,
             position,
@@ -216,7 +581,7 @@ pub mod rotors {
     pub fn rotor_iii(ring_setting: usize, position: usize) -> Result<Rotor, String> {
         Rotor::new(
             "BDFHJLCPRTXVZNYEIWGAKMUSQO",
-            'V',
+            "V",
             "III",
             ring_setting,
             position,
@@ -227,7 +592,7 @@ pub mod rotors {
     pub fn rotor_iv(ring_setting: usize, position: usize) -> Result<Rotor, String> {
         Rotor::new(
             "ESOVPZJAYQUIRHXLNFTGKDCMWB",
-            'J',
+            "J",
             "IV",
             ring_setting,
             position,
@@ -238,24 +603,377 @@ pub mod rotors {
     pub fn rotor_v(ring_setting: usize, position: usize) -> Result<Rotor, String> {
         Rotor::new(
             "VZBRGITYUPSDNHLXAWMJQOFECK",
-            'Z',
+            "Z",
             "V",
             ring_setting,
             position,
         )
     }
 
+    /// Erstellt Rotor VI (Kriegsmarine, Kerben bei Z und M)
+    pub fn rotor_vi(ring_setting: usize, position: usize) -> Result<Rotor, String> {
+        Rotor::new(
+            "JPGVOUMFYQBENHZRDKASXLICTW",
+            "ZM",
+            "VI",
+            ring_setting,
+            position,
+        )
+    }
+
+    /// Erstellt Rotor VII (Kriegsmarine, Kerben bei Z und M)
+    pub fn rotor_vii(ring_setting: usize, position: usize) -> Result<Rotor, String> {
+        Rotor::new(
+            "NZJHGRCXMYSWBOUFAIVLPEKQDT",
+            "ZM",
+            "VII",
+            ring_setting,
+            position,
+        )
+    }
+
+    /// Erstellt Rotor VIII (Kriegsmarine, Kerben bei Z und M)
+    pub fn rotor_viii(ring_setting: usize, position: usize) -> Result<Rotor, String> {
+        Rotor::new(
+            "FKQHTLXOCBJSPDZRAMEWNIUYGV",
+            "ZM",
+            "VIII",
+            ring_setting,
+            position,
+        )
+    }
+
+    /// Erstellt die Zusatzwalze Beta (M4, feststehend)
+    pub fn rotor_beta(ring_setting: usize, position: usize) -> Result<Rotor, String> {
+        // Die Zusatzwalzen haben keine Kerbe, da sie nie weiterdrehen
+        let mut rotor = Rotor::new(
+            "LEYJVCNIXWPBQMDRTAKZGFUHOS",
+            "",
+            "Beta",
+            ring_setting,
+            position,
+        )?;
+        rotor.stationary = true;
+        Ok(rotor)
+    }
+
+    /// Erstellt die Zusatzwalze Gamma (M4, feststehend)
+    pub fn rotor_gamma(ring_setting: usize, position: usize) -> Result<Rotor, String> {
+        let mut rotor = Rotor::new(
+            "FSOKANUERHMBTIYCWLQPZXVGJD",
+            "",
+            "Gamma",
+            ring_setting,
+            position,
+        )?;
+        rotor.stationary = true;
+        Ok(rotor)
+    }
+
+    /// Präfix für Rotoren mit frei gewählter Verdrahtung
+    pub const CUSTOM_PREFIX: &str = "CUSTOM:";
+
+    /// Erstellt einen Rotor mit frei gewählter Verdrahtung
+    ///
+    /// Der Name des Rotors ist die vollständige Spezifikation, damit eine
+    /// gespeicherte Konfiguration den Rotor wieder erzeugen kann.
+    ///
+    /// # Arguments
+    /// * `wiring` - Die Verdrahtung (26 Buchstaben, jeder genau einmal)
+    /// * `notch` - Der Kerbenbuchstabe
+    /// * `ring_setting` - Die Ringstellung (0-25)
+    /// * `position` - Die Position (0-25)
+    ///
+    /// # Returns
+    /// * `Result<Rotor, String>` - Der erstellte Rotor oder ein Fehler
+    pub fn rotor_custom(
+        wiring: &str,
+        notch: char,
+        ring_setting: usize,
+        position: usize,
+    ) -> Result<Rotor, String> {
+        let wiring = wiring.to_ascii_uppercase();
+        let notch = notch.to_ascii_uppercase();
+        Rotor::new(
+            &wiring,
+            &notch.to_string(),
+            &format!("{}{}@{}", CUSTOM_PREFIX, wiring, notch),
+            ring_setting,
+            position,
+        )
+    }
+
+    /// Zerlegt eine Spezifikation der Form "CUSTOM:<Verdrahtung>@<Kerbe>"
+    ///
+    /// # Arguments
+    /// * `spec` - Die Spezifikation, z.B. "CUSTOM:EKMFLGDQVZNTOWYHXUSPAIBRCJ@Q"
+    ///
+    /// # Returns
+    /// * `Result<(&str, char), String>` - Verdrahtung und Kerbe oder ein Fehler
+    pub fn parse_custom_spec(spec: &str) -> Result<(&str, char), String> {
+        let body = spec
+            .strip_prefix(CUSTOM_PREFIX)
+            .ok_or_else(|| format!("Rotorangabe muss mit {} beginnen", CUSTOM_PREFIX))?;
+        let (wiring, notch) = body.split_once('@').ok_or_else(|| {
+            format!(
+                "Kerbe fehlt in Rotorangabe: {} (Format <Verdrahtung>@<Kerbe>)",
+                spec
+            )
+        })?;
+
+        let mut notch_chars = notch.chars();
+        match (notch_chars.next(), notch_chars.next()) {
+            (Some(notch), None) => Ok((wiring, notch)),
+            _ => Err(format!("Kerbe muss genau ein Buchstabe sein: {}", notch)),
+        }
+    }
+
+    /// Historische Angaben zu einem vordefinierten Rotor
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct RotorInfo {
+        /// Der Name des Rotors (z.B. "IV")
+        pub name: &'static str,
+        /// Die Verdrahtung als Buchstabenfolge
+        pub wiring: String,
+        /// Die Kerbenbuchstaben im Rotorfenster (leer bei Beta/Gamma)
+        pub notches: String,
+        /// Zeitpunkt der Einführung (z.B. "Dezember 1938")
+        pub introduced: &'static str,
+        /// Die Truppenteile, die den Rotor verwendeten
+        pub service: &'static str,
+        /// Kurze historische Anmerkung
+        pub note: &'static str,
+    }
+
+    /// Einführung, Verwendung und Anmerkung der vordefinierten Rotoren
+    const HISTORY: [(&str, &str, &str, &str); 10] = [
+        (
+            "I",
+            "1930",
+            "Reichswehr/Wehrmacht, Luftwaffe, Kriegsmarine",
+            "Einer der drei ursprünglichen Rotoren der Enigma I",
+        ),
+        (
+            "II",
+            "1930",
+            "Reichswehr/Wehrmacht, Luftwaffe, Kriegsmarine",
+            "Einer der drei ursprünglichen Rotoren der Enigma I",
+        ),
+        (
+            "III",
+            "1930",
+            "Reichswehr/Wehrmacht, Luftwaffe, Kriegsmarine",
+            "Einer der drei ursprünglichen Rotoren der Enigma I",
+        ),
+        (
+            "IV",
+            "Dezember 1938",
+            "Wehrmacht, Luftwaffe, Kriegsmarine",
+            "Erhöhte die möglichen Walzenlagen von 6 auf 60",
+        ),
+        (
+            "V",
+            "Dezember 1938",
+            "Wehrmacht, Luftwaffe, Kriegsmarine",
+            "Zusammen mit Rotor IV eingeführt",
+        ),
+        (
+            "VI",
+            "1939",
+            "Kriegsmarine",
+            "Nur bei der Marine; zwei Kerben (Z und M)",
+        ),
+        (
+            "VII",
+            "1939",
+            "Kriegsmarine",
+            "Nur bei der Marine; zwei Kerben (Z und M)",
+        ),
+        (
+            "VIII",
+            "1940",
+            "Kriegsmarine",
+            "Nur bei der Marine; zwei Kerben (Z und M)",
+        ),
+        (
+            "Beta",
+            "Februar 1942",
+            "Kriegsmarine (U-Boote)",
+            "Feststehende Zusatzwalze der M4, nur mit dünnem Reflektor B",
+        ),
+        (
+            "Gamma",
+            "Juli 1943",
+            "Kriegsmarine (U-Boote)",
+            "Feststehende Zusatzwalze der M4, nur mit dünnem Reflektor C",
+        ),
+    ];
+
+    /// Gibt die historischen Angaben zu einem vordefinierten Rotor zurück
+    ///
+    /// Verdrahtung und Kerben stammen aus dem Rotor selbst, sodass sie nicht
+    /// von der Implementierung abweichen können.
+    ///
+    /// # Arguments
+    /// * `name` - Der Rotorname wie in `available_rotor_names` (z.B. "IV")
+    ///
+    /// # Returns
+    /// * `Some(info)` - Die Angaben zum Rotor
+    /// * `None` - Bei unbekannten oder frei verdrahteten Rotoren
+    pub fn info(name: &str) -> Option<RotorInfo> {
+        let &(name, introduced, service, note) =
+            HISTORY.iter().find(|(known, ..)| *known == name)?;
+        let (_, create) = available_rotors()
+            .into_iter()
+            .find(|(known, _)| *known == name)?;
+        let rotor = create(0, 0).ok()?;
+
+        Some(RotorInfo {
+            name,
+            wiring: rotor.wiring_string(),
+            notches: rotor
+                .notches
+                .iter()
+                .filter_map(|&notch| index_to_letter(notch))
+                .collect(),
+            introduced,
+            service,
+            note,
+        })
+    }
+
+    /// Returns the names of all available rotors
+    ///
+    /// In the same order as `available_rotors`, e.g. for selection lists.
+    pub fn available_rotor_names() -> Vec<&'static str> {
+        available_rotors()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
     /// Returns all available rotors
-    pub fn available_rotors() -> Vec<(&'static str, fn(usize, usize) -> Result<Rotor, String>)> {
+    pub fn available_rotors() -> Vec<(&'static str, RotorCreator)> {
         vec![
-            ("I", rotor_i as fn(usize, usize) -> Result<Rotor, String>),
-            ("II", rotor_ii as fn(usize, usize) -> Result<Rotor, String>),
-            (
-                "III",
-                rotor_iii as fn(usize, usize) -> Result<Rotor, String>,
-            ),
-            ("IV", rotor_iv as fn(usize, usize) -> Result<Rotor, String>),
-            ("V", rotor_v as fn(usize, usize) -> Result<Rotor, String>),
+            ("I", rotor_i as RotorCreator),
+            ("II", rotor_ii as RotorCreator),
+            ("III", rotor_iii as RotorCreator),
+            ("IV", rotor_iv as RotorCreator),
+            ("V", rotor_v as RotorCreator),
+            ("VI", rotor_vi as RotorCreator),
+            ("VII", rotor_vii as RotorCreator),
+            ("VIII", rotor_viii as RotorCreator),
+            ("Beta", rotor_beta as RotorCreator),
+            ("Gamma", rotor_gamma as RotorCreator),
         ]
     }
 }
+
+/// Auswahl eines Rotors anhand seines Namens
+///
+/// Zentraler Parser für Rotorangaben in CLI, GUI und Factory: "I" bis "VIII",
+/// die Zusatzwalzen "Beta" und "Gamma" sowie "CUSTOM:<Verdrahtung>@<Kerbe>".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotorType {
+    I,
+    II,
+    III,
+    IV,
+    V,
+    VI,
+    VII,
+    VIII,
+    /// Zusatzwalze Beta (M4, feststehend)
+    Beta,
+    /// Zusatzwalze Gamma (M4, feststehend)
+    Gamma,
+    /// Rotor mit frei gewählter Verdrahtung
+    Custom {
+        wiring: String,
+        notch: char,
+    },
+}
+
+impl RotorType {
+    /// Checks whether the rotor is a stationary Greek rotor (Beta/Gamma)
+    pub fn is_stationary(&self) -> bool {
+        matches!(self, RotorType::Beta | RotorType::Gamma)
+    }
+
+    /// Erstellt den Rotor dieses Typs
+    ///
+    /// # Arguments
+    /// * `ring_setting` - Die Ringstellung (0-25)
+    /// * `position` - Die Position (0-25)
+    ///
+    /// # Returns
+    /// * `Result<Rotor, String>` - Der erstellte Rotor oder ein Fehler
+    pub fn create(&self, ring_setting: usize, position: usize) -> Result<Rotor, String> {
+        use rotors::*;
+
+        match self {
+            RotorType::I => rotor_i(ring_setting, position),
+            RotorType::II => rotor_ii(ring_setting, position),
+            RotorType::III => rotor_iii(ring_setting, position),
+            RotorType::IV => rotor_iv(ring_setting, position),
+            RotorType::V => rotor_v(ring_setting, position),
+            RotorType::VI => rotor_vi(ring_setting, position),
+            RotorType::VII => rotor_vii(ring_setting, position),
+            RotorType::VIII => rotor_viii(ring_setting, position),
+            RotorType::Beta => rotor_beta(ring_setting, position),
+            RotorType::Gamma => rotor_gamma(ring_setting, position),
+            RotorType::Custom { wiring, notch } => {
+                rotor_custom(wiring, *notch, ring_setting, position)
+            }
+        }
+    }
+}
+
+impl FromStr for RotorType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "I" => Ok(RotorType::I),
+            "II" => Ok(RotorType::II),
+            "III" => Ok(RotorType::III),
+            "IV" => Ok(RotorType::IV),
+            "V" => Ok(RotorType::V),
+            "VI" => Ok(RotorType::VI),
+            "VII" => Ok(RotorType::VII),
+            "VIII" => Ok(RotorType::VIII),
+            "Beta" => Ok(RotorType::Beta),
+            "Gamma" => Ok(RotorType::Gamma),
+            spec if spec.starts_with(rotors::CUSTOM_PREFIX) => {
+                let (wiring, notch) = rotors::parse_custom_spec(spec)?;
+                // Verdrahtung und Kerbe sofort prüfen statt erst beim Erstellen
+                rotors::rotor_custom(wiring, notch, 0, 0)?;
+                Ok(RotorType::Custom {
+                    wiring: wiring.to_ascii_uppercase(),
+                    notch: notch.to_ascii_uppercase(),
+                })
+            }
+            other => Err(format!("Unbekannter Rotortyp: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for RotorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotorType::I => write!(f, "I"),
+            RotorType::II => write!(f, "II"),
+            RotorType::III => write!(f, "III"),
+            RotorType::IV => write!(f, "IV"),
+            RotorType::V => write!(f, "V"),
+            RotorType::VI => write!(f, "VI"),
+            RotorType::VII => write!(f, "VII"),
+            RotorType::VIII => write!(f, "VIII"),
+            RotorType::Beta => write!(f, "Beta"),
+            RotorType::Gamma => write!(f, "Gamma"),
+            RotorType::Custom { wiring, notch } => {
+                write!(f, "{}{}@{}", rotors::CUSTOM_PREFIX, wiring, notch)
+            }
+        }
+    }
+}
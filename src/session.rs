@@ -0,0 +1,94 @@
+//! Funkverkehr eines Tages mit gemeinsamem Tagesschlüssel
+//!
+//! Alle Funksprüche eines Tages nutzen denselben Tagesschlüssel (Walzenlage,
+//! Ringstellung, Steckerbrett, Grundstellung). Jeder einzelne Spruch wird
+//! jedoch mit einem eigenen Spruchschlüssel, also einer frei gewählten
+//! Walzenstellung, verschlüsselt.
+
+use crate::machine::{factory, MachineConfig};
+use crate::utils::{clean_text, letter_to_index};
+
+/// Verfahren zur Übermittlung des Spruchschlüssels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndicatorProcedure {
+    /// Der Spruchschlüssel wird nicht mitgesendet
+    None,
+    /// Der Spruchschlüssel wird in der Grundstellung zweimal hintereinander
+    /// verschlüsselt und dem Spruch vorangestellt (Verfahren bis 1940)
+    DoubledKey,
+}
+
+/// Ein verschlüsselter Funkspruch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedMessage {
+    /// Der verschlüsselte Spruchschlüssel (nur bei `IndicatorProcedure::DoubledKey`)
+    pub indicator: Option<String>,
+    /// Der Geheimtext des Spruchs
+    pub ciphertext: String,
+}
+
+/// Verschlüsselt mehrere Funksprüche mit je eigenem Spruchschlüssel
+///
+/// Vor jedem Spruch wird die Maschine in die Grundstellung des
+/// Tagesschlüssels (`daily.rotor_positions`) zurückgesetzt. Beim
+/// Verfahren `DoubledKey` wird dort zuerst der verdoppelte Spruchschlüssel
+/// verschlüsselt; anschließend werden die Walzen auf den Spruchschlüssel
+/// gestellt und der Klartext verschlüsselt.
+///
+/// # Arguments
+/// * `daily` - Der Tagesschlüssel
+/// * `messages` - Die Sprüche als (Spruchschlüssel, Klartext), z.B. ("RTZ", "ANGRIFF")
+/// * `procedure` - Das Verfahren für den Spruchschlüssel
+///
+/// # Returns
+/// * `Result<Vec<EncryptedMessage>, String>` - Die Geheimtexte in gleicher Reihenfolge
+///   oder ein Fehler bei ungültigem Tages- oder Spruchschlüssel
+pub fn encrypt_batch(
+    daily: &MachineConfig,
+    messages: &[(&str, &str)],
+    procedure: IndicatorProcedure,
+) -> Result<Vec<EncryptedMessage>, String> {
+    let mut machine = factory::from_config(daily)?;
+    let rotor_count = machine.rotors.len();
+
+    messages
+        .iter()
+        .enumerate()
+        .map(|(i, &(message_key, plaintext))| {
+            let key: Vec<char> = message_key
+                .chars()
+                .map(|c| c.to_ascii_uppercase())
+                .collect();
+            if key.len() != rotor_count || key.iter().any(|&c| letter_to_index(c).is_none()) {
+                return Err(format!(
+                    "Spruchschlüssel '{}' von Spruch {} muss aus {} Buchstaben bestehen",
+                    message_key,
+                    i + 1,
+                    rotor_count
+                ));
+            }
+
+            machine.reset();
+            let indicator = match procedure {
+                IndicatorProcedure::None => None,
+                IndicatorProcedure::DoubledKey => {
+                    let key_text: String = key.iter().collect();
+                    Some(machine.process_exact(&key_text.repeat(2)))
+                }
+            };
+
+            machine.set_rotor_positions(&key);
+            let ciphertext = machine.encrypt(&clean_text(plaintext));
+            info!(
+                "Spruch {} mit Spruchschlüssel {} verschlüsselt",
+                i + 1,
+                message_key
+            );
+
+            Ok(EncryptedMessage {
+                indicator,
+                ciphertext,
+            })
+        })
+        .collect()
+}
@@ -3,6 +3,10 @@
 //! This module contains various utility functions for the Enigma machine,
 //! such as alphabet conversion and validation.
 
+use crate::plugboard::{DEFAULT_MAX_CONNECTIONS, FULL_CONNECTIONS};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
 /// Converts a letter (A-Z) to an index (0-25)
 ///
 /// # Arguments
@@ -35,6 +39,59 @@ pub fn index_to_letter(index: usize) -> Option<char> {
     }
 }
 
+/// Parses a single rotor setting given as a letter or as a number
+///
+/// Key sheets recorded ring settings as numbers 01-26, so both "C" and "03"
+/// describe the same setting.
+///
+/// # Arguments
+/// * `setting` - A letter (A-Z) or a number (1-26)
+///
+/// # Returns
+/// * `Ok(letter)` - The setting as an uppercase letter
+/// * `Err(message)` - If the setting is neither a letter nor a number in range
+pub fn parse_setting(setting: &str) -> Result<char, String> {
+    let setting = setting.trim();
+
+    if let Ok(number) = setting.parse::<usize>() {
+        return match number {
+            1..=26 => Ok(index_to_letter(number - 1).unwrap_or('A')),
+            _ => Err(format!("Zahl {} liegt außerhalb von 1-26", number)),
+        };
+    }
+
+    let mut chars = setting.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => Ok(letter.to_ascii_uppercase()),
+        _ => Err(format!("Ungültige Einstellung: '{}'", setting)),
+    }
+}
+
+/// Parses rotor settings given as letters or as numbers
+///
+/// Accepts a plain letter string ("ABC") or numbers separated by spaces,
+/// commas or dashes ("1 2 3", "01-02-03", "1,2,3").
+///
+/// # Arguments
+/// * `settings` - The settings string
+///
+/// # Returns
+/// * `Ok(letters)` - One uppercase letter per rotor, from left to right
+/// * `Err(message)` - If any part is invalid or out of range
+pub fn parse_settings(settings: &str) -> Result<Vec<char>, String> {
+    let settings = settings.trim();
+
+    if settings.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Ok(settings.chars().map(|c| c.to_ascii_uppercase()).collect());
+    }
+
+    settings
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '-')
+        .filter(|part| !part.is_empty())
+        .map(parse_setting)
+        .collect()
+}
+
 /// Validates text to ensure it only contains valid letters
 ///
 /// # Arguments
@@ -62,40 +119,484 @@ pub fn clean_text(text: &str) -> String {
         .collect()
 }
 
+/// German spelled forms of the digits 0-9 as used in message preparation
+///
+/// "ZWO" instead of "ZWEI" avoids confusion with "DREI" on noisy radio links.
+const SPELLED_DIGITS: [&str; 10] = [
+    "NULL", "EINS", "ZWO", "DREI", "VIER", "FUENF", "SECHS", "SIEBEN", "ACHT", "NEUN",
+];
+
+/// Cleans text following the German message preparation procedure
+///
+/// Unlike `clean_text`, which silently drops everything that is not a letter,
+/// this function rewrites the text the way operators prepared it:
+///
+/// | Input        | Output                               |
+/// |--------------|--------------------------------------|
+/// | `0`-`9`      | NULL, EINS, ZWO, DREI, VIER, FUENF, SECHS, SIEBEN, ACHT, NEUN |
+/// | `.`          | X                                    |
+/// | `Ä`, `Ö`, `Ü`| AE, OE, UE                           |
+/// | `ß`          | SS                                   |
+///
+/// All other non-letters are removed and letters are uppercased.
+///
+/// # Arguments
+/// * `text` - The text to clean
+///
+/// # Returns
+/// * The prepared text (only letters A-Z)
+pub fn clean_text_procedural(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '0'..='9' => result.push_str(SPELLED_DIGITS[c as usize - '0' as usize]),
+            '.' => result.push('X'),
+            'ä' | 'Ä' => result.push_str("AE"),
+            'ö' | 'Ö' => result.push_str("OE"),
+            'ü' | 'Ü' => result.push_str("UE"),
+            'ß' => result.push_str("SS"),
+            c if c.is_ascii_alphabetic() => result.push(c.to_ascii_uppercase()),
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// Figures of the teleprinter alphabet (ITA2) and the letter keys they share
+///
+/// As on a teleprinter, every figure is typed on a letter key while the
+/// machine is shifted to figures, e.g. `Q` becomes `1` and `M` becomes `.`.
+const FIGURES: [(char, char); 21] = [
+    ('1', 'Q'),
+    ('2', 'W'),
+    ('3', 'E'),
+    ('4', 'R'),
+    ('5', 'T'),
+    ('6', 'Y'),
+    ('7', 'U'),
+    ('8', 'I'),
+    ('9', 'O'),
+    ('0', 'P'),
+    ('-', 'A'),
+    ('?', 'B'),
+    (':', 'C'),
+    ('(', 'K'),
+    (')', 'L'),
+    ('.', 'M'),
+    (',', 'N'),
+    ('\'', 'S'),
+    ('=', 'V'),
+    ('/', 'X'),
+    ('+', 'Z'),
+];
+
+/// Teleprinter-style letters/figures shift for carrying digits and symbols
+///
+/// The Enigma core only knows the 26 letters. This layer is a convention on
+/// top of it, not a change to the rotor math: before encryption, digits and
+/// a few symbols are rewritten as letters between explicit shift markers,
+/// and after decryption the markers are turned back into figures.
+///
+/// | Sequence | Meaning                                      |
+/// |----------|----------------------------------------------|
+/// | `JF`     | Shift to figures                             |
+/// | `JL`     | Shift back to letters                        |
+/// | `JJ`     | A literal `J` (letters mode)                 |
+///
+/// In figures mode each letter stands for one figure as listed in
+/// `FIGURES` (`Q` = 1 ... `P` = 0, `M` = `.`, `N` = `,`, ...). Spaces and
+/// characters without a figure are dropped, just like `clean_text` does.
+///
+/// ```
+/// use enigma_rs::utils::FigureShift;
+///
+/// let encoded = FigureShift::encode("Pier 7, 14:30");
+/// assert_eq!(encoded, "PIERJFUNQRCEP");
+/// assert_eq!(FigureShift::decode(&encoded).unwrap(), "PIER7,14:30");
+/// ```
+pub struct FigureShift;
+
+impl FigureShift {
+    /// Escape letter that introduces a shift marker
+    pub const ESCAPE: char = 'J';
+
+    /// Rewrites text so that only letters remain, with figures shifted
+    ///
+    /// # Arguments
+    /// * `text` - The text, may contain digits and the supported symbols
+    ///
+    /// # Returns
+    /// * The encoded text (only letters A-Z), ready for encryption
+    pub fn encode(text: &str) -> String {
+        let mut result = String::with_capacity(text.len() * 2);
+        let mut figures = false;
+
+        for c in text.chars() {
+            if c.is_ascii_alphabetic() {
+                if figures {
+                    result.push(Self::ESCAPE);
+                    result.push('L');
+                    figures = false;
+                }
+                let letter = c.to_ascii_uppercase();
+                result.push(letter);
+                if letter == Self::ESCAPE {
+                    result.push(Self::ESCAPE);
+                }
+            } else if let Some(&(_, key)) = FIGURES.iter().find(|(figure, _)| *figure == c) {
+                if !figures {
+                    result.push(Self::ESCAPE);
+                    result.push('F');
+                    figures = true;
+                }
+                result.push(key);
+            }
+        }
+
+        result
+    }
+
+    /// Restores figures from text produced by `encode`
+    ///
+    /// Whitespace is ignored, so the grouped output of a decryption can be
+    /// passed in directly.
+    ///
+    /// # Arguments
+    /// * `text` - The decrypted text
+    ///
+    /// # Returns
+    /// * `Ok(text)` - The text with digits and symbols restored
+    /// * `Err(message)` - If a shift marker or figure is invalid
+    pub fn decode(text: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(text.len());
+        let mut figures = false;
+        let mut letters = text
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase());
+
+        while let Some(c) = letters.next() {
+            if c == Self::ESCAPE {
+                match (letters.next(), figures) {
+                    (Some('F'), _) => figures = true,
+                    (Some('L'), _) => figures = false,
+                    (Some(escape), false) if escape == Self::ESCAPE => result.push(escape),
+                    (Some(other), _) => {
+                        return Err(format!("Invalid shift marker: {}{}", Self::ESCAPE, other))
+                    }
+                    (None, _) => return Err("Incomplete shift marker at end of text".to_string()),
+                }
+            } else if figures {
+                let figure = FIGURES
+                    .iter()
+                    .find(|(_, key)| *key == c)
+                    .map(|(figure, _)| *figure)
+                    .ok_or_else(|| format!("No figure on letter key {}", c))?;
+                result.push(figure);
+            } else if c.is_ascii_alphabetic() {
+                result.push(c);
+            } else {
+                return Err(format!("Invalid character: {}", c));
+            }
+        }
+
+        Ok(result)
+    }
+}
+
 /// Creates a random key for the plugboard
 ///
+/// Uses `DEFAULT_MAX_CONNECTIONS` cables, so the result is always accepted
+/// by `Plugboard::from_string`.
+///
 /// # Returns
 /// * A string with randomly connected letter pairs
 pub fn generate_random_plugboard() -> String {
-    use rand::Rng;
-    use std::collections::HashSet;
+    generate_random_plugboard_with(&mut rand::rng())
+}
 
-    let mut rng = rand::rng();
-    let mut used = HashSet::new();
-    let mut connections = Vec::new();
+/// Creates a random key for the plugboard from the given random number generator
+///
+/// Seeding the generator (e.g. `StdRng::seed_from_u64`) makes the result
+/// reproducible.
+///
+/// # Arguments
+/// * `rng` - The random number generator
+///
+/// # Returns
+/// * A string with `DEFAULT_MAX_CONNECTIONS` randomly connected letter pairs
+pub fn generate_random_plugboard_with<R: Rng + ?Sized>(rng: &mut R) -> String {
+    generate_random_plugboard_cables_with(DEFAULT_MAX_CONNECTIONS, rng)
+}
 
-    let letters: Vec<char> = (b'A'..=b'Z').map(|b| b as char).collect();
+/// Creates a random plugboard key with a fixed number of cables
+///
+/// Key sheets prescribed a fixed number of cables (usually 10);
+/// `generate_random_plugboard` always uses `DEFAULT_MAX_CONNECTIONS`.
+///
+/// # Arguments
+/// * `cables` - The number of cables (at most 13)
+///
+/// # Returns
+/// * A string with `cables` randomly connected letter pairs, e.g. "AQ DZ ..."
+pub fn generate_random_plugboard_cables(cables: usize) -> String {
+    generate_random_plugboard_cables_with(cables, &mut rand::rng())
+}
+
+/// Creates a random plugboard key with a fixed number of cables from the given generator
+///
+/// # Arguments
+/// * `cables` - The number of cables (at most 13)
+/// * `rng` - The random number generator
+///
+/// # Returns
+/// * A string with `cables` randomly connected letter pairs, e.g. "AQ DZ ..."
+pub fn generate_random_plugboard_cables_with<R: Rng + ?Sized>(
+    cables: usize,
+    rng: &mut R,
+) -> String {
+    let mut letters: Vec<char> = (b'A'..=b'Z').map(|b| b as char).collect();
+    letters.shuffle(rng);
+
+    letters
+        .chunks(2)
+        .take(cables.min(FULL_CONNECTIONS))
+        .map(|pair| pair.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    for &letter in &letters {
-        if used.contains(&letter) {
-            continue;
+/// Creates random rotor positions or ring settings
+///
+/// # Arguments
+/// * `count` - The number of rotors
+///
+/// # Returns
+/// * One random letter A-Z per rotor
+pub fn generate_random_settings(count: usize) -> Vec<char> {
+    generate_random_settings_with(count, &mut rand::rng())
+}
+
+/// Creates random rotor positions or ring settings from the given generator
+///
+/// # Arguments
+/// * `count` - The number of rotors
+/// * `rng` - The random number generator
+///
+/// # Returns
+/// * One random letter A-Z per rotor
+pub fn generate_random_settings_with<R: Rng + ?Sized>(count: usize, rng: &mut R) -> Vec<char> {
+    (0..count)
+        .map(|_| (b'A' + rng.random_range(0..26u8)) as char)
+        .collect()
+}
+
+/// Computes a stable 64-bit FNV-1a hash
+///
+/// Unlike `std::collections::hash_map::DefaultHasher`, the result does not
+/// change between Rust versions, so it can be shown to users as a fingerprint.
+///
+/// # Arguments
+/// * `data` - The bytes to hash
+///
+/// # Returns
+/// * The 64-bit hash value
+pub fn fnv1a_64(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Alphabet of the URL-safe Base64 variant (RFC 4648, section 5)
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encodes bytes as URL-safe Base64 without padding
+///
+/// The result contains only letters, digits, `-` and `_`, so it can be put
+/// into URLs, file names or QR codes without escaping.
+///
+/// # Arguments
+/// * `data` - The bytes to encode
+///
+/// # Returns
+/// * The Base64 text
+pub fn encode_base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            let sextet = (bits >> (18 - 6 * i)) & 0x3f;
+            encoded.push(BASE64_ALPHABET[sextet as usize] as char);
         }
+    }
 
-        let available: Vec<char> = letters
-            .iter()
-            .filter(|&&l| !used.contains(&l))
-            .copied()
-            .collect();
-
-        if available.len() > 1 {
-            let partner = available[rng.random_range(0..available.len())];
-            if partner != letter {
-                connections.push(format!("{}{}", letter, partner));
-                used.insert(letter);
-                used.insert(partner);
-            }
+    encoded
+}
+
+/// Decodes URL-safe Base64 as produced by `encode_base64`
+///
+/// Trailing `=` padding is accepted and ignored.
+///
+/// # Arguments
+/// * `text` - The Base64 text
+///
+/// # Returns
+/// * `Result<Vec<u8>, String>` - Die Bytes oder ein Fehler bei ungültigen Zeichen oder Länge
+pub fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim().trim_end_matches('=');
+    if text.len() % 4 == 1 {
+        return Err(format!("Ungültige Base64-Länge: {}", text.len()));
+    }
+
+    let mut decoded = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, &symbol) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET
+                .iter()
+                .position(|&c| c == symbol)
+                .ok_or_else(|| format!("Ungültiges Base64-Zeichen: {}", symbol as char))?;
+            bits |= (value as u32) << (18 - 6 * i);
         }
+        for i in 0..chunk.len() - 1 {
+            decoded.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// Splits a text into space-separated groups of fixed size
+///
+/// # Arguments
+/// * `text` - The text to group (non-letters are removed first)
+/// * `group_size` - The number of letters per group (0 disables grouping)
+///
+/// # Returns
+/// * The grouped text (e.g. "HELLO WORLD")
+pub fn group_text(text: &str, group_size: usize) -> String {
+    let letters: Vec<char> = clean_text(text).chars().collect();
+    if group_size == 0 {
+        return letters.into_iter().collect();
+    }
+
+    letters
+        .chunks(group_size)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Relative letter frequencies of English text (A-Z)
+const ENGLISH_FREQUENCIES: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Counts how often each letter A-Z occurs in a text
+///
+/// Lowercase letters are counted as uppercase, all other characters are
+/// ignored.
+///
+/// # Arguments
+/// * `text` - The text to analyse
+///
+/// # Returns
+/// * The count per letter (index 0 = A, 25 = Z)
+pub fn letter_histogram(text: &str) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for index in text.chars().filter_map(letter_to_index) {
+        counts[index] += 1;
+    }
+    counts
+}
+
+/// Formats a letter histogram as an ASCII bar chart
+///
+/// One line per letter, e.g. "E | ########## 12". The most frequent
+/// letter gets a bar of `width` characters, the others are scaled
+/// accordingly.
+///
+/// # Arguments
+/// * `histogram` - The counts per letter, e.g. from `letter_histogram`
+/// * `width` - The length of the longest bar
+///
+/// # Returns
+/// * The chart with 26 lines
+pub fn format_histogram(histogram: &[usize; 26], width: usize) -> String {
+    let max = histogram.iter().copied().max().unwrap_or(0).max(1);
+
+    histogram
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            let bar = "#".repeat((count * width + max / 2) / max);
+            format!("{} | {:<width$} {}", (b'A' + i as u8) as char, bar, count)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Counts the letters of a text and their total
+fn letter_counts(text: &str) -> ([usize; 26], usize) {
+    let counts = letter_histogram(text);
+    (counts, counts.iter().sum())
+}
+
+/// Computes the index of coincidence of a text
+///
+/// The index is the probability that two randomly chosen letters of the
+/// text are equal. Uniformly random text scores about 0.038, English text
+/// about 0.067. Non-letters are ignored.
+///
+/// # Arguments
+/// * `text` - The text to analyse
+///
+/// # Returns
+/// * The index of coincidence (0.0 for texts with fewer than 2 letters)
+pub fn index_of_coincidence(text: &str) -> f64 {
+    let (counts, total) = letter_counts(text);
+    if total < 2 {
+        return 0.0;
+    }
+
+    let coincidences: usize = counts.iter().map(|&n| n * n.saturating_sub(1)).sum();
+    coincidences as f64 / (total * (total - 1)) as f64
+}
+
+/// Computes the chi-squared distance between a text and English letter frequencies
+///
+/// Lower values mean the letter distribution is closer to English.
+/// Non-letters are ignored.
+///
+/// # Arguments
+/// * `text` - The text to analyse
+///
+/// # Returns
+/// * The chi-squared statistic (0.0 for texts without letters)
+pub fn chi_squared_english(text: &str) -> f64 {
+    let (counts, total) = letter_counts(text);
+    if total == 0 {
+        return 0.0;
     }
 
-    connections.join(" ")
+    counts
+        .iter()
+        .zip(ENGLISH_FREQUENCIES.iter())
+        .map(|(&observed, &frequency)| {
+            let expected = frequency * total as f64;
+            let difference = observed as f64 - expected;
+            difference * difference / expected
+        })
+        .sum()
 }
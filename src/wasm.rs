@@ -0,0 +1,46 @@
+//! JavaScript bindings for running the encryption core in the browser
+//!
+//! Only available with the `wasm` feature. The machine is described by the
+//! JSON form of `MachineConfig`, so a web page can store and exchange
+//! settings in the same format as the desktop application. Build without
+//! the default features to keep the GUI out of the WASM module:
+//!
+//! ```text
+//! cargo build --lib --no-default-features --features wasm --target wasm32-unknown-unknown
+//! ```
+
+use crate::machine::{factory, EnigmaMachine, MachineConfig};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Erstellt eine Maschine aus einer JSON-Konfiguration
+fn machine_from_json(config_json: &str) -> Result<EnigmaMachine, String> {
+    factory::from_config(&MachineConfig::from_json(config_json)?)
+}
+
+/// Verschlüsselt einen Text mit der angegebenen Konfiguration
+///
+/// # Arguments
+/// * `config_json` - Die Konfiguration als JSON (siehe `MachineConfig::to_json`)
+/// * `text` - Der zu verschlüsselnde Text
+///
+/// # Returns
+/// * `Result<String, String>` - Der Geheimtext in 5er-Gruppen oder ein Fehler
+///   (in JavaScript als Ausnahme geworfen)
+#[wasm_bindgen]
+pub fn encrypt(config_json: &str, text: &str) -> Result<String, String> {
+    Ok(machine_from_json(config_json)?.encrypt(text))
+}
+
+/// Entschlüsselt einen Text mit der angegebenen Konfiguration
+///
+/// # Arguments
+/// * `config_json` - Die Konfiguration als JSON (siehe `MachineConfig::to_json`)
+/// * `text` - Der zu entschlüsselnde Text
+///
+/// # Returns
+/// * `Result<String, String>` - Der Klartext in 5er-Gruppen oder ein Fehler
+///   (in JavaScript als Ausnahme geworfen)
+#[wasm_bindgen]
+pub fn decrypt(config_json: &str, text: &str) -> Result<String, String> {
+    Ok(machine_from_json(config_json)?.decrypt(text))
+}
@@ -12,13 +12,13 @@ fn test_encrypt_decrypt_symmetry() {
     .expect("Machine should be creatable");
 
     let original_text = "HELLO WORLD";
-    let clean_original = clean_text(&original_text);
+    let clean_original = clean_text(original_text);
 
     // Encrypt the text
     let encrypted = machine.encrypt(&clean_original);
 
     // Reset the machine to the original positions
-    machine.set_rotor_positions(['A', 'A', 'A']);
+    machine.reset();
 
     // Decrypt the text
     let decrypted = machine.decrypt(&encrypted);
@@ -41,7 +41,7 @@ fn test_different_positions() {
     let encrypted = machine.encrypt(text);
 
     // Reset
-    machine.set_rotor_positions(['B', 'C', 'D']);
+    machine.reset();
 
     let decrypted = machine.decrypt(&encrypted);
     assert_eq!(clean_text(&decrypted), clean_text(text));
@@ -61,7 +61,7 @@ fn test_different_ring_settings() {
     let encrypted = machine.encrypt(text);
 
     // Reset
-    machine.set_rotor_positions(['A', 'A', 'A']);
+    machine.reset();
 
     let decrypted = machine.decrypt(&encrypted);
     assert_eq!(clean_text(&decrypted), clean_text(text));
@@ -83,7 +83,7 @@ fn test_different_rotor_types() {
     let encrypted = machine.encrypt(text);
 
     // Reset
-    machine.set_rotor_positions(['A', 'A', 'A']);
+    machine.reset();
 
     let decrypted = machine.decrypt(&encrypted);
     assert_eq!(clean_text(&decrypted), clean_text(text));
@@ -121,8 +121,8 @@ fn test_different_reflectors() {
     assert_ne!(encrypted_a, encrypted_b);
 
     // But both should be symmetric
-    machine_a.set_rotor_positions(['A', 'A', 'A']);
-    machine_b.set_rotor_positions(['A', 'A', 'A']);
+    machine_a.reset();
+    machine_b.reset();
 
     let decrypted_a = machine_a.decrypt(&encrypted_a);
     let decrypted_b = machine_b.decrypt(&encrypted_b);
@@ -145,7 +145,7 @@ fn test_plugboard() {
     let encrypted = machine.encrypt(text);
 
     // Reset
-    machine.set_rotor_positions(['A', 'A', 'A']);
+    machine.reset();
 
     let decrypted = machine.decrypt(&encrypted);
     assert_eq!(clean_text(&decrypted), clean_text(text));
@@ -162,7 +162,7 @@ fn test_longer_text() {
     let encrypted = machine.encrypt(long_text);
 
     // Reset
-    machine.set_rotor_positions(['A', 'A', 'A']);
+    machine.reset();
 
     let decrypted = machine.decrypt(&encrypted);
     assert_eq!(clean_text(&decrypted), clean_text(long_text));
@@ -181,6 +181,40 @@ fn test_rotor_stepping() {
     assert_ne!(char1, char2);
 }
 
+/// Tests that non-letters are rejected without advancing the rotors
+#[test]
+fn test_encrypt_char_rejects_non_letters() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    for ch in ['1', ' ', '.', 'é', 'Ä', 'ß'] {
+        assert_eq!(machine.encrypt_char(ch), None);
+    }
+    assert_eq!(machine.get_rotor_positions(), vec!['A', 'A', 'A']);
+
+    // Lowercase letters are accepted and encrypt like their uppercase form
+    assert_eq!(machine.encrypt_char('a'), Some('B'));
+    assert_eq!(machine.get_rotor_positions(), vec!['A', 'A', 'B']);
+}
+
+/// Tests that process_exact round-trips without inserting group spaces
+#[test]
+fn test_process_exact_round_trip() {
+    let mut machine = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "AB CD")
+        .expect("Machine should be creatable");
+
+    let plaintext = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    let ciphertext = machine.process_exact(plaintext);
+    assert_eq!(ciphertext.len(), plaintext.len());
+    assert!(!ciphertext.contains(' '));
+
+    machine.reset();
+    assert_eq!(machine.encrypt(plaintext).replace(' ', ""), ciphertext);
+
+    machine.reset();
+    assert_eq!(machine.process_exact(&ciphertext), plaintext);
+}
+
 /// Tests configuration information
 #[test]
 fn test_configuration_info() {
@@ -195,4 +229,2640 @@ fn test_configuration_info() {
     assert!(config.contains("B C D")); // Positions
     assert!(config.contains("B")); // Reflector
     assert!(config.contains("AB CD")); // Plugboard
+    assert!(config.contains("Modell: Enigma I / M3"));
+    assert!(config.contains("Rotor 3: III (Ring G, Position D)"));
+}
+
+/// Tests the configuration report for other rotor counts
+#[test]
+fn test_configuration_info_models() {
+    let m4 = factory::create_m4_machine(
+        ["Gamma", "I", "II", "III"],
+        ['A', 'B', 'C', 'D'],
+        ['A', 'A', 'A', 'A'],
+        "C-Thin",
+        "",
+    )
+    .expect("M4 should be creatable");
+    let info = m4.get_configuration_info();
+    assert!(info.contains("Modell: Enigma M4"));
+    assert!(info.contains("Rotor 1: Gamma (Ring A, Position A)"));
+    assert!(info.contains("Rotor 4: III (Ring A, Position D)"));
+
+    let single = factory::create_custom_machine(["II"], ['Q'], ['A'], "B", "")
+        .expect("Machine should be creatable");
+    let info = single.get_configuration_info();
+    assert!(info.contains("Modell: Enigma mit 1 Rotor"));
+    assert!(info.contains("Positionen: Q"));
+}
+
+/// Tests the Display output of the machine and its components
+#[test]
+fn test_display() {
+    let machine = factory::create_standard_machine(['B', 'C', 'D'], ['E', 'F', 'G'], "AB CD")
+        .expect("Machine should be creatable");
+
+    assert_eq!(machine.to_string(), machine.get_configuration_info());
+    assert_eq!(machine.rotors[0].to_string(), "I (Ring E, Position B)");
+    assert_eq!(machine.reflector.to_string(), "B");
+    assert_eq!(machine.plugboard.to_string(), "AB CD");
+    assert!(format!("{}", machine).ends_with("Reflektor: B\nSteckerbrett: AB CD"));
+}
+
+/// Tests that equivalent (position, ring) pairs encipher the first character identically
+#[test]
+fn test_canonical_offset_equivalence() {
+    use enigma_rs::crypto::canonical_offset;
+
+    // Right rotor: position B / ring A and position D / ring C share the same offset
+    assert_eq!(canonical_offset(1, 0), canonical_offset(3, 2));
+    assert_eq!(canonical_offset(0, 25), 1);
+
+    for letter in b'A'..=b'Z' {
+        let input = letter as char;
+
+        let mut machine_a = factory::create_standard_machine(['A', 'A', 'B'], ['A', 'A', 'A'], "")
+            .expect("Machine should be creatable");
+        let mut machine_b = factory::create_standard_machine(['A', 'A', 'D'], ['A', 'A', 'C'], "")
+            .expect("Machine should be creatable");
+
+        assert_eq!(machine_a.encrypt_char(input), machine_b.encrypt_char(input));
+    }
+}
+
+/// Tests that the lazy encryption iterator matches the batch encryption
+#[test]
+fn test_encrypt_iter_matches_encrypt() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB CD")
+        .expect("Machine should be creatable");
+
+    let text = "Hello, lazy world!";
+    let expected = machine.encrypt(text);
+
+    machine.reset();
+    let streamed: String = machine.encrypt_iter(text).collect();
+
+    assert_eq!(streamed, expected);
+}
+
+/// Tests that the configuration fingerprint ignores positions but tracks the key
+#[test]
+fn test_configuration_fingerprint() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB CD")
+        .expect("Machine should be creatable");
+    let fingerprint = machine.configuration_fingerprint();
+    assert_eq!(fingerprint.len(), 8);
+
+    // Advancing the rotors is not a configuration change
+    machine.encrypt("DRIFT");
+    assert_eq!(machine.configuration_fingerprint(), fingerprint);
+
+    let other = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB CE")
+        .expect("Machine should be creatable");
+    assert_ne!(other.configuration_fingerprint(), fingerprint);
+
+    // The Enigma Uhr changes the encryption and therefore the fingerprint
+    let mut uhr = machine.clone();
+    uhr.plugboard.set_uhr_setting(Some(7)).unwrap();
+    assert_ne!(uhr.configuration_fingerprint(), fingerprint);
+    let mut other_uhr = machine.clone();
+    other_uhr.plugboard.set_uhr_setting(Some(8)).unwrap();
+    assert_ne!(other_uhr.configuration_hash(), uhr.configuration_hash());
+}
+
+/// Tests the middle-rotor double step (ADU -> ADV -> AEW -> BFX)
+#[test]
+fn test_double_step_turnovers() {
+    let mut machine = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    assert_eq!(machine.next_step_turnovers(), [false, false, true]);
+    machine.encrypt_char('A');
+    assert_eq!(machine.get_rotor_positions(), ['A', 'D', 'V']);
+
+    assert_eq!(machine.next_step_turnovers(), [false, true, true]);
+    machine.encrypt_char('A');
+    assert_eq!(machine.get_rotor_positions(), ['A', 'E', 'W']);
+
+    assert_eq!(machine.next_step_turnovers(), [true, true, true]);
+    machine.encrypt_char('A');
+    assert_eq!(machine.get_rotor_positions(), ['B', 'F', 'X']);
+}
+
+/// Tests notch accessors and turnover prediction
+#[test]
+fn test_next_turnover_in() {
+    use enigma_rs::rotor::rotors;
+
+    let mut machine = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    assert_eq!(machine.rotors[2].get_notch_char(), Some('V'));
+    assert_eq!(machine.rotors[2].steps_until_notch(), Some(1));
+    assert_eq!(machine.next_turnover_in(), Some(2));
+
+    machine.encrypt_char('A');
+    assert_eq!(machine.next_turnover_in(), Some(1));
+    machine.encrypt_char('A');
+    // Double step: the middle rotor stands at its own notch
+    assert_eq!(machine.next_turnover_in(), Some(1));
+    machine.encrypt_char('A');
+    assert_eq!(machine.get_rotor_positions(), ['B', 'F', 'X']);
+    assert_eq!(machine.next_turnover_in(), Some(25));
+
+    // The prediction matches the actual stepping
+    for _ in 0..100 {
+        let expected = machine.next_turnover_in().expect("Middle rotor steps");
+        let middle = machine.rotors[1].position;
+        for _ in 1..expected {
+            machine.encrypt_char('A');
+            assert_eq!(machine.rotors[1].position, middle);
+        }
+        machine.encrypt_char('A');
+        assert_ne!(machine.rotors[1].position, middle);
+    }
+
+    let double_notch = rotors::rotor_vi(0, 0).expect("Rotor VI should be creatable");
+    assert_eq!(double_notch.get_notch_char(), Some('M'));
+    let beta = rotors::rotor_beta(0, 0).expect("Rotor Beta should be creatable");
+    assert_eq!(beta.get_notch_char(), None);
+    assert_eq!(beta.steps_until_notch(), None);
+}
+
+/// Tests the gear-driven odometer stepping of the Enigma G
+#[test]
+fn test_gear_stepping_model() {
+    use enigma_rs::machine::{MachineConfig, SteppingModel};
+
+    let mut machine = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    assert_eq!(machine.get_stepping_model(), SteppingModel::PawlRatchet);
+    machine.set_stepping_model(SteppingModel::Gear);
+
+    // III passes its notch V: the middle rotor advances to E
+    machine.encrypt_char('A');
+    assert_eq!(machine.get_rotor_positions(), ['A', 'D', 'V']);
+    machine.encrypt_char('A');
+    assert_eq!(machine.get_rotor_positions(), ['A', 'E', 'W']);
+    // No double step: the middle rotor stays at its notch E
+    assert_eq!(machine.next_step_turnovers(), [false, false, true]);
+    machine.encrypt_char('A');
+    assert_eq!(machine.get_rotor_positions(), ['A', 'E', 'X']);
+
+    // The left rotor only moves once the middle rotor leaves E
+    machine.advance(23);
+    assert_eq!(machine.get_rotor_positions(), ['A', 'E', 'U']);
+    assert_eq!(machine.next_turnover_in(), Some(2));
+    machine.advance(2);
+    assert_eq!(machine.get_rotor_positions(), ['B', 'F', 'W']);
+
+    // Both models remain reciprocal
+    machine.reset();
+    let ciphertext = machine.encrypt("ENIGMAGEAR");
+    machine.reset();
+    assert_eq!(clean_text(&machine.decrypt(&ciphertext)), "ENIGMAGEAR");
+
+    // The model survives a config round trip and is part of the hash
+    machine.reset();
+    let config = machine.to_config();
+    assert_eq!(config.stepping_model, SteppingModel::Gear);
+    let restored = MachineConfig::from_json(&config.to_json().unwrap()).unwrap();
+    assert_eq!(factory::from_config(&restored).unwrap(), machine);
+    assert!(config.to_bytes().is_err());
+
+    let pawl = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "").unwrap();
+    assert_ne!(pawl.configuration_hash(), machine.configuration_hash());
+
+    // Older configurations without the field keep the pawl mechanism
+    let json = pawl.to_config().to_json().unwrap();
+    let legacy = json.replace(",\n  \"stepping_model\": \"PawlRatchet\"", "");
+    assert!(!legacy.contains("stepping_model"));
+    assert_eq!(
+        MachineConfig::from_json(&legacy).unwrap().stepping_model,
+        SteppingModel::PawlRatchet
+    );
+}
+
+/// Tests display grouping of cipher output
+#[test]
+fn test_group_text() {
+    use enigma_rs::utils::group_text;
+
+    assert_eq!(group_text("ABCDEFGHIJKL", 5), "ABCDE FGHIJ KL");
+    assert_eq!(group_text("ABCDE FGHIJ KL", 0), "ABCDEFGHIJKL");
+    assert_eq!(group_text("", 5), "");
+}
+
+/// Tests building a reflector from letter pairs
+#[test]
+fn test_reflector_from_pairs() {
+    use enigma_rs::reflector::reflectors::reflector_b;
+    use enigma_rs::reflector::Reflector;
+
+    let reflector = Reflector::from_pairs("AY BR CU DH EQ FS GL IP JX KN MO TZ VW")
+        .expect("Reflector B pairs should be valid");
+    assert_eq!(reflector.wiring, reflector_b().unwrap().wiring);
+
+    // Fixed point, reused letter and incomplete pairing are rejected
+    assert!(Reflector::from_pairs("AA BR CU DH EQ FS GL IP JX KN MO TZ VW").is_err());
+    assert!(Reflector::from_pairs("AY AR CU DH EQ FS GL IP JX KN MO TZ VW").is_err());
+    assert!(Reflector::from_pairs("AY BR CU DH EQ FS GL IP JX KN MO TZ").is_err());
+}
+
+/// Tests that a machine with a pair-built reflector can be saved, reloaded
+/// and told apart from other custom wirings
+#[test]
+fn test_reflector_from_pairs_round_trip() {
+    use enigma_rs::machine::MachineConfig;
+    use enigma_rs::reflector::Reflector;
+
+    let pairs = "AZ BY CX DW EV FU GT HS IR JQ KP LO MN";
+    let mut machine = factory::create_standard_machine(['A'; 3], ['A'; 3], "AB CD").unwrap();
+    machine.reflector = Reflector::from_pairs(pairs).unwrap();
+    assert_eq!(machine.reflector.name, "CUSTOM:ZYXWVUTSRQPONMLKJIHGFEDCBA");
+
+    let config = MachineConfig::from_json(&machine.to_config().to_json().unwrap()).unwrap();
+    let mut rebuilt = factory::from_config(&config).expect("Custom reflector should reload");
+    assert_eq!(rebuilt.encrypt("SPIEGEL"), machine.encrypt("SPIEGEL"));
+
+    // Different wirings give different fingerprints, equal wirings the same
+    let mut other = machine.clone();
+    other.reflector = Reflector::from_pairs("AY BZ CX DW EV FU GT HS IR JQ KP LO MN").unwrap();
+    assert_ne!(
+        other.configuration_fingerprint(),
+        machine.configuration_fingerprint()
+    );
+    let mut named_b = machine.clone();
+    named_b.reflector = Reflector::from_pairs("AY BR CU DH EQ FS GL IP JX KN MO TZ VW").unwrap();
+    let standard_b = factory::create_standard_machine(['A'; 3], ['A'; 3], "AB CD").unwrap();
+    assert_eq!(
+        named_b.configuration_hash(),
+        standard_b.configuration_hash()
+    );
+
+    // Swapping rotors keeps the custom wiring
+    machine
+        .reconfigure(&["II", "I", "III"], &machine.reflector.name.clone())
+        .expect("Custom reflector name should be accepted");
+    assert_eq!(machine.reflector.name, "CUSTOM:ZYXWVUTSRQPONMLKJIHGFEDCBA");
+}
+
+/// Tests that decrypting grouped ciphertext verbatim recovers the plaintext
+#[test]
+fn test_decrypt_grouped_output_directly() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB CD")
+        .expect("Machine should be creatable");
+
+    let original = "GROUPING MUST NOT BREAK THE ROUND TRIP";
+    let encrypted = machine.encrypt(original);
+    assert!(encrypted.contains(' '), "Output should be grouped");
+
+    machine.reset();
+
+    // The group spaces are fed back in unchanged; decrypt's clean_text drops them
+    let decrypted = machine.decrypt(&encrypted);
+    assert_eq!(clean_text(&decrypted), clean_text(original));
+}
+
+/// Tests that a configuration survives a JSON round trip
+#[test]
+fn test_config_json_round_trip() {
+    use enigma_rs::machine::MachineConfig;
+
+    let mut machine = factory::create_custom_machine(
+        ["II", "IV", "V"],
+        ['B', 'U', 'L'],
+        ['C', 'D', 'E'],
+        "C",
+        "AB CD EF",
+    )
+    .expect("Machine should be creatable");
+
+    let config = machine.to_config();
+    assert_eq!(config.rotor_positions, vec!['B', 'U', 'L']);
+
+    let json = config.to_json().expect("Config should serialize");
+    let restored = MachineConfig::from_json(&json).expect("Config should deserialize");
+    assert_eq!(restored, config);
+
+    let mut rebuilt = factory::from_config(&restored).expect("Machine should be creatable");
+    assert_eq!(rebuilt.to_config(), config);
+    assert_eq!(rebuilt.encrypt("ROUNDTRIP"), machine.encrypt("ROUNDTRIP"));
+}
+
+/// Tests that a machine with all 13 cables can be saved and rebuilt
+#[test]
+fn test_config_round_trip_with_full_plugboard() {
+    use enigma_rs::machine::MachineConfig;
+    use enigma_rs::plugboard::{Plugboard, FULL_CONNECTIONS};
+
+    let all = "AB CD EF GH IJ KL MN OP QR ST UV WX YZ";
+    let mut machine = factory::create_standard_machine(['A', 'B', 'C'], ['A'; 3], "").unwrap();
+    machine.plugboard = Plugboard::from_string_with_max(all, FULL_CONNECTIONS).unwrap();
+
+    let config = machine.to_config();
+    let restored = MachineConfig::from_json(&config.to_json().unwrap()).unwrap();
+    let mut rebuilt =
+        factory::from_config(&restored).expect("13 cables should be accepted from a config");
+    assert_eq!(rebuilt.plugboard.get_connection_count(), FULL_CONNECTIONS);
+    assert_eq!(rebuilt.to_config(), config);
+    assert_eq!(rebuilt.encrypt("ROUNDTRIP"), machine.encrypt("ROUNDTRIP"));
+
+    // The operator-facing formats keep the historical limit of 10 cables
+    assert!(MachineConfig::from_settings("I II III", "AAA", "AAA", "B", all).is_err());
+}
+
+/// Tests that the Enigma Uhr setting survives saving and rebuilding
+#[test]
+fn test_config_round_trip_with_uhr() {
+    use enigma_rs::machine::MachineConfig;
+
+    let pairs = "AB CD EF GH IJ KL MN OP QR ST";
+    let mut machine = factory::create_standard_machine(['A'; 3], ['A'; 3], pairs).unwrap();
+    machine.plugboard.set_uhr_setting(Some(7)).unwrap();
+
+    let config = machine.to_config();
+    assert_eq!(config.uhr_setting, Some(7));
+    assert_ne!(config.state_fingerprint(), {
+        let mut without = config.clone();
+        without.uhr_setting = None;
+        without.state_fingerprint()
+    });
+
+    let restored = MachineConfig::from_json(&config.to_json().unwrap()).unwrap();
+    let mut rebuilt = factory::from_config(&restored).expect("Machine should be creatable");
+    assert_eq!(rebuilt.plugboard.get_uhr_setting(), Some(7));
+    assert_eq!(machine.encrypt("HELLOWORLD").trim(), "KPVBH JKGXV");
+    assert_eq!(rebuilt.encrypt("HELLOWORLD").trim(), "KPVBH JKGXV");
+
+    // Older configuration files without the field have no Uhr
+    let legacy = MachineConfig::from_json(
+        &machine
+            .to_config()
+            .to_json()
+            .unwrap()
+            .replace(",\n  \"uhr_setting\": 7", ""),
+    )
+    .unwrap();
+    assert_eq!(legacy.uhr_setting, None);
+}
+
+/// Tests the four-rotor M4 with a stationary Greek rotor
+#[test]
+fn test_m4_machine() {
+    let mut machine = factory::create_m4_machine(
+        ["Beta", "I", "II", "III"],
+        ['C', 'A', 'D', 'U'],
+        ['A', 'A', 'A', 'A'],
+        "B-Thin",
+        "AB CD",
+    )
+    .expect("M4 should be creatable");
+
+    let text = "UBOOTVERKEHR";
+    let encrypted = machine.encrypt(text);
+
+    // The Greek rotor never steps, the other three behave like an Enigma I
+    assert_eq!(machine.get_rotor_positions()[0], 'C');
+    assert!(!machine.next_step_turnovers()[0]);
+
+    machine.reset();
+    let decrypted = machine.decrypt(&encrypted);
+    assert_eq!(clean_text(&decrypted), clean_text(text));
+
+    // The Greek rotor only fits the leftmost slot, and M4 needs a thin reflector
+    assert!(factory::create_m4_machine(
+        ["I", "Beta", "II", "III"],
+        ['A'; 4],
+        ['A'; 4],
+        "B-Thin",
+        ""
+    )
+    .is_err());
+    assert!(
+        factory::create_m4_machine(["Beta", "I", "II", "III"], ['A'; 4], ['A'; 4], "B", "")
+            .is_err()
+    );
+}
+
+/// Tests that rotors VI-VIII carry over at both of their notches (M and Z)
+#[test]
+fn test_double_notch_rotors() {
+    for right in ["VI", "VII", "VIII"] {
+        for (start, after) in [('M', 'N'), ('Z', 'A')] {
+            let mut machine = factory::create_custom_machine(
+                ["I", "II", right],
+                ['A', 'A', start],
+                ['A', 'A', 'A'],
+                "B",
+                "",
+            )
+            .expect("Machine should be creatable");
+
+            machine.encrypt_char('A');
+            assert_eq!(machine.get_rotor_positions(), ['A', 'B', after]);
+        }
+
+        // Any other position does not move the middle rotor
+        let mut machine = factory::create_custom_machine(
+            ["I", "II", right],
+            ['A', 'A', 'Q'],
+            ['A', 'A', 'A'],
+            "B",
+            "",
+        )
+        .expect("Machine should be creatable");
+        machine.encrypt_char('A');
+        assert_eq!(machine.get_rotor_positions(), ['A', 'A', 'R']);
+    }
+}
+
+/// Tests that the signal trace is consistent with the encrypted letter
+#[test]
+fn test_encrypt_char_traced() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB")
+        .expect("Machine should be creatable");
+    let mut reference = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB")
+        .expect("Machine should be creatable");
+
+    let trace = machine
+        .encrypt_char_traced('A')
+        .expect("Letters should be traced");
+
+    assert_eq!(trace.input, 'A');
+    assert_eq!(trace.after_plugboard, 'B');
+    assert_eq!(trace.forward.len(), 3);
+    assert_eq!(trace.backward.len(), 3);
+    assert_eq!(trace.rotor_positions, ['A', 'A', 'B']);
+    assert_eq!(Some(trace.output), reference.encrypt_char('A'));
+
+    // One line with every hop, as printed by the CLI with --trace
+    let line = trace.to_string();
+    assert!(line.starts_with("[A A B] Stecker A→B | Walzen B→"));
+    assert!(line.ends_with(&format!("→{}", trace.output)));
+    assert_eq!(line.matches('|').count(), 4);
+
+    // Non-letters are rejected before the rotors step
+    for input in ['1', ' ', 'é'] {
+        assert!(machine.encrypt_char_traced(input).is_none());
+    }
+    assert_eq!(machine.get_rotor_positions(), ['A', 'A', 'B']);
+}
+
+/// Tests the non-stepping substitution
+#[test]
+fn test_transform_char_does_not_step() {
+    let machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    let first = machine
+        .transform_char('A')
+        .expect("Letters should be transformed");
+    assert_eq!(machine.transform_char('A'), Some(first));
+    assert_eq!(machine.get_rotor_positions(), ['A', 'A', 'A']);
+
+    // The substitution is reciprocal and never maps a letter to itself
+    assert_eq!(machine.transform_char(first), Some('A'));
+    assert_ne!(first, 'A');
+
+    // Non-letters have no substitution instead of borrowing the one of 'A'
+    assert_eq!(machine.transform_char('a'), Some(first));
+    assert_eq!(machine.transform_char('é'), None);
+    assert_eq!(machine.transform_char('1'), None);
+
+    // encrypt_char uses the substitution of the stepped state
+    let mut stepped = factory::create_standard_machine(['A', 'A', 'Z'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    assert_eq!(stepped.encrypt_char('A'), Some(first));
+}
+
+/// Tests that formatting survives the preserving encryption
+#[test]
+fn test_encrypt_preserving() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB CD")
+        .expect("Machine should be creatable");
+
+    let text = "Hello, World! 42";
+    let encrypted = machine.encrypt_preserving(text);
+
+    assert_eq!(encrypted.len(), text.len());
+    assert_eq!(&encrypted[5..7], ", ");
+    assert!(encrypted.ends_with("! 42"));
+    assert!(encrypted.starts_with(|c: char| c.is_ascii_uppercase()));
+
+    // Letters are encrypted exactly as by the grouped encrypt
+    machine.reset();
+    let grouped = machine.encrypt(text);
+    assert_eq!(clean_text(&encrypted), clean_text(&grouped));
+
+    machine.reset();
+    assert_eq!(machine.encrypt_preserving(&encrypted), text);
+}
+
+/// Tests configurable output grouping
+#[test]
+fn test_output_grouping() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    assert_eq!(machine.get_output_grouping(), Some(5));
+
+    let text = "ABCDEFGHIJ";
+    let grouped = machine.encrypt(text);
+
+    machine.reset();
+    machine.set_output_grouping(Some(4));
+    let grouped_by_four = machine.encrypt(text);
+    assert_eq!(grouped_by_four.split_whitespace().next().unwrap().len(), 4);
+
+    machine.reset();
+    machine.set_output_grouping(None);
+    let continuous = machine.encrypt(text);
+    assert!(!continuous.contains(' '));
+    assert_eq!(continuous, clean_text(&grouped));
+    assert_eq!(continuous, clean_text(&grouped_by_four));
+}
+
+/// Tests toggling plugboard wires
+#[test]
+fn test_plugboard_toggle_connection() {
+    use enigma_rs::plugboard::Plugboard;
+
+    let mut plugboard = Plugboard::new();
+
+    assert_eq!(plugboard.toggle_connection('A', 'B'), Ok(true));
+    assert!(plugboard.is_connected('A') && plugboard.is_connected('B'));
+
+    // A is already wired to B, so it cannot be wired to C
+    assert!(plugboard.toggle_connection('A', 'C').is_err());
+    assert!(plugboard.toggle_connection('C', 'B').is_err());
+
+    // Toggling the same pair (in either order) removes the wire
+    assert_eq!(plugboard.toggle_connection('b', 'a'), Ok(false));
+    assert_eq!(plugboard.get_connection_count(), 0);
+}
+
+/// Tests the Enigma Uhr plugboard accessory
+#[test]
+fn test_enigma_uhr() {
+    const PAIRS: &str = "AB CD EF GH IJ KL MN OP QR ST";
+
+    let mut plain = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], PAIRS)
+        .expect("Machine should be creatable");
+    let mut uhr = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], PAIRS)
+        .expect("Machine should be creatable");
+
+    // Dial position 00 is the ordinary reciprocal plugboard
+    uhr.plugboard.set_uhr_setting(Some(0)).unwrap();
+    assert_eq!(uhr.encrypt("UHRSTELLUNG"), plain.encrypt("UHRSTELLUNG"));
+
+    // Other positions make the plugboard non-reciprocal
+    uhr.plugboard.set_uhr_setting(Some(27)).unwrap();
+    let letters = (b'A'..=b'Z').map(|b| b as char);
+    assert!(letters
+        .clone()
+        .any(|c| uhr.plugboard.process_forward(c) != uhr.plugboard.process_backward(c)));
+    for c in letters {
+        assert_eq!(
+            uhr.plugboard
+                .process_backward(uhr.plugboard.process_forward(c)),
+            c
+        );
+    }
+
+    // ...but the machine as a whole stays self-reciprocal
+    uhr.reset();
+    let encrypted = uhr.encrypt("GEHEIMEKOMMANDOSACHE");
+    uhr.reset();
+    assert_eq!(clean_text(&uhr.decrypt(&encrypted)), "GEHEIMEKOMMANDOSACHE");
+
+    assert!(uhr.plugboard.set_uhr_setting(Some(40)).is_err());
+}
+
+/// Tests that reset restores the starting positions but keeps ring settings
+#[test]
+fn test_reset_to_initial_positions() {
+    let mut machine = factory::create_standard_machine(['Q', 'E', 'V'], ['B', 'C', 'D'], "")
+        .expect("Machine should be creatable");
+
+    machine.encrypt("ADVANCE THE ROTORS PAST SEVERAL TURNOVERS");
+    assert_ne!(machine.get_rotor_positions(), ['Q', 'E', 'V']);
+
+    machine.reset();
+    assert_eq!(machine.get_rotor_positions(), ['Q', 'E', 'V']);
+    assert_eq!(machine.get_ring_settings(), ['B', 'C', 'D']);
+}
+
+/// Tests the plugboard cable limit
+#[test]
+fn test_plugboard_max_connections() {
+    use enigma_rs::plugboard::{Plugboard, DEFAULT_MAX_CONNECTIONS};
+    use enigma_rs::utils::generate_random_plugboard;
+
+    let ten = "AB CD EF GH IJ KL MN OP QR ST";
+    let all = "AB CD EF GH IJ KL MN OP QR ST UV WX YZ";
+
+    assert_eq!(
+        Plugboard::from_string(ten).unwrap().get_connection_count(),
+        10
+    );
+    assert!(Plugboard::from_string(all).is_err());
+
+    let full = Plugboard::from_string_with_max(all, 13).expect("13 pairs should be allowed");
+    assert_eq!(full.get_connection_count(), 13);
+
+    let mut limited = Plugboard::with_max_connections(1).unwrap();
+    limited.add_connection('A', 'B').unwrap();
+    assert!(limited.add_connection('C', 'D').is_err());
+    assert!(Plugboard::with_max_connections(14).is_err());
+
+    // The random generator stays within the default limit
+    let random = generate_random_plugboard();
+    assert_eq!(
+        Plugboard::from_string(&random)
+            .unwrap()
+            .get_connection_count(),
+        DEFAULT_MAX_CONNECTIONS
+    );
+}
+
+/// Tests streaming encryption from an arbitrary character source
+#[test]
+fn test_encrypt_chars_stream() {
+    use std::io::{BufRead, BufReader};
+
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB")
+        .expect("Machine should be creatable");
+
+    let input = "first line\nsecond line\n";
+    let expected = clean_text(&machine.encrypt(input));
+    machine.reset();
+
+    let reader = BufReader::new(input.as_bytes());
+    let chars = reader
+        .lines()
+        .map_while(Result::ok)
+        .flat_map(|line| line.chars().collect::<Vec<_>>());
+    let streamed: String = machine.encrypt_chars(chars).collect();
+
+    assert_eq!(streamed, expected);
+}
+
+/// Tests recovering the start positions from a known crib
+#[test]
+fn test_brute_force_positions() {
+    use enigma_rs::crack::{brute_force_positions, possible_crib_offsets};
+
+    let mut machine = factory::create_custom_machine(
+        ["I", "II", "III"],
+        ['C', 'Q', 'M'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    )
+    .expect("Machine should be creatable");
+    let ciphertext = machine.encrypt("WETTERBERICHTFUERDIENORDSEE");
+
+    assert!(possible_crib_offsets(&ciphertext, "WETTERBERICHT").contains(&0));
+
+    let candidates = brute_force_positions(&ciphertext, "WETTERBERICHT", ["I", "II", "III"], "B");
+    assert!(candidates
+        .iter()
+        .any(|config| config.rotor_positions == vec!['C', 'Q', 'M']));
+    assert!(candidates.len() < 5, "Crib should narrow the search");
+}
+
+/// Tests the statistical scores used to rank candidate decryptions
+#[test]
+fn test_index_of_coincidence_and_chi_squared() {
+    use enigma_rs::utils::{chi_squared_english, index_of_coincidence};
+
+    let english = "It was the best of times, it was the worst of times, it was the age of \
+        wisdom, it was the age of foolishness, it was the epoch of belief, it was the epoch \
+        of incredulity, it was the season of Light, it was the season of Darkness, it was the \
+        spring of hope, it was the winter of despair, we had everything before us, we had \
+        nothing before us, we were all going direct to Heaven, we were all going direct the \
+        other way";
+
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    let random = machine.encrypt(&english.repeat(4));
+
+    let english_ioc = index_of_coincidence(english);
+    let random_ioc = index_of_coincidence(&random);
+    assert!(
+        (english_ioc - 0.067).abs() < 0.01,
+        "English IoC {}",
+        english_ioc
+    );
+    assert!(
+        (random_ioc - 0.038).abs() < 0.005,
+        "Random IoC {}",
+        random_ioc
+    );
+
+    assert!(chi_squared_english(english) < chi_squared_english(&random));
+    assert_eq!(index_of_coincidence("A"), 0.0);
+    assert_eq!(index_of_coincidence("AAAA"), 1.0);
+}
+
+/// Tests machines built from arbitrary rotor wirings
+#[test]
+fn test_custom_rotor_wirings() {
+    let mut custom = factory::create_machine_with_wirings(
+        [
+            ("EKMFLGDQVZNTOWYHXUSPAIBRCJ", 'Q'),
+            ("AJDKSIRUXBLHWTMCQGZNPYFVOE", 'E'),
+            ("BDFHJLCPRTXVZNYEIWGAKMUSQO", 'V'),
+        ],
+        ['A', 'D', 'U'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    )
+    .expect("Historical wirings should be valid");
+    let mut standard = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    let text = "CUSTOMWIRINGSBEHAVELIKETHEHISTORICALONES";
+    assert_eq!(custom.encrypt(text), standard.encrypt(text));
+
+    // Round trip through the configuration keeps the custom rotors
+    let config = custom.to_config();
+    assert!(factory::from_config(&config).is_ok());
+
+    // Not a permutation: E appears twice
+    assert!(factory::create_machine_with_wirings(
+        [
+            ("EEMFLGDQVZNTOWYHXUSPAIBRCJ", 'Q'),
+            ("AJDKSIRUXBLHWTMCQGZNPYFVOE", 'E'),
+            ("BDFHJLCPRTXVZNYEIWGAKMUSQO", 'V'),
+        ],
+        ['A', 'A', 'A'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    )
+    .is_err());
+
+    let mixed = factory::create_custom_machine(
+        ["CUSTOM:EKMFLGDQVZNTOWYHXUSPAIBRCJ@Q", "II", "III"],
+        ['A', 'A', 'A'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    );
+    assert!(mixed.is_ok());
+}
+
+/// Tests the rewirable reflector UKW-D
+#[test]
+fn test_reflector_d() {
+    use enigma_rs::reflector::reflectors::reflector_d;
+
+    let pairs = "AB CD EF GH IK LM NO PQ RS TU VW XZ";
+    let reflector = reflector_d(pairs).expect("12 pairs without J and Y should be valid");
+    assert_eq!(reflector.reflect('J'), 'Y');
+    assert_eq!(reflector.reflect('Y'), 'J');
+    assert_eq!(reflector.reflect('A'), 'B');
+
+    // J and Y may not be rewired, and exactly 12 pairs are required
+    assert!(reflector_d("AJ CD EF GH IK LM NO PQ RS TU VW XZ").is_err());
+    assert!(reflector_d("AB CD EF").is_err());
+
+    let mut machine = factory::create_custom_machine(
+        ["I", "II", "III"],
+        ['A', 'A', 'A'],
+        ['A', 'A', 'A'],
+        &format!("D:{}", pairs),
+        "",
+    )
+    .expect("UKW-D machine should be creatable");
+    let mut restored =
+        factory::from_config(&machine.to_config()).expect("UKW-D configuration should round-trip");
+
+    let encrypted = machine.encrypt("SPAETKRIEGSVERKEHR");
+    assert_eq!(
+        clean_text(&restored.decrypt(&encrypted)),
+        "SPAETKRIEGSVERKEHR"
+    );
+}
+
+/// Tests that no letter ever encrypts to itself
+#[test]
+fn test_no_self_encryption() {
+    use enigma_rs::reflector::Reflector;
+
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    // Many positions, all 26 inputs at each of them
+    for _ in 0..200 {
+        let before = machine.get_rotor_positions();
+        for letter in 'A'..='Z' {
+            assert_ne!(machine.transform_char(letter), Some(letter));
+        }
+        machine.encrypt_char('A');
+        assert_ne!(machine.get_rotor_positions(), before);
+    }
+
+    let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let positions = machine.get_rotor_positions();
+    assert!(machine.verify_no_self_encryption(&alphabet.repeat(10)));
+    assert_eq!(machine.get_rotor_positions(), positions);
+
+    // The identity "reflector" is a permutation with fixed points
+    machine.reflector = Reflector::new(alphabet, "Identity").unwrap();
+    assert!(!machine.verify_no_self_encryption(alphabet));
+}
+
+/// Tests that ring settings from the factory and from set_ring_settings agree
+#[test]
+fn test_ring_settings_factory_matches_setter() {
+    let plaintext = "RINGSTELLUNGREGRESSIONSTEST";
+
+    let mut built = factory::create_standard_machine(['A', 'A', 'A'], ['B', 'B', 'B'], "")
+        .expect("Machine should be creatable");
+
+    let mut configured = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    configured.set_ring_settings(['B', 'B', 'B']);
+
+    let mut lowercase = factory::create_standard_machine(['a', 'a', 'a'], ['b', 'b', 'b'], "")
+        .expect("Lowercase settings should be accepted like in set_ring_settings");
+
+    assert_eq!(built.get_ring_settings(), configured.get_ring_settings());
+    let expected = built.encrypt(plaintext);
+    assert_eq!(configured.encrypt(plaintext), expected);
+    assert_eq!(lowercase.encrypt(plaintext), expected);
+
+    assert!(factory::create_standard_machine(['A', 'A', '1'], ['A', 'A', 'A'], "").is_err());
+}
+
+/// Tests parsing settings written as letters or as key sheet numbers
+#[test]
+fn test_parse_settings() {
+    use enigma_rs::utils::{parse_setting, parse_settings};
+
+    assert_eq!(parse_settings("abc").unwrap(), ['A', 'B', 'C']);
+    assert_eq!(parse_settings("1 2 3").unwrap(), ['A', 'B', 'C']);
+    assert_eq!(parse_settings("01-02-26").unwrap(), ['A', 'B', 'Z']);
+    assert_eq!(parse_settings("2,21,12").unwrap(), ['B', 'U', 'L']);
+
+    assert!(parse_settings("0 1 2").is_err());
+    assert!(parse_settings("1 2 27").is_err());
+    assert!(parse_settings("A1B").is_err());
+
+    assert_eq!(parse_setting("03"), Ok('C'));
+    assert_eq!(parse_setting("q"), Ok('Q'));
+    assert!(parse_setting("AB").is_err());
+}
+
+/// Tests machines with fewer or more than three rotors
+#[test]
+fn test_arbitrary_rotor_counts() {
+    use enigma_rs::machine::MachineConfig;
+
+    for rotor_types in [vec!["III"], vec!["II", "III"], vec!["IV", "I", "II", "III"]] {
+        let count = rotor_types.len();
+        let mut machine = factory::create_custom_machine(
+            &rotor_types,
+            vec!['A'; count],
+            vec!['A'; count],
+            "B",
+            "",
+        )
+        .expect("Machine should be creatable");
+        assert_eq!(machine.rotors.len(), count);
+
+        let encrypted = machine.encrypt("LEHRMASCHINE");
+        machine.reset();
+        assert_eq!(clean_text(&machine.decrypt(&encrypted)), "LEHRMASCHINE");
+    }
+
+    // The rightmost rotor steps on every key press and carries into the left one
+    let mut two_rotors =
+        factory::create_custom_machine(["I", "III"], ['A', 'V'], ['A', 'A'], "B", "")
+            .expect("Machine should be creatable");
+    two_rotors.encrypt_char('A');
+    assert_eq!(two_rotors.get_rotor_positions(), ['B', 'W']);
+
+    let config = MachineConfig {
+        rotor_types: vec![
+            "Beta".to_string(),
+            "I".to_string(),
+            "II".to_string(),
+            "III".to_string(),
+        ],
+        rotor_positions: vec!['A'; 4],
+        ring_settings: vec!['A'; 4],
+        reflector_type: "B-Thin".to_string(),
+        plugboard: String::new(),
+        stepping_model: Default::default(),
+        uhr_setting: None,
+    };
+    let mut m4 = factory::from_config(&config).expect("M4 configuration should be accepted");
+    assert_eq!(clean_text(&m4.encrypt("AAAAA")), "BDZGO");
+
+    assert!(factory::create_custom_machine::<&str>([], [], [], "B", "").is_err());
+}
+
+/// Tests that random key-sheet plugboards have the requested number of cables
+#[test]
+fn test_generate_random_plugboard_cables() {
+    use enigma_rs::plugboard::Plugboard;
+    use enigma_rs::utils::generate_random_plugboard_cables;
+
+    for _ in 0..20 {
+        let key = generate_random_plugboard_cables(10);
+        assert_eq!(key.split(' ').count(), 10);
+        assert!(Plugboard::from_string(&key).is_ok());
+    }
+    assert_eq!(generate_random_plugboard_cables(20).split(' ').count(), 13);
+    assert_eq!(generate_random_plugboard_cables(0), "");
+}
+
+/// Tests that a rotor type can only be selected once by default
+#[test]
+fn test_duplicate_rotors_rejected() {
+    let result =
+        factory::create_custom_machine(["I", "II", "I"], ['A', 'A', 'A'], ['A', 'A', 'A'], "B", "");
+    assert_eq!(
+        result.err(),
+        Some("Rotor I wurde mehrfach ausgewählt".to_string())
+    );
+    assert!(factory::create_m4_machine(
+        ["Beta", "II", "II", "III"],
+        ['A'; 4],
+        ['A'; 4],
+        "B-Thin",
+        ""
+    )
+    .is_err());
+
+    let mut config = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable")
+        .to_config();
+    config.rotor_types = vec!["III".to_string(), "III".to_string(), "III".to_string()];
+    assert!(factory::from_config(&config).is_err());
+    assert!(factory::from_config_allowing_duplicates(&config).is_ok());
+
+    let mut machine = factory::create_custom_machine_allowing_duplicates(
+        ["I", "I", "I"],
+        ['A', 'A', 'A'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    )
+    .expect("Duplicates should be allowed explicitly");
+    assert_eq!(clean_text(&machine.encrypt("AAAAA")).len(), 5);
+
+    assert_eq!(factory::duplicate_rotor(&["I", "II", "III"]), None);
+    assert_eq!(factory::duplicate_rotor(&["IV", "V", "V"]), Some("V"));
+}
+
+/// Tests the shared parser for rotor and reflector names
+#[test]
+fn test_rotor_and_reflector_type_parsing() {
+    use enigma_rs::reflector::ReflectorType;
+    use enigma_rs::rotor::RotorType;
+
+    for name in [
+        "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "Beta", "Gamma",
+    ] {
+        let rotor_type: RotorType = name.parse().expect("Rotor type should parse");
+        assert_eq!(rotor_type.to_string(), name);
+        let rotor = rotor_type.create(0, 0).expect("Rotor should be creatable");
+        assert_eq!(rotor.name, name);
+    }
+    assert!(RotorType::Beta.is_stationary());
+    assert!("IX".parse::<RotorType>().is_err());
+    assert!("i".parse::<RotorType>().is_err());
+
+    let custom: RotorType = "CUSTOM:ekmflgdqvzntowyhxuspaibrcj@q"
+        .parse()
+        .expect("Custom rotor should parse");
+    assert_eq!(custom.to_string(), "CUSTOM:EKMFLGDQVZNTOWYHXUSPAIBRCJ@Q");
+    assert!("CUSTOM:AAAAAAAAAAAAAAAAAAAAAAAAAA@A"
+        .parse::<RotorType>()
+        .is_err());
+
+    for name in ["A", "B", "C", "B-Thin", "C-Thin"] {
+        let reflector_type: ReflectorType = name.parse().expect("Reflector type should parse");
+        assert_eq!(reflector_type.to_string(), name);
+        assert_eq!(reflector_type.create().unwrap().name, name);
+    }
+    assert!(ReflectorType::CThin.is_thin());
+    let ukw_d: ReflectorType = "D:ak bz cu dv ei fr gl hp mo nw qs tx"
+        .parse()
+        .expect("UKW-D should parse");
+    assert_eq!(ukw_d.to_string(), "D:AK BZ CU DV EI FR GL HP MO NW QS TX");
+    assert!("D:AB".parse::<ReflectorType>().is_err());
+    assert!("E".parse::<ReflectorType>().is_err());
+
+    // Greek rotors and thin reflectors are reserved for the M4
+    assert!(
+        factory::create_custom_machine(["Beta", "II", "III"], ['A'; 3], ['A'; 3], "B", "").is_err()
+    );
+    assert!(
+        factory::create_custom_machine(["I", "II", "III"], ['A'; 3], ['A'; 3], "B-Thin", "")
+            .is_err()
+    );
+}
+
+/// Tests building a plugboard from letter pairs
+#[test]
+fn test_plugboard_from_pairs() {
+    use enigma_rs::plugboard::Plugboard;
+
+    let plugboard =
+        Plugboard::from_pairs(&[('A', 'B'), ('c', 'D')]).expect("Pairs should be valid");
+    assert_eq!(
+        plugboard.get_connections_string(),
+        Plugboard::from_string("AB CD")
+            .unwrap()
+            .get_connections_string()
+    );
+    assert_eq!(plugboard.process('C'), 'D');
+
+    assert!(Plugboard::from_pairs(&[('A', 'B'), ('B', 'C')]).is_err());
+    assert!(Plugboard::from_pairs(&[('A', 'a')]).is_err());
+    assert!(Plugboard::from_pairs(&[('A', '1')]).is_err());
+    assert_eq!(
+        Plugboard::from_pairs(&[]).unwrap().get_connection_count(),
+        0
+    );
+}
+
+/// Tests the structured step observer
+#[test]
+fn test_step_observer() {
+    use enigma_rs::machine::StepEvent;
+    use std::sync::{Arc, Mutex};
+
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "AB")
+        .expect("Machine should be creatable");
+
+    let events: Arc<Mutex<Vec<StepEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    machine.set_step_observer(Box::new(move |event| sink.lock().unwrap().push(event)));
+
+    let output = machine.encrypt_char('A');
+
+    {
+        let events = events.lock().unwrap();
+        // Plugboard, 3 rotors, reflector, 3 rotors, plugboard
+        assert_eq!(events.len(), 9);
+        assert_eq!(events[0].stage, "Steckerbrett (vorwärts)");
+        assert_eq!(events[0].input, 'A');
+        assert_eq!(events[0].output, 'B');
+        assert_eq!(events[1].stage, "Rotor III (vorwärts)");
+        assert_eq!(events[4].stage, "Reflektor B");
+        assert_eq!(Some(events[8].output), output);
+        assert!(events.iter().all(|e| e.rotor_positions == ['A', 'A', 'B']));
+        for pair in events.windows(2) {
+            assert_eq!(pair[0].output, pair[1].input);
+        }
+    }
+
+    machine.clear_step_observer();
+    machine.encrypt_char('A');
+    assert_eq!(events.lock().unwrap().len(), 9);
+}
+
+/// Tests message preparation with spelled digits and X for periods
+#[test]
+fn test_clean_text_procedural() {
+    use enigma_rs::utils::clean_text_procedural;
+
+    assert_eq!(
+        clean_text_procedural("Angriff um 0520. Über Brücke"),
+        "ANGRIFFUMNULLFUENFZWONULLXUEBERBRUECKE"
+    );
+    assert_eq!(clean_text_procedural("Straße 12"), "STRASSEEINSZWO");
+    assert_eq!(clean_text_procedural("a-b!c"), clean_text("a-b!c"));
+}
+
+/// Tests setting rotor position and ring setting from letters
+#[test]
+fn test_rotor_char_setters() {
+    use enigma_rs::rotor::rotors::rotor_i;
+
+    let mut rotor = rotor_i(0, 0).expect("Rotor should be creatable");
+
+    rotor.set_position_char('q').unwrap();
+    rotor.set_ring_setting_char('C').unwrap();
+    assert_eq!(rotor.get_position_char(), 'Q');
+    assert_eq!(rotor.get_ring_setting_char(), 'C');
+
+    assert!(rotor.set_position_char('1').is_err());
+    assert!(rotor.set_ring_setting_char('ä').is_err());
+    assert_eq!(rotor.get_position_char(), 'Q');
+    assert_eq!(rotor.get_ring_setting_char(), 'C');
+}
+
+/// Tests that the index-based fast path matches the traced signal path
+#[test]
+fn test_fast_path_matches_traced_path() {
+    let text = "DASOBERKOMMANDODERWEHRMACHTGIBTBEKANNT".repeat(30);
+
+    for uhr in [None, Some(7)] {
+        let mut fast = factory::create_custom_machine(
+            ["II", "IV", "V"],
+            ['B', 'L', 'A'],
+            ['B', 'U', 'L'],
+            "B",
+            "AV BS CG DL FU HZ IN KM OW RX",
+        )
+        .expect("Machine should be creatable");
+        let mut traced = factory::from_config(&fast.to_config()).unwrap();
+        fast.plugboard.set_uhr_setting(uhr).unwrap();
+        traced.plugboard.set_uhr_setting(uhr).unwrap();
+
+        let expected: String = text
+            .chars()
+            .map(|c| traced.encrypt_char_traced(c).unwrap().output)
+            .collect();
+        assert_eq!(clean_text(&fast.encrypt(&text)), expected);
+    }
+}
+
+/// Tests comparing machines and components directly
+#[test]
+fn test_machine_equality() {
+    let standard = factory::create_standard_machine(['A', 'B', 'C'], ['D', 'E', 'F'], "AB CD")
+        .expect("Machine should be creatable");
+    let custom = factory::create_custom_machine(
+        ["I", "II", "III"],
+        ['A', 'B', 'C'],
+        ['D', 'E', 'F'],
+        "B",
+        "AB CD",
+    )
+    .expect("Machine should be creatable");
+    assert_eq!(standard, custom);
+
+    // Serde round trip yields an identical machine
+    let json = custom.to_config().to_json().unwrap();
+    let restored =
+        factory::from_config(&enigma_rs::machine::MachineConfig::from_json(&json).unwrap())
+            .unwrap();
+    assert_eq!(restored, custom);
+
+    let mut stepped = factory::create_standard_machine(['A', 'B', 'C'], ['D', 'E', 'F'], "AB CD")
+        .expect("Machine should be creatable");
+    stepped.encrypt_char('A');
+    assert_ne!(stepped, standard);
+    assert_ne!(stepped.rotors[2], standard.rotors[2]);
+    assert_eq!(stepped.reflector, standard.reflector);
+    assert_eq!(stepped.plugboard, standard.plugboard);
+}
+
+/// Tests batch encryption with per-message keys and the doubled indicator
+#[test]
+fn test_encrypt_batch_with_message_keys() {
+    use enigma_rs::session::{encrypt_batch, IndicatorProcedure};
+
+    let daily = factory::create_standard_machine(['G', 'K', 'P'], ['B', 'U', 'L'], "AV BS CG")
+        .expect("Machine should be creatable")
+        .to_config();
+    let messages = [
+        ("RTZ", "Angriff im Morgengrauen"),
+        ("QWE", "Keine besonderen Vorkommnisse"),
+    ];
+
+    let plain = encrypt_batch(&daily, &messages, IndicatorProcedure::None)
+        .expect("Batch should be encryptable");
+    assert!(plain.iter().all(|message| message.indicator.is_none()));
+
+    let doubled = encrypt_batch(&daily, &messages, IndicatorProcedure::DoubledKey)
+        .expect("Batch should be encryptable");
+    assert_eq!(doubled.len(), 2);
+
+    for ((message_key, plaintext), (encrypted, without_indicator)) in
+        messages.iter().zip(doubled.iter().zip(plain.iter()))
+    {
+        // The indicator does not influence the message body
+        assert_eq!(encrypted.ciphertext, without_indicator.ciphertext);
+
+        // The receiver recovers the message key at the Grundstellung ...
+        let mut receiver = factory::from_config(&daily).expect("Machine should be creatable");
+        let indicator = encrypted.indicator.as_deref().expect("Indicator expected");
+        assert_eq!(indicator.len(), 6);
+        let doubled_key = receiver.process_exact(indicator);
+        assert_eq!(doubled_key, message_key.repeat(2));
+
+        // ... and decrypts the body at the message key
+        let key: Vec<char> = message_key.chars().collect();
+        receiver.set_rotor_positions(&key);
+        assert_eq!(
+            clean_text(&receiver.decrypt(&encrypted.ciphertext)),
+            clean_text(plaintext)
+        );
+    }
+
+    assert!(encrypt_batch(&daily, &[("AB", "TEXT")], IndicatorProcedure::None).is_err());
+    assert!(encrypt_batch(&daily, &[("A1C", "TEXT")], IndicatorProcedure::None).is_err());
+}
+
+/// Tests that wirings can be shown as letter strings
+#[test]
+fn test_wiring_string() {
+    use enigma_rs::reflector::reflectors::reflector_b;
+    use enigma_rs::rotor::rotors::{rotor_custom, rotor_i};
+
+    let rotor = rotor_i(5, 17).expect("Rotor I should be creatable");
+    assert_eq!(rotor.wiring_string(), "EKMFLGDQVZNTOWYHXUSPAIBRCJ");
+
+    let custom = rotor_custom("bdfhjlcprtxvznyeiwgakmusqo", 'v', 0, 0)
+        .expect("Custom rotor should be creatable");
+    assert_eq!(custom.wiring_string(), "BDFHJLCPRTXVZNYEIWGAKMUSQO");
+
+    let reflector = reflector_b().expect("Reflector B should be creatable");
+    assert_eq!(reflector.wiring_string(), "YRUHQSLDPXNGOKMIEBFZCWVJAT");
+}
+
+/// Tests that a settable reflector stays reciprocal in every position
+#[test]
+fn test_steppable_reflector() {
+    use enigma_rs::reflector::reflectors::reflector_b;
+
+    let mut reflector = reflector_b().expect("Reflector B should be creatable");
+    let initial: Vec<char> = ('A'..='Z').map(|c| reflector.reflect(c)).collect();
+
+    reflector.step();
+    assert_eq!(reflector.position, 1);
+    let stepped: Vec<char> = ('A'..='Z').map(|c| reflector.reflect(c)).collect();
+    assert_ne!(stepped, initial);
+    for c in 'A'..='Z' {
+        let reflected = reflector.reflect(c);
+        assert_ne!(reflected, c);
+        assert_eq!(reflector.reflect(reflected), c);
+    }
+
+    // Position and ring setting cancel out like on a rotor
+    reflector.set_ring_setting(1);
+    let cancelled: Vec<char> = ('A'..='Z').map(|c| reflector.reflect(c)).collect();
+    assert_eq!(cancelled, initial);
+
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    machine.reflector.set_position(7);
+    let encrypted = machine.encrypt("HELLOWORLD");
+    assert_ne!(clean_text(&encrypted), "ILBDAAMTAZ");
+    machine.reset();
+    assert_eq!(clean_text(&machine.decrypt(&encrypted)), "HELLOWORLD");
+}
+
+/// Tests the full substitution alphabet of a frozen machine state
+#[test]
+fn test_current_alphabet() {
+    let mut machine =
+        factory::create_standard_machine(['Q', 'E', 'V'], ['C', 'D', 'E'], "AB CD EF GH")
+            .expect("Machine should be creatable");
+
+    let alphabet = machine.current_alphabet();
+    for (i, &output) in alphabet.iter().enumerate() {
+        let input = (b'A' + i as u8) as char;
+        assert_eq!(Some(output), machine.transform_char(input));
+        assert_ne!(output, input);
+        // Reciprocal: the output letter maps back to the input letter
+        assert_eq!(alphabet[(output as u8 - b'A') as usize], input);
+    }
+
+    // The alphabet does not step the rotors
+    assert_eq!(machine.get_rotor_positions(), ['Q', 'E', 'V']);
+
+    let first = machine.encrypt_char('X');
+    let stepped = machine.current_alphabet();
+    assert_eq!(Some(stepped[23]), first);
+}
+
+/// Tests the step counter and fast-forwarding
+#[test]
+fn test_step_count_and_advance() {
+    let mut machine = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "AB")
+        .expect("Machine should be creatable");
+    assert_eq!(machine.get_step_count(), 0);
+
+    let ciphertext = machine.encrypt("HELLOWORLD");
+    assert_eq!(machine.get_step_count(), 10);
+    // Non-letters do not count
+    machine.encrypt_char('1');
+    assert_eq!(machine.get_step_count(), 10);
+
+    machine.reset();
+    assert_eq!(machine.get_step_count(), 0);
+
+    // Skipping the first five letters continues exactly where they ended
+    machine.advance(5);
+    assert_eq!(machine.get_step_count(), 5);
+    let tail = machine.process_exact("WORLD");
+    assert_eq!(tail, clean_text(&ciphertext)[5..]);
+    assert_eq!(machine.get_step_count(), 10);
+}
+
+/// Tests that the public plugboard API always keeps the wiring reciprocal
+#[test]
+fn test_plugboard_connections_involution() {
+    use enigma_rs::plugboard::Plugboard;
+
+    let mut plugboard = Plugboard::from_string("QA CD").expect("Plugboard should be valid");
+    assert_eq!(plugboard.connections(), vec![('A', 'Q'), ('C', 'D')]);
+    assert!(plugboard.is_valid_involution());
+
+    // Every mutating call either succeeds symmetrically or is rejected
+    let _ = plugboard.add_connection('A', 'B');
+    let _ = plugboard.add_connection('E', 'E');
+    let _ = plugboard.toggle_connection('C', 'E');
+    assert!(plugboard.is_valid_involution());
+    plugboard.toggle_connection('C', 'D').unwrap();
+    plugboard.add_connection('C', 'Z').unwrap();
+    plugboard.remove_connection('Q').unwrap();
+    assert!(plugboard.is_valid_involution());
+    assert_eq!(plugboard.connections(), vec![('C', 'Z')]);
+
+    plugboard.clear();
+    assert!(plugboard.connections().is_empty());
+    assert!(plugboard.is_valid_involution());
+}
+
+/// Tests looking up the plug partner of a letter
+#[test]
+fn test_plugboard_partner_of() {
+    use enigma_rs::plugboard::Plugboard;
+
+    let plugboard = Plugboard::from_string("AB CZ").expect("Plugboard should be valid");
+    assert_eq!(plugboard.partner_of('A'), Some('B'));
+    assert_eq!(plugboard.partner_of('b'), Some('A'));
+    assert_eq!(plugboard.partner_of('Z'), Some('C'));
+    assert_eq!(plugboard.partner_of('D'), None);
+    assert_eq!(plugboard.partner_of('1'), None);
+}
+
+/// Tests the index-based encryption API
+#[test]
+fn test_encrypt_indices() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    let plaintext: Vec<u8> = "HELLOWORLD".bytes().map(|b| b - b'A').collect();
+    let ciphertext = machine.encrypt_indices(&plaintext);
+    let letters: String = ciphertext.iter().map(|&i| (b'A' + i) as char).collect();
+    assert_eq!(letters, "ILBDAAMTAZ");
+
+    machine.reset();
+    assert_eq!(machine.encrypt_indices(&ciphertext), plaintext);
+    assert!(machine.encrypt_indices(&[]).is_empty());
+}
+
+/// Tests explicit notch letters for custom rotors
+#[test]
+fn test_custom_notch_letters() {
+    use enigma_rs::machine::EnigmaMachine;
+    use enigma_rs::rotor::rotors;
+
+    let rotor = rotors::rotor_i(0, 0)
+        .expect("Rotor should be creatable")
+        .with_notch_char('c')
+        .expect("Lowercase notch should be accepted");
+    assert_eq!(rotor.notches, vec![2]);
+    assert!(rotors::rotor_i(0, 0).unwrap().with_notch_char('1').is_err());
+
+    // A historical rotor with a changed notch is saved as a custom rotor
+    assert_eq!(rotor.name, "CUSTOM:EKMFLGDQVZNTOWYHXUSPAIBRCJ@C");
+    let unchanged = rotors::rotor_i(0, 0).unwrap().with_notch_char('Q').unwrap();
+    assert_eq!(unchanged.name, "I");
+    assert!(rotors::rotor_beta(0, 0)
+        .unwrap()
+        .with_notch_char('A')
+        .is_err());
+
+    let machine = EnigmaMachine::new(
+        vec![
+            rotor,
+            rotors::rotor_ii(0, 0).unwrap(),
+            rotors::rotor_iii(0, 0).unwrap(),
+        ],
+        enigma_rs::reflector::reflectors::reflector_b().unwrap(),
+        Default::default(),
+    );
+    let restored = factory::from_config(&machine.to_config()).unwrap();
+    assert_eq!(restored, machine);
+    let standard = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    assert_ne!(machine.configuration_hash(), standard.configuration_hash());
+
+    // Custom rotors keep the notch in their name for saved configurations
+    let custom = rotors::rotor_custom("EKMFLGDQVZNTOWYHXUSPAIBRCJ", 'Q', 0, 0)
+        .unwrap()
+        .with_notch_char('Z')
+        .unwrap();
+    assert_eq!(custom.name, "CUSTOM:EKMFLGDQVZNTOWYHXUSPAIBRCJ@Z");
+
+    // The notch decides when the middle rotor steps
+    let mut machine = factory::create_machine_with_wirings(
+        [
+            ("EKMFLGDQVZNTOWYHXUSPAIBRCJ", 'Q'),
+            ("AJDKSIRUXBLHWTMCQGZNPYFVOE", 'E'),
+            ("BDFHJLCPRTXVZNYEIWGAKMUSQO", 'B'),
+        ],
+        ['A', 'A', 'A'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    )
+    .expect("Custom notch should be valid");
+    machine.process_exact("AA");
+    assert_eq!(machine.get_rotor_positions(), vec!['A', 'B', 'C']);
+
+    let err = factory::create_machine_with_wirings(
+        [
+            ("EKMFLGDQVZNTOWYHXUSPAIBRCJ", 'Q'),
+            ("AJDKSIRUXBLHWTMCQGZNPYFVOE", '?'),
+            ("BDFHJLCPRTXVZNYEIWGAKMUSQO", 'V'),
+        ],
+        ['A', 'A', 'A'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    )
+    .unwrap_err();
+    assert!(err.contains("Rotor 2"));
+}
+
+/// Tests the pre-step preview of the substitution
+#[test]
+fn test_peek_char() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'Z'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    let positions = machine.get_rotor_positions();
+
+    let peeked: String = ('A'..='Z').map(|c| machine.peek_char(c)).collect();
+    assert_eq!(machine.get_rotor_positions(), positions);
+    assert_eq!(machine.peek_char('h'), machine.peek_char('H'));
+    assert_eq!(machine.peek_char('1'), '1');
+
+    // Same substitution as transform_char; non-letters are never enciphered
+    for c in ('A'..='Z').chain(['é', '1', ' ']) {
+        assert_eq!(machine.peek_char(c), machine.transform_char(c).unwrap_or(c));
+    }
+    assert_eq!(machine.peek_char('é'), 'é');
+
+    // Peeking at AAZ shows what a keypress at AAY would produce
+    let mut earlier = factory::create_standard_machine(['A', 'A', 'Y'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    for c in 'A'..='Z' {
+        earlier.set_rotor_positions(['A', 'A', 'Y']);
+        assert_eq!(
+            earlier.encrypt_char(c),
+            peeked.chars().nth((c as u8 - b'A') as usize)
+        );
+    }
+
+    // Peeking does not change what the next keypress produces
+    let mut untouched = factory::create_standard_machine(['A', 'A', 'Z'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    assert_eq!(machine.encrypt_char('A'), untouched.encrypt_char('A'));
+}
+
+/// Tests parsing daily keys from key sheet lines
+#[test]
+fn test_from_keysheet_line() {
+    use enigma_rs::machine::MachineConfig;
+
+    let config =
+        MachineConfig::from_keysheet_line("III I II | 14 09 22 | AB CD EF GH IJ KL MN OP QR ST")
+            .expect("Key sheet line should be valid");
+    assert_eq!(config.rotor_types, vec!["III", "I", "II"]);
+    assert_eq!(config.ring_settings, vec!['N', 'I', 'V']);
+    assert_eq!(config.rotor_positions, vec!['A', 'A', 'A']);
+    assert_eq!(config.reflector_type, "B");
+    assert_eq!(config.plugboard, "AB CD EF GH IJ KL MN OP QR ST");
+    assert!(factory::from_config(&config).is_ok());
+
+    // Lines as printed by genkey carry a leading day column
+    let with_day = MachineConfig::from_keysheet_line(
+        "  7 | III I II     | NIV | ab cd ef gh ij kl mn op qr st",
+    )
+    .expect("Day column should be accepted");
+    assert_eq!(with_day, config);
+
+    // Empty plugboard
+    assert!(MachineConfig::from_keysheet_line("I II III | 01 01 01 |").is_ok());
+
+    // Malformed lines
+    assert!(MachineConfig::from_keysheet_line("I II III | 01 01 01").is_err());
+    assert!(MachineConfig::from_keysheet_line("I II IX | 01 01 01 | AB").is_err());
+    assert!(MachineConfig::from_keysheet_line("I II III | 01 01 | AB").is_err());
+    assert!(MachineConfig::from_keysheet_line("I II III | 01 01 27 | AB").is_err());
+    assert!(MachineConfig::from_keysheet_line("I II III | 01 01 01 | AB AC").is_err());
+    assert!(MachineConfig::from_keysheet_line("X | I II III | 01 01 01 | AB").is_err());
+    assert!(MachineConfig::from_keysheet_line(" | 01 01 01 | AB").is_err());
+
+    // Ring settings are required on key sheets, unlike on the command line
+    assert!(MachineConfig::from_keysheet_line("I II III |  | AB").is_err());
+    assert!(MachineConfig::from_keysheet_line("3 | I II III | | AB").is_err());
+    assert_eq!(
+        MachineConfig::from_settings("I II III", "", "", "B", "")
+            .unwrap()
+            .ring_settings,
+        ['A'; 3]
+    );
+}
+
+/// Tests guessing an unknown reflector from English-likeness
+#[test]
+fn test_try_reflectors() {
+    use enigma_rs::crack::{try_reflectors, try_reflectors_allowing_duplicates};
+
+    let plaintext = "THEWEATHERREPORTFORTODAYISCLEARSKIESWITHLIGHTWINDSFROMTHEWEST";
+    let mut machine = factory::create_custom_machine(
+        ["II", "IV", "V"],
+        ['B', 'L', 'A'],
+        ['B', 'U', 'L'],
+        "C",
+        "AV BS CG DL FU HZ IN KM OW RX",
+    )
+    .expect("Machine should be creatable");
+    let mut config = machine.to_config();
+    let ciphertext = machine.encrypt(plaintext);
+
+    config.reflector_type = "A".to_string();
+    let results = try_reflectors(&config, &ciphertext);
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].0, "C");
+    assert_eq!(results[0].1, plaintext);
+    assert!(results.windows(2).all(|pair| pair[0].2 <= pair[1].2));
+
+    // Duplicate rotors are only accepted by the permissive variant
+    let mut duplicates = factory::create_custom_machine_allowing_duplicates(
+        ["II", "II", "V"],
+        ['B', 'L', 'A'],
+        ['B', 'U', 'L'],
+        "B",
+        "",
+    )
+    .unwrap();
+    let mut duplicate_config = duplicates.to_config();
+    let ciphertext = duplicates.encrypt(plaintext);
+    duplicate_config.reflector_type = "A".to_string();
+    assert!(try_reflectors(&duplicate_config, &ciphertext).is_empty());
+    let results = try_reflectors_allowing_duplicates(&duplicate_config, &ciphertext);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].0, "B");
+}
+
+/// Tests deep copies of machines for independent message streams
+#[test]
+fn test_clone_at_position() {
+    let daily = factory::create_custom_machine(
+        ["IV", "II", "V"],
+        ['A', 'A', 'A'],
+        ['G', 'M', 'Y'],
+        "B",
+        "DN GR IS KC QX TM PV HY FW BJ",
+    )
+    .expect("Machine should be creatable");
+
+    let mut copy = daily.clone();
+    assert_eq!(copy, daily);
+    copy.encrypt("ADVANCE");
+    copy.plugboard.clear();
+    assert_eq!(daily.get_rotor_positions(), vec!['A', 'A', 'A']);
+    assert_ne!(daily.plugboard, copy.plugboard);
+
+    // Each message key gets its own machine, encrypted on its own thread
+    let keys = [['R', 'T', 'Z'], ['W', 'X', 'C'], ['K', 'D', 'B']];
+    let handles: Vec<_> = keys
+        .iter()
+        .map(|&key| {
+            let mut machine = daily.clone_at_position(key);
+            std::thread::spawn(move || machine.process_exact("ANGRIFFIMMORGENGRAUEN"))
+        })
+        .collect();
+    let parallel: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    for (key, ciphertext) in keys.iter().zip(&parallel) {
+        let mut sequential = daily.clone();
+        sequential.set_rotor_positions(key);
+        assert_eq!(
+            &sequential.process_exact("ANGRIFFIMMORGENGRAUEN"),
+            ciphertext
+        );
+    }
+
+    // reset returns to the message key
+    let mut message = daily.clone_at_position(['Q', 'E', 'V']);
+    message.process_exact("HELLO");
+    message.reset();
+    assert_eq!(message.get_rotor_positions(), vec!['Q', 'E', 'V']);
+    assert_eq!(message.get_step_count(), 0);
+}
+
+/// Tests the rotor type accessor
+#[test]
+fn test_get_rotor_types() {
+    let machine = factory::create_custom_machine(
+        ["III", "I", "VIII"],
+        ['A', 'A', 'A'],
+        ['A', 'A', 'A'],
+        "B",
+        "",
+    )
+    .expect("Machine should be creatable");
+    assert_eq!(machine.get_rotor_types(), vec!["III", "I", "VIII"]);
+    assert_eq!(machine.get_rotor_types(), machine.to_config().rotor_types);
+
+    let m4 = factory::create_m4_machine(
+        ["Beta", "II", "IV", "I"],
+        ['A', 'A', 'A', 'A'],
+        ['A', 'A', 'A', 'A'],
+        "B-Thin",
+        "",
+    )
+    .expect("M4 should be creatable");
+    assert_eq!(m4.get_rotor_types(), vec!["Beta", "II", "IV", "I"]);
+}
+
+/// Tests swapping rotors and reflector in place
+#[test]
+fn test_reconfigure() {
+    let mut machine = factory::create_custom_machine(
+        ["I", "II", "III"],
+        ['A', 'B', 'C'],
+        ['D', 'E', 'F'],
+        "B",
+        "AB CD",
+    )
+    .expect("Machine should be creatable");
+    machine.process_exact("HELLO");
+
+    machine
+        .reconfigure(&["IV", "V", "I"], "C")
+        .expect("Valid rotors should be accepted");
+    assert_eq!(machine.get_rotor_types(), vec!["IV", "V", "I"]);
+    assert_eq!(machine.reflector.name, "C");
+    assert_eq!(machine.get_rotor_positions(), vec!['A', 'B', 'H']);
+    assert_eq!(machine.get_ring_settings(), vec!['D', 'E', 'F']);
+    assert_eq!(machine.plugboard.get_connections_string(), "AB CD");
+
+    // Equivalent to a freshly built machine
+    let mut fresh = factory::create_custom_machine(
+        ["IV", "V", "I"],
+        ['A', 'B', 'H'],
+        ['D', 'E', 'F'],
+        "C",
+        "AB CD",
+    )
+    .unwrap();
+    assert_eq!(
+        machine.process_exact("TESTTEXT"),
+        fresh.process_exact("TESTTEXT")
+    );
+    machine.reset();
+    assert_eq!(machine.get_rotor_positions(), vec!['A', 'B', 'C']);
+
+    // Switching to an M4 keeps the three right rotors' settings
+    machine
+        .reconfigure(&["Gamma", "IV", "V", "I"], "C-Thin")
+        .expect("M4 layout should be accepted");
+    assert_eq!(machine.get_rotor_positions(), vec!['A', 'A', 'B', 'C']);
+    assert_eq!(machine.get_ring_settings(), vec!['A', 'D', 'E', 'F']);
+
+    // Invalid combinations leave the machine untouched
+    let before = machine.clone();
+    assert!(machine.reconfigure(&["I", "I", "II"], "B").is_err());
+    assert!(machine.reconfigure(&["I", "II", "III"], "X").is_err());
+    assert_eq!(machine, before);
+
+    // Duplicate rotors need the permissive variant
+    machine
+        .reconfigure_allowing_duplicates(&["I", "I", "II"], "B")
+        .expect("Duplicates should be allowed explicitly");
+    assert_eq!(machine.get_rotor_types(), ["I", "I", "II"]);
+}
+
+/// Tests the letter histogram and its ASCII chart
+#[test]
+fn test_letter_histogram() {
+    use enigma_rs::utils::{format_histogram, letter_histogram};
+
+    let histogram = letter_histogram("Hello, World!");
+    assert_eq!(histogram[(b'L' - b'A') as usize], 3);
+    assert_eq!(histogram[(b'O' - b'A') as usize], 2);
+    assert_eq!(histogram[(b'H' - b'A') as usize], 1);
+    assert_eq!(histogram.iter().sum::<usize>(), 10);
+    assert_eq!(letter_histogram(""), [0; 26]);
+
+    let chart = format_histogram(&histogram, 6);
+    let lines: Vec<&str> = chart.lines().collect();
+    assert_eq!(lines.len(), 26);
+    assert_eq!(lines[11], "L | ###### 3");
+    assert_eq!(lines[14], "O | ####   2");
+    assert_eq!(lines[0], "A |        0");
+
+    // Ciphertext of repetitive text is spread over many letters
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    let ciphertext = machine.encrypt(&"E".repeat(260));
+    assert!(
+        letter_histogram(&ciphertext)
+            .iter()
+            .filter(|&&n| n > 0)
+            .count()
+            > 20
+    );
+}
+
+/// Tests whitespace handling and error messages of plugboard strings
+#[test]
+fn test_plugboard_string_whitespace() {
+    use enigma_rs::plugboard::Plugboard;
+
+    let expected = Plugboard::from_string("AB CD EF").expect("Plugboard should be valid");
+
+    // Newline-separated pairs as produced by a multiline text field
+    let multiline =
+        Plugboard::from_string("AB\nCD\r\n\tEF\n").expect("Newlines should separate pairs");
+    assert_eq!(multiline, expected);
+
+    // Leading and trailing whitespace
+    assert_eq!(Plugboard::from_string(" AB CD EF ").unwrap(), expected);
+    assert_eq!(Plugboard::from_string("AB CD EF\n").unwrap(), expected);
+    assert_eq!(
+        Plugboard::from_string(" \n\t ")
+            .unwrap()
+            .get_connection_count(),
+        0
+    );
+
+    // Same behaviour through the factory
+    let mut from_multiline = factory::create_standard_machine(['A'; 3], ['A'; 3], "AB\nCD\nEF")
+        .expect("Machine should be creatable");
+    let mut from_line = factory::create_standard_machine(['A'; 3], ['A'; 3], "AB CD EF ")
+        .expect("Machine should be creatable");
+    assert_eq!(from_multiline.encrypt("HELLO"), from_line.encrypt("HELLO"));
+
+    // Runs of letters without separator
+    let odd = Plugboard::from_string("AB CDE").unwrap_err();
+    assert!(odd.contains("'CDE'") && odd.contains("ungerade"));
+    let joined = Plugboard::from_string("ABCD").unwrap_err();
+    assert!(joined.contains("'ABCD'") && joined.contains("Leerzeichen"));
+    assert!(Plugboard::from_string("A").is_err());
+    assert!(Plugboard::from_string("A1")
+        .unwrap_err()
+        .contains("Buchstaben"));
+}
+
+/// Tests the human-readable configuration diff
+#[test]
+fn test_config_diff() {
+    let old = factory::create_standard_machine(['A', 'B', 'C'], ['A', 'A', 'A'], "AB CD EF")
+        .expect("Machine should be creatable")
+        .to_config();
+
+    assert!(old.diff(&old).is_empty());
+
+    let mut new = old.clone();
+    new.rotor_types[2] = "IV".to_string();
+    new.ring_settings[1] = 'B';
+    new.reflector_type = "C".to_string();
+    new.plugboard = "fe dc GH".to_string();
+
+    assert_eq!(
+        old.diff(&new),
+        vec![
+            "Rotoren: I II III → I II IV",
+            "Ringstellungen: A A A → A B A",
+            "Reflektor: B → C",
+            "Steckerbrett: hinzugefügt GH, entfernt AB",
+        ]
+    );
+
+    let mut moved = old.clone();
+    moved.rotor_positions = vec!['A', 'B', 'D'];
+    assert_eq!(old.diff(&moved), vec!["Positionen: A B C → A B D"]);
+
+    let mut uhr = old.clone();
+    uhr.uhr_setting = Some(12);
+    assert_eq!(old.diff(&uhr), vec!["Uhr: keine → 12"]);
+}
+
+/// Tests the involution check and custom reflector wirings
+#[test]
+fn test_reflector_involution() {
+    use enigma_rs::machine::MachineConfig;
+    use enigma_rs::reflector::{reflectors, Reflector, ReflectorType};
+
+    for (name, create) in reflectors::available_reflectors() {
+        assert!(create().unwrap().is_involution(), "Reflector {}", name);
+    }
+
+    // A rotation is a permutation but not its own inverse
+    assert!(Reflector::new("BCDEFGHIJKLMNOPQRSTUVWXYZA", "Shift")
+        .unwrap_err()
+        .contains("Involution"));
+
+    // Custom wirings through the type parser
+    let wiring = reflectors::reflector_b().unwrap().wiring_string();
+    let spec = format!("CUSTOM:{}", wiring.to_lowercase());
+    let parsed: ReflectorType = spec.parse().expect("Reflector B wiring should be valid");
+    assert_eq!(parsed, ReflectorType::Custom(wiring.clone()));
+    assert_eq!(parsed.to_string(), format!("CUSTOM:{}", wiring));
+
+    assert!("CUSTOM:BCDEFGHIJKLMNOPQRSTUVWXYZA"
+        .parse::<ReflectorType>()
+        .is_err());
+    assert!("CUSTOM:ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+        .parse::<ReflectorType>()
+        .unwrap_err()
+        .contains("Fixpunkt"));
+    assert!("CUSTOM:YRUHQ".parse::<ReflectorType>().is_err());
+
+    // A custom copy of reflector B encrypts like B and survives a config round trip
+    let config = MachineConfig {
+        rotor_types: vec!["I".into(), "II".into(), "III".into()],
+        rotor_positions: vec!['A'; 3],
+        ring_settings: vec!['A'; 3],
+        reflector_type: format!("CUSTOM:{}", wiring),
+        plugboard: String::new(),
+        stepping_model: Default::default(),
+        uhr_setting: None,
+    };
+    let mut custom = factory::from_config(&config).expect("Custom reflector should be usable");
+    let mut standard = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    assert_eq!(custom.encrypt("HELLOWORLD"), standard.encrypt("HELLOWORLD"));
+    assert_eq!(custom.to_config().reflector_type, config.reflector_type);
+}
+
+/// Tests the turnover prediction around the double step
+#[test]
+fn test_will_step_middle_and_left() {
+    use enigma_rs::machine::SteppingModel;
+
+    // Rotor III turns over at V, rotor II at E
+    let mut machine = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    let expected = [
+        // (positions before the keypress, middle steps, left steps)
+        ("ADU", false, false),
+        ("ADV", true, false),
+        ("AEW", true, true), // double step
+        ("BFX", false, false),
+        ("BFY", false, false),
+    ];
+    for (positions, middle, left) in expected {
+        assert_eq!(
+            machine.get_rotor_positions().iter().collect::<String>(),
+            positions
+        );
+        assert_eq!(
+            machine.will_step_middle(),
+            middle,
+            "middle at {}",
+            positions
+        );
+        assert_eq!(machine.will_step_left(), left, "left at {}", positions);
+
+        let before = machine.get_rotor_positions();
+        machine.encrypt_char('A');
+        let after = machine.get_rotor_positions();
+        assert_eq!(before[1] != after[1], middle);
+        assert_eq!(before[0] != after[0], left);
+    }
+
+    // Without pawls there is no double step
+    machine.set_stepping_model(SteppingModel::Gear);
+    machine.set_rotor_positions(['A', 'E', 'W']);
+    assert!(!machine.will_step_middle());
+    assert!(!machine.will_step_left());
+
+    // The M4's Greek rotor never steps; the left rotor is the third from the right
+    let mut m4 = factory::create_m4_machine(
+        ["Beta", "II", "IV", "I"],
+        ['A', 'A', 'J', 'Q'],
+        ['A', 'A', 'A', 'A'],
+        "B-Thin",
+        "",
+    )
+    .expect("M4 should be creatable");
+    assert!(m4.will_step_middle());
+    assert!(m4.will_step_left());
+    m4.encrypt_char('A');
+    assert_eq!(m4.get_rotor_positions(), vec!['A', 'B', 'K', 'R']);
+}
+
+/// Tests that documents keep their line and paragraph structure
+#[test]
+fn test_encrypt_document() {
+    let document = "Dear Sir,\n\nThe convoy leaves at dawn.\r\nRoute: north.\n\n\nEnd\n";
+    let mut machine = factory::create_standard_machine(['Q', 'E', 'V'], ['A', 'A', 'A'], "AB CD")
+        .expect("Machine should be creatable");
+
+    let encrypted = machine.encrypt_document(document);
+    assert_ne!(encrypted, document);
+
+    // Same line layout, including CRLF, empty lines and the trailing newline
+    assert_eq!(encrypted.len(), document.len());
+    assert_eq!(encrypted.split('\n').count(), document.split('\n').count());
+    for (cipher_line, plain_line) in encrypted.split('\n').zip(document.split('\n')) {
+        assert_eq!(cipher_line.len(), plain_line.len());
+        assert_eq!(cipher_line.ends_with('\r'), plain_line.ends_with('\r'));
+    }
+
+    // Line breaks do not step the rotors: the letters match a single-line run
+    let letters = clean_text(document).len();
+    assert_eq!(machine.get_step_count(), letters);
+    let mut single =
+        factory::create_standard_machine(['Q', 'E', 'V'], ['A', 'A', 'A'], "AB CD").unwrap();
+    assert_eq!(
+        clean_text(&encrypted),
+        single.process_exact(&clean_text(document))
+    );
+
+    // Decryption is symmetric
+    machine.reset();
+    assert_eq!(machine.encrypt_document(&encrypted), document);
+    assert_eq!(machine.encrypt_document(""), "");
+}
+
+/// Tests that seeded random generators are reproducible
+#[test]
+fn test_seeded_random_generators() {
+    use enigma_rs::plugboard::Plugboard;
+    use enigma_rs::utils::{
+        generate_random_plugboard_cables_with, generate_random_plugboard_with,
+        generate_random_settings_with,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let generate = |seed: u64| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (
+            generate_random_plugboard_with(&mut rng),
+            generate_random_plugboard_cables_with(10, &mut rng),
+            generate_random_settings_with(3, &mut rng),
+        )
+    };
+
+    let first = generate(42);
+    assert_eq!(first, generate(42));
+    assert_ne!(first, generate(43));
+
+    let (plugboard, cables, settings) = first;
+    assert!(Plugboard::from_string(&plugboard).is_ok());
+    assert_eq!(
+        Plugboard::from_string(&cables)
+            .unwrap()
+            .get_connection_count(),
+        10
+    );
+    assert_eq!(settings.len(), 3);
+    assert!(settings.iter().all(|c| c.is_ascii_uppercase()));
+}
+
+/// Tests that timed encryption matches plain encryption
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_encrypt_timed() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    let text = "TIMEDENCRYPTION".repeat(100);
+
+    let (timed, elapsed) = machine.encrypt_timed(&text);
+    machine.reset();
+    assert_eq!(timed, machine.encrypt(&text));
+    assert!(elapsed.as_secs() < 10);
+    assert_eq!(machine.get_step_count(), text.len());
+}
+
+/// Tests the 1-based numeric accessors for ring setting and position
+#[test]
+fn test_ring_setting_and_position_numbers() {
+    use enigma_rs::machine::MachineConfig;
+    use enigma_rs::rotor::rotors;
+
+    let rotor = rotors::rotor_i(0, 25).expect("Rotor should be creatable");
+    assert_eq!(rotor.get_ring_setting_number(), 1);
+    assert_eq!(rotor.get_position_number(), 26);
+
+    // Key sheet numbers are 1-based and round-trip through the accessor
+    let config = MachineConfig::from_keysheet_line("I II III | 01 14 26 |").unwrap();
+    let machine = factory::from_config(&config).unwrap();
+    let numbers: Vec<u8> = machine
+        .rotors
+        .iter()
+        .map(|r| r.get_ring_setting_number())
+        .collect();
+    assert_eq!(numbers, vec![1, 14, 26]);
+    assert_eq!(machine.get_ring_settings(), vec!['A', 'N', 'Z']);
+}
+
+/// Tests a machine built from boxed trait-object scramblers, including a custom wheel
+#[test]
+fn test_machine_with_boxed_scramblers() {
+    use enigma_rs::machine::EnigmaMachine;
+    use enigma_rs::plugboard::Plugboard;
+    use enigma_rs::reflector::reflectors;
+    use enigma_rs::rotor::{rotors, Scrambler};
+
+    /// Stationary wheel that passes every letter through unchanged
+    #[derive(Debug)]
+    struct Identity;
+
+    impl Scrambler for Identity {
+        fn forward(&self, input: char) -> char {
+            input
+        }
+
+        fn backward(&self, input: char) -> char {
+            input
+        }
+
+        fn step(&mut self) -> bool {
+            false
+        }
+
+        fn is_stationary(&self) -> bool {
+            true
+        }
+    }
+
+    let scramblers: Vec<Box<dyn Scrambler>> = vec![
+        Box::new(Identity),
+        Box::new(rotors::rotor_i(0, 0).unwrap()),
+        Box::new(rotors::rotor_ii(0, 0).unwrap()),
+        Box::new(rotors::rotor_iii(0, 0).unwrap()),
+    ];
+    let mut mixed = EnigmaMachine::new(
+        scramblers,
+        reflectors::reflector_b().unwrap(),
+        Plugboard::new(),
+    );
+    let mut standard = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+
+    // Enough letters for the middle rotor to turn over and double-step
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG".repeat(20);
+    assert_eq!(mixed.encrypt(&text), standard.encrypt(&text));
+    assert_eq!(
+        mixed.get_rotor_positions()[1..],
+        standard.get_rotor_positions()
+    );
+    assert_eq!(mixed.get_rotor_types()[0], "Scrambler");
+
+    mixed.reset();
+    assert_eq!(mixed.get_rotor_positions(), vec!['A'; 4]);
+}
+
+/// Tests the complete message format with doubled-key indicator and Kenngruppe
+#[test]
+fn test_encode_and_decode_message() {
+    use enigma_rs::procedure::{decode_message, encode_message, FormattedMessage};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let daily = factory::create_standard_machine(['G', 'K', 'P'], ['B', 'U', 'L'], "AV BS CG")
+        .expect("Machine should be creatable")
+        .to_config();
+    let mut rng = StdRng::seed_from_u64(1940);
+
+    let message = encode_message(&daily, "ktm", "Angriff im Morgengrauen", &mut rng)
+        .expect("Message should be encodable");
+    assert_eq!(message.indicator.len(), 6);
+    assert_eq!(message.kenngruppe.len(), 5);
+    assert_eq!(message.discriminant(), "KTM");
+    assert!(message
+        .body
+        .split(' ')
+        .all(|group| !group.is_empty() && group.len() <= 5));
+
+    // The transmitted text parses back into the same message
+    let received: FormattedMessage = message.to_string().parse().expect("Message should parse");
+    assert_eq!(received, message);
+    assert_eq!(
+        decode_message(&daily, &received).expect("Message should decode"),
+        "ANGRIFFIMMORGENGRAUEN"
+    );
+
+    // A different Grundstellung yields two different key halves
+    let mut wrong = daily.clone();
+    wrong.rotor_positions = vec!['A', 'A', 'A'];
+    assert!(decode_message(&wrong, &received).is_err());
+
+    assert!("".parse::<FormattedMessage>().is_err());
+
+    // The Kenngruppe follows the indicator unencrypted and is required
+    let transmitted = message.to_string();
+    let groups: Vec<&str> = transmitted.split(' ').collect();
+    assert_eq!(groups[0], message.indicator);
+    assert_eq!(groups[1], message.kenngruppe);
+    assert!(groups[1].ends_with("KTM"));
+    assert!(message.indicator.parse::<FormattedMessage>().is_err());
+    assert!(format!("{} KTM", message.indicator)
+        .parse::<FormattedMessage>()
+        .is_err());
+    assert!(encode_message(&daily, "KT", "TEXT", &mut rng).is_err());
+    assert!(encode_message(&daily, "K1M", "TEXT", &mut rng).is_err());
+}
+
+/// Tests that every listed component name parses back into its type
+#[test]
+fn test_available_component_names() {
+    use enigma_rs::reflector::{reflectors, ReflectorType};
+    use enigma_rs::rotor::{rotors, RotorType};
+
+    let rotor_names = rotors::available_rotor_names();
+    assert_eq!(rotor_names.len(), rotors::available_rotors().len());
+    assert_eq!(rotor_names[..3], ["I", "II", "III"]);
+    for name in &rotor_names {
+        assert!(name.parse::<RotorType>().is_ok(), "Rotor {}", name);
+    }
+
+    let reflector_names = reflectors::available_reflector_names();
+    assert!(reflector_names.contains(&"B-Thin"));
+    for name in &reflector_names {
+        assert!(name.parse::<ReflectorType>().is_ok(), "Reflector {}", name);
+    }
+}
+
+/// Tests that a reset machine is indistinguishable from a freshly built one
+#[test]
+fn test_reset_matches_cold_start() {
+    use std::sync::{Arc, Mutex};
+
+    let config = factory::create_custom_machine(
+        ["VI", "II", "VIII"],
+        ['Q', 'Z', 'H'],
+        ['C', 'X', 'M'],
+        "C",
+        "AQ BW ER TZ",
+    )
+    .expect("Machine should be creatable")
+    .to_config();
+
+    for count in [1, 25, 26, 677, 2000] {
+        let mut used = factory::from_config(&config).expect("Machine should be creatable");
+        let events = Arc::new(Mutex::new(0usize));
+        let counter = Arc::clone(&events);
+        used.set_step_observer(Box::new(move |_| *counter.lock().unwrap() += 1));
+        let text: String = "ENIGMA".chars().cycle().take(count).collect();
+        used.process_exact(&text);
+        used.clear_step_observer();
+        used.reset();
+
+        let mut fresh = factory::from_config(&config).expect("Machine should be creatable");
+        assert_eq!(used, fresh, "after {} letters", count);
+        assert_eq!(used.get_rotor_positions(), fresh.get_rotor_positions());
+        assert_eq!(used.current_alphabet(), fresh.current_alphabet());
+        assert_eq!(used.get_step_count(), 0);
+        assert_eq!(used.next_step_turnovers(), fresh.next_step_turnovers());
+
+        let text = "COLDSTARTCHECK".repeat(60);
+        assert_eq!(used.process_exact(&text), fresh.process_exact(&text));
+        assert!(*events.lock().unwrap() > 0);
+    }
+}
+
+/// Tests that a scoped encryption leaves the machine where it was
+#[test]
+fn test_scoped_encryption_restores_positions() {
+    let mut machine = factory::create_standard_machine(['Q', 'E', 'V'], ['A'; 3], "AB CD")
+        .expect("Machine should be creatable");
+    machine.process_exact("HELLO");
+    let positions = machine.get_rotor_positions();
+    let mut untouched = machine.clone();
+
+    let probe = {
+        let mut guard = machine.scoped();
+        let probe = guard.encrypt("OUT OF BAND PROBE");
+        assert_ne!(guard.get_rotor_positions(), positions);
+        probe
+    };
+    assert_eq!(machine.get_rotor_positions(), positions);
+    assert_eq!(machine.get_step_count(), 5);
+
+    // The probe behaves exactly like encrypting from the saved positions,
+    // and the machine continues as if the probe never happened
+    assert_eq!(machine.clone().encrypt("OUT OF BAND PROBE"), probe);
+    assert_eq!(
+        machine.process_exact("WORLD"),
+        untouched.process_exact("WORLD")
+    );
+}
+
+/// Tests the plugboard as a permutation array
+#[test]
+fn test_plugboard_as_permutation() {
+    use enigma_rs::plugboard::Plugboard;
+
+    let identity: Vec<usize> = (0..26).collect();
+    assert_eq!(Plugboard::new().as_permutation().to_vec(), identity);
+
+    let plugboard = Plugboard::from_string("AB CZ").unwrap();
+    let permutation = plugboard.as_permutation();
+    assert_eq!(permutation[0], 1);
+    assert_eq!(permutation[2], 25);
+    assert_eq!(permutation[4], 4);
+    assert!((0..26).all(|i| permutation[permutation[i]] == i));
+
+    // With the Uhr the way back is the inverse permutation
+    let mut uhr = Plugboard::from_string("AB CD EF GH IJ KL MN OP QR ST").unwrap();
+    uhr.set_uhr_setting(Some(27)).unwrap();
+    let permutation = uhr.as_permutation();
+    let mut sorted = permutation.to_vec();
+    sorted.sort_unstable();
+    assert_eq!(sorted, identity);
+    for (i, &target) in permutation.iter().enumerate() {
+        assert_eq!(uhr.process_backward_index(target as u8) as usize, i);
+    }
+}
+
+/// Tests that mismatched rotor, position and ring counts are rejected
+#[test]
+fn test_custom_machine_count_mismatch() {
+    let fewer_rotors = factory::create_custom_machine(["I", "II"], ['A'; 3], ['A'; 3], "B", "");
+    assert!(fewer_rotors.unwrap_err().contains("2 Rotoren"));
+
+    assert!(
+        factory::create_custom_machine(["I", "II", "III"], ['A'; 2], ['A'; 3], "B", "").is_err()
+    );
+    assert!(
+        factory::create_custom_machine(["I", "II", "III"], ['A'; 3], ['A'; 4], "B", "").is_err()
+    );
+    assert!(factory::create_custom_machine_allowing_duplicates(
+        ["I", "I", "I"],
+        ['A'; 3],
+        ['A'; 1],
+        "B",
+        ""
+    )
+    .is_err());
+
+    // Matching counts of any length still work
+    let two = factory::create_custom_machine(["I", "II"], ['A'; 2], ['A'; 2], "B", "")
+        .expect("Two rotors should be allowed");
+    assert_eq!(two.rotors.len(), 2);
+}
+
+/// Tests the compact binary configuration format and its Base64 form
+#[test]
+fn test_config_binary_round_trip() {
+    use enigma_rs::machine::MachineConfig;
+    use enigma_rs::utils::{decode_base64, encode_base64};
+
+    let standard = factory::create_custom_machine(
+        ["VIII", "II", "V"],
+        ['Q', 'E', 'V'],
+        ['Z', 'A', 'M'],
+        "C",
+        "AQ BW ER TZ UI OP LK JH GF DS",
+    )
+    .unwrap()
+    .to_config();
+    let bytes = standard
+        .to_bytes()
+        .expect("Standard config should be storable");
+    assert_eq!(bytes.len(), 16);
+    assert_eq!(MachineConfig::from_bytes(&bytes).unwrap(), standard);
+
+    let code = standard.to_base64().unwrap();
+    assert_eq!(code.len(), 22);
+    assert!(code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    assert_eq!(MachineConfig::from_base64(&code).unwrap(), standard);
+
+    // M4 and the extremes of the plugboard numbering
+    let mut m4 = factory::create_m4_machine(
+        ["Gamma", "VI", "VII", "VIII"],
+        ['A', 'B', 'C', 'D'],
+        ['W', 'X', 'Y', 'Z'],
+        "C-Thin",
+        "",
+    )
+    .unwrap()
+    .to_config();
+    for plugboard in ["", "AB", "YZ", "AZ BY CX DW EV FU GT HS IR JQ KP LO MN"] {
+        m4.plugboard = plugboard.to_string();
+        let bytes = m4.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 18);
+        assert_eq!(MachineConfig::from_bytes(&bytes).unwrap(), m4);
+    }
+
+    // Decoded configurations build working machines, with the Uhr and with
+    // all 13 cables
+    let mut with_uhr = standard.clone();
+    with_uhr.uhr_setting = Some(39);
+    let decoded = MachineConfig::from_base64(&with_uhr.to_base64().unwrap()).unwrap();
+    assert_eq!(decoded, with_uhr);
+    let mut original = factory::from_config(&with_uhr).unwrap();
+    let mut rebuilt = factory::from_config(&decoded).expect("Decoded config should build");
+    assert_eq!(rebuilt.plugboard.get_uhr_setting(), Some(39));
+    assert_eq!(
+        rebuilt.encrypt("UHRSTELLUNG"),
+        original.encrypt("UHRSTELLUNG")
+    );
+
+    let mut full = standard.clone();
+    full.plugboard = "AZ BY CX DW EV FU GT HS IR JQ KP LO MN".to_string();
+    let decoded = MachineConfig::from_bytes(&full.to_bytes().unwrap()).unwrap();
+    let mut rebuilt = factory::from_config(&decoded).expect("Decoded config should build");
+    assert_eq!(rebuilt.plugboard.get_connection_count(), 13);
+    assert_eq!(
+        rebuilt.encrypt("STECKER"),
+        factory::from_config(&full).unwrap().encrypt("STECKER")
+    );
+
+    // The Uhr accepts at most 10 cables
+    full.uhr_setting = Some(1);
+    assert!(full.to_bytes().is_err());
+
+    // Version 1 data without the Uhr byte is still readable
+    let mut legacy = standard.to_bytes().unwrap();
+    legacy[0] = 0x13;
+    legacy.remove(8);
+    assert_eq!(MachineConfig::from_bytes(&legacy).unwrap(), standard);
+
+    // Components without a number cannot be stored
+    let mut custom = standard.clone();
+    custom.reflector_type = "CUSTOM:YRUHQSLDPXNGOKMIEBFZCWVJAT".to_string();
+    assert!(custom.to_bytes().is_err());
+
+    // Damaged input is rejected instead of panicking
+    assert!(MachineConfig::from_bytes(&[]).is_err());
+    assert!(MachineConfig::from_bytes(&bytes[..14]).is_err());
+    assert!(MachineConfig::from_bytes(&[0x13; 15]).is_err());
+    assert!(MachineConfig::from_bytes(&[0xff; 15]).is_err());
+    let mut bad_uhr = bytes.clone();
+    bad_uhr[8] = 40;
+    assert!(MachineConfig::from_bytes(&bad_uhr).is_err());
+    assert!(MachineConfig::from_base64("not base64!").is_err());
+
+    for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\x00\xfe\x01"] {
+        assert_eq!(decode_base64(&encode_base64(data)).unwrap(), data);
+    }
+    assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+}
+
+/// Tests listing the letters without a cable
+#[test]
+fn test_plugboard_unconnected_letters() {
+    use enigma_rs::plugboard::Plugboard;
+
+    let mut plugboard = Plugboard::new();
+    assert_eq!(plugboard.unconnected_letters().len(), 26);
+
+    plugboard.add_connection('A', 'B').unwrap();
+    plugboard.add_connection('C', 'D').unwrap();
+    let expected: Vec<char> = ('E'..='Z').collect();
+    assert_eq!(plugboard.unconnected_letters(), expected);
+}
+
+/// Tests the grouping styles and the unencrypted header group
+#[test]
+fn test_grouping_style_and_header_group() {
+    use enigma_rs::machine::GroupingStyle;
+
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+    let text = "KRIEGSMARINEFUNKSPRUCH";
+    let army = machine.encrypt(text);
+
+    machine.reset();
+    machine.set_grouping_style(GroupingStyle::Navy4);
+    assert_eq!(machine.get_output_grouping(), Some(4));
+    machine.set_header_group(Some("mqxk")).unwrap();
+    let navy = machine.encrypt(text);
+
+    let mut groups = navy.split_whitespace();
+    assert_eq!(groups.next(), Some("MQXK"));
+    assert!(groups.clone().all(|group| group.len() <= 4));
+    assert_eq!(groups.collect::<String>(), clean_text(&army));
+
+    // The header does not step the rotors and can be removed again
+    machine.reset();
+    machine.set_header_group(None).unwrap();
+    machine.set_grouping_style(GroupingStyle::None);
+    assert_eq!(machine.encrypt(text), clean_text(&army));
+
+    assert!(machine.set_header_group(Some("AB1")).is_err());
+    assert!(machine.set_header_group(Some("")).is_err());
+}
+
+/// Tests that grouped ciphertext decrypts verbatim for every length up to 50
+#[test]
+fn test_grouped_ciphertext_round_trip_all_lengths() {
+    let alphabet = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    let mut machine = factory::create_custom_machine(
+        ["II", "IV", "V"],
+        ['B', 'L', 'A'],
+        ['B', 'U', 'L'],
+        "B",
+        "AV BS CG DL FU HZ IN KM OW RX",
+    )
+    .expect("Machine should be creatable");
+
+    for group_size in [Some(5), Some(4), Some(1), None] {
+        machine.set_output_grouping(group_size);
+
+        for length in 1..=50 {
+            let plaintext: String = alphabet.chars().cycle().take(length).collect();
+
+            machine.reset();
+            let ciphertext = machine.encrypt(&plaintext);
+
+            // Every group is full except possibly the last one
+            if let Some(size) = group_size {
+                let groups: Vec<&str> = ciphertext.split_whitespace().collect();
+                assert_eq!(groups.len(), length.div_ceil(size));
+                let (last, full) = groups.split_last().unwrap();
+                assert!(full.iter().all(|group| group.len() == size));
+                assert!(!last.is_empty() && last.len() <= size);
+            }
+
+            // The grouped text is pasted back verbatim, with or without the
+            // trailing space and with extra whitespace around it
+            for pasted in [
+                ciphertext.clone(),
+                ciphertext.trim_end().to_string(),
+                format!("  {}\n", ciphertext),
+            ] {
+                machine.reset();
+                let decrypted = machine.decrypt(&pasted);
+                assert_eq!(
+                    clean_text(&decrypted),
+                    plaintext,
+                    "length {}, grouping {:?}",
+                    length,
+                    group_size
+                );
+            }
+        }
+    }
+}
+
+/// Tests applying all settings from the CLI text formats in place
+#[test]
+fn test_apply_settings() {
+    use enigma_rs::machine::MachineConfig;
+
+    let config = MachineConfig::from_settings("II, IV,V", "B L A", "02-21-12", "B", " AV BS ")
+        .expect("Settings should parse");
+    assert_eq!(config.rotor_types, ["II", "IV", "V"]);
+    assert_eq!(config.rotor_positions, ['B', 'L', 'A']);
+    assert_eq!(config.ring_settings, ['B', 'U', 'L']);
+    assert_eq!(config.plugboard, "AV BS");
+
+    let defaults = MachineConfig::from_settings("Beta I II III", "", "", "B-Thin", "").unwrap();
+    assert_eq!(defaults.rotor_positions, ['A'; 4]);
+
+    let mut machine = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    machine.set_output_grouping(None);
+    machine.process_exact("ADVANCE");
+    machine
+        .apply_settings("II,IV,V", "BLA", "BUL", "B", "AV BS")
+        .expect("Settings should apply");
+    assert_eq!(machine.get_step_count(), 0);
+    assert_eq!(machine.get_output_grouping(), None);
+
+    let mut expected = factory::from_config(&config).unwrap();
+    expected.set_output_grouping(None);
+    assert_eq!(machine, expected);
+
+    // Invalid settings leave the machine untouched
+    for (rotors, positions, reflector) in [
+        ("I,II", "ABC", "B"),
+        ("I,II,IX", "", "B"),
+        ("I,I,II", "", "B"),
+        ("I,II,III", "", "Z"),
+    ] {
+        assert!(machine
+            .apply_settings(rotors, positions, "", reflector, "")
+            .is_err());
+        assert_eq!(machine, expected);
+    }
+}
+
+/// Tests the historical rotor metadata
+#[test]
+fn test_rotor_info() {
+    use enigma_rs::rotor::rotors;
+
+    for name in ["I", "II", "III", "IV", "V"] {
+        let info = rotors::info(name).expect("Historical rotors should have info");
+        assert_eq!(info.name, name);
+        assert_eq!(info.notches.len(), 1);
+        assert!(!info.introduced.is_empty());
+    }
+
+    // Every available rotor is documented, and the data matches the rotor
+    for (name, create) in rotors::available_rotors() {
+        let info = rotors::info(name).expect("Available rotors should have info");
+        assert_eq!(info.wiring, create(0, 0).unwrap().wiring_string());
+    }
+
+    let iv = rotors::info("IV").unwrap();
+    assert_eq!(iv.wiring, "ESOVPZJAYQUIRHXLNFTGKDCMWB");
+    assert_eq!(iv.notches, "J");
+    assert_eq!(iv.introduced, "Dezember 1938");
+    assert_eq!(rotors::info("VI").unwrap().notches, "ZM");
+    assert_eq!(rotors::info("Beta").unwrap().notches, "");
+    assert!(rotors::info("IX").is_none());
+}
+
+/// Tests the checked decryption
+#[test]
+fn test_decrypt_checked() {
+    use enigma_rs::machine::factory;
+
+    let mut sender = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    let ciphertext = sender.encrypt("HELLOWORLD");
+
+    let mut reference = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    let mut receiver = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    let plaintext = receiver.decrypt_checked(&ciphertext).unwrap();
+    assert_eq!(plaintext, reference.decrypt(&ciphertext));
+    assert_eq!(plaintext.replace(' ', ""), "HELLOWORLD");
+    assert_eq!(receiver.get_rotor_positions(), sender.get_rotor_positions());
+
+    // Invalid characters are reported by character index and leave the rotors alone
+    let mut receiver = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    let before = receiver.get_rotor_positions();
+    assert_eq!(receiver.decrypt_checked("ABC1D-E"), Err(vec![3, 5]));
+    assert_eq!(receiver.get_rotor_positions(), before);
+
+    // Whitespace between groups is fine
+    let mut receiver = factory::create_standard_machine(['A'; 3], ['A'; 3], "").unwrap();
+    assert!(receiver.decrypt_checked("AB  CD\nEF").is_ok());
+}
+
+/// Tests the fingerprint of a saved configuration including its start position
+#[test]
+fn test_config_state_fingerprint() {
+    use enigma_rs::machine::{MachineConfig, SteppingModel};
+
+    let config = MachineConfig::from_settings("I II III", "ABC", "DEF", "B", "AB CD").unwrap();
+    let fingerprint = config.state_fingerprint();
+    assert_eq!(fingerprint.len(), 16);
+    assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+
+    // Same settings written differently
+    let mut respelled = config.clone();
+    respelled.rotor_positions = vec!['a', 'b', 'c'];
+    respelled.plugboard = "dc ba".to_string();
+    assert_eq!(respelled.state_fingerprint(), fingerprint);
+
+    // Any setting that changes the encryption changes the fingerprint
+    let mut moved = config.clone();
+    moved.rotor_positions = vec!['A', 'B', 'D'];
+    assert_ne!(moved.state_fingerprint(), fingerprint);
+    let mut restecked = config.clone();
+    restecked.plugboard = "AC BD".to_string();
+    assert_ne!(restecked.state_fingerprint(), fingerprint);
+
+    // Configurations without a compact byte form still get a fingerprint
+    let ukw_d = MachineConfig::from_settings(
+        "I II III",
+        "",
+        "",
+        "D:AC BD EF GH IK LM NO PQ RS TU VW XZ",
+        "",
+    )
+    .unwrap();
+    assert_eq!(ukw_d.state_fingerprint(), ukw_d.clone().state_fingerprint());
+    assert_ne!(ukw_d.state_fingerprint(), fingerprint);
+
+    // Only the stepping model differs: the fallback form still tells them apart
+    let mut gear = ukw_d.clone();
+    gear.stepping_model = SteppingModel::Gear;
+    assert_ne!(gear.state_fingerprint(), ukw_d.state_fingerprint());
+}
+
+/// Tests the letters/figures shift round trip through the machine
+#[test]
+fn test_figure_shift_round_trip() {
+    use enigma_rs::machine::factory;
+    use enigma_rs::utils::FigureShift;
+
+    let message = "Meet Jan at 14:30, Pier 7 (gate 2-B)?";
+    let encoded = FigureShift::encode(message);
+    assert!(encoded.chars().all(|c| c.is_ascii_uppercase()));
+
+    let mut sender = factory::create_standard_machine(['A'; 3], ['A'; 3], "AB CD").unwrap();
+    let ciphertext = sender.encrypt(&encoded);
+    let mut receiver = factory::create_standard_machine(['A'; 3], ['A'; 3], "AB CD").unwrap();
+    let decrypted = receiver.decrypt(&ciphertext);
+
+    assert_eq!(
+        FigureShift::decode(&decrypted).unwrap(),
+        "MEETJANAT14:30,PIER7(GATE2-B)?"
+    );
+
+    // Literal escape letters survive, malformed markers are reported
+    assert_eq!(FigureShift::encode("JJ"), "JJJJ");
+    assert_eq!(FigureShift::decode("JJJJ").unwrap(), "JJ");
+    assert!(FigureShift::decode("ABJ").is_err());
+    assert!(FigureShift::decode("JX").is_err());
+    assert!(FigureShift::decode("JFD").is_err());
 }
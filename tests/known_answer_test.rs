@@ -0,0 +1,99 @@
+use enigma_rs::machine::factory;
+use enigma_rs::utils::clean_text;
+
+/// Rotors I II III, reflector B, rings AAA, position AAA, no plugboard
+#[test]
+fn test_basic_vector() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    assert_eq!(clean_text(&machine.encrypt("AAAAA")), "BDZGO");
+
+    machine.reset();
+    assert_eq!(clean_text(&machine.encrypt("HELLOWORLD")), "ILBDAAMTAZ");
+}
+
+/// Same machine with ring settings BBB
+#[test]
+fn test_ring_setting_vector() {
+    let mut machine = factory::create_standard_machine(['A', 'A', 'A'], ['B', 'B', 'B'], "")
+        .expect("Machine should be creatable");
+
+    assert_eq!(clean_text(&machine.encrypt("AAAAA")), "EWTYX");
+}
+
+/// Start position ADU runs through the double step of the middle rotor
+#[test]
+fn test_double_step_vector() {
+    let mut machine = factory::create_standard_machine(['A', 'D', 'U'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable");
+
+    assert_eq!(clean_text(&machine.encrypt("AAAAA")), "EQIBM");
+    assert_eq!(machine.get_rotor_positions(), ['B', 'F', 'Z']);
+}
+
+/// Operation Barbarossa message, part 1 (7 July 1941)
+///
+/// Rotors II IV V, rings BUL, plugboard AV BS CG DL FU HZ IN KM OW RX.
+/// The indicator KCH enciphered at the ground setting WXC gives the
+/// message key BLA.
+#[test]
+fn test_barbarossa_vector() {
+    let plugboard = "AV BS CG DL FU HZ IN KM OW RX";
+
+    let mut indicator = factory::create_custom_machine(
+        ["II", "IV", "V"],
+        ['W', 'X', 'C'],
+        ['B', 'U', 'L'],
+        "B",
+        plugboard,
+    )
+    .expect("Machine should be creatable");
+    assert_eq!(clean_text(&indicator.decrypt("KCH")), "BLA");
+
+    let mut machine = factory::create_custom_machine(
+        ["II", "IV", "V"],
+        ['B', 'L', 'A'],
+        ['B', 'U', 'L'],
+        "B",
+        plugboard,
+    )
+    .expect("Machine should be creatable");
+
+    let ciphertext = "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK UBPMM \
+        YLKLT TDEIS MDICA GYKUA CTCDO MOHWX MUUIA UBSTS LRNBZ SZWNR FXWFY SSXJZ VIJHI DISHP \
+        RKLKA YUPAD TXQSP INQMA TLPIF SVKDA SCTAC DPBOP VHJK";
+    let plaintext = "AUFKLXABTEILUNGXVONXKURTINOWAXKURTINOWAXNORDWESTLXSEBEZXSEBEZXUAFFLIEGER\
+        STRASZERIQTUNGXDUBROWKIXDUBROWKIXOPOTSCHKAXOPOTSCHKAXUMXEINSAQTDREINULLXUHRANGETRETEN\
+        XANGRIFFXINFXRGTX";
+
+    assert_eq!(clean_text(&machine.decrypt(ciphertext)), plaintext);
+}
+
+/// An M4 with Beta at A and the thin reflector B behaves like an M3 with reflector B
+#[test]
+fn test_m4_matches_m3_vector() {
+    let mut m4 = factory::create_m4_machine(
+        ["Beta", "I", "II", "III"],
+        ['A', 'A', 'A', 'A'],
+        ['A', 'A', 'A', 'A'],
+        "B-Thin",
+        "",
+    )
+    .expect("M4 should be creatable");
+
+    assert_eq!(clean_text(&m4.encrypt("AAAAA")), "BDZGO");
+
+    let mut m4 = factory::create_m4_machine(
+        ["Beta", "II", "IV", "V"],
+        ['A', 'B', 'L', 'A'],
+        ['A', 'B', 'U', 'L'],
+        "B-Thin",
+        "AV BS CG DL FU HZ IN KM OW RX",
+    )
+    .expect("M4 should be creatable");
+    assert_eq!(
+        clean_text(&m4.decrypt("EDPUD NRGYS ZRCXN UYTPO")),
+        "AUFKLXABTEILUNGXVONX"
+    );
+}
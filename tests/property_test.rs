@@ -0,0 +1,66 @@
+use enigma_rs::machine::{factory, EnigmaMachine};
+use enigma_rs::utils::clean_text;
+use proptest::prelude::*;
+
+const ROTORS: [&str; 8] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII"];
+
+/// Generates three different rotors in random order
+fn rotor_order() -> impl Strategy<Value = Vec<&'static str>> {
+    proptest::sample::subsequence(ROTORS.to_vec(), 3).prop_shuffle()
+}
+
+/// Generates one setting letter per rotor
+fn settings() -> impl Strategy<Value = [char; 3]> {
+    proptest::array::uniform3(0u8..26).prop_map(|indices| indices.map(|i| (b'A' + i) as char))
+}
+
+/// Generates a plugboard with 0-10 random cables
+fn plugboard() -> impl Strategy<Value = String> {
+    let letters: Vec<char> = ('A'..='Z').collect();
+    (Just(letters).prop_shuffle(), 0usize..=10).prop_map(|(letters, cables)| {
+        letters
+            .chunks(2)
+            .take(cables)
+            .map(|pair| pair.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ")
+    })
+}
+
+/// Generates a complete random machine
+fn machine() -> impl Strategy<Value = EnigmaMachine> {
+    (
+        rotor_order(),
+        settings(),
+        settings(),
+        prop_oneof![Just("A"), Just("B"), Just("C")],
+        plugboard(),
+    )
+        .prop_map(|(rotors, positions, rings, reflector, plugboard)| {
+            factory::create_custom_machine(rotors, positions, rings, reflector, &plugboard)
+                .expect("Generated settings should be valid")
+        })
+}
+
+proptest! {
+    /// Decrypting the ciphertext from the same start state restores the plaintext
+    #[test]
+    fn prop_encrypt_decrypt_symmetry(mut machine in machine(), text in "[A-Za-z ,.!]{0,200}") {
+        let ciphertext = machine.encrypt(&text);
+        machine.reset();
+        let decrypted = machine.decrypt(&ciphertext);
+
+        prop_assert_eq!(clean_text(&decrypted), clean_text(&text));
+    }
+
+    /// No letter is ever encrypted to itself
+    #[test]
+    fn prop_no_self_encryption(mut machine in machine(), text in "[A-Z]{1,200}") {
+        let ciphertext = machine.process_exact(&text);
+
+        prop_assert_eq!(ciphertext.len(), text.len());
+        for (plain, cipher) in text.chars().zip(ciphertext.chars()) {
+            prop_assert_ne!(plain, cipher);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Tests of the turnover log events (needs the default `logging` feature)
+//!
+//! Installs a process-wide logger, so it lives in its own test binary.
+#![cfg(feature = "logging")]
+
+use enigma_rs::machine::{factory, TURNOVER_LOG_TARGET};
+use std::sync::Mutex;
+
+/// Collects the messages logged under the turnover target
+struct TurnoverLogger(Mutex<Vec<String>>);
+
+impl log::Log for TurnoverLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == TURNOVER_LOG_TARGET
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: TurnoverLogger = TurnoverLogger(Mutex::new(Vec::new()));
+
+/// Tests that only notch turnovers are logged under the turnover target
+#[test]
+fn test_turnover_log_events() {
+    log::set_logger(&LOGGER).expect("No other logger should be installed");
+    log::set_max_level(log::LevelFilter::Info);
+
+    // Rotor III carries at V, rotor II at E: the middle rotor moves on the
+    // first key press and double steps together with the left rotor on the second
+    let mut machine = factory::create_standard_machine(['A', 'D', 'V'], ['A'; 3], "").unwrap();
+    machine.encrypt("AAAAA");
+
+    assert_eq!(
+        *LOGGER.0.lock().unwrap(),
+        [
+            "Übertrag bei Schritt 1: Rotor II (2. von links) dreht auf E",
+            "Übertrag bei Schritt 2: Rotor I (1. von links) dreht auf B",
+            "Übertrag bei Schritt 2: Rotor II (2. von links) dreht auf F",
+        ]
+    );
+}
@@ -0,0 +1,23 @@
+//! Tests of the browser bindings (run with `cargo test --features wasm`)
+#![cfg(feature = "wasm")]
+
+use enigma_rs::machine::factory;
+use enigma_rs::utils::clean_text;
+use enigma_rs::wasm;
+
+#[test]
+fn test_wasm_encrypt_decrypt() {
+    let config = factory::create_standard_machine(['A', 'A', 'A'], ['A', 'A', 'A'], "")
+        .expect("Machine should be creatable")
+        .to_config()
+        .to_json()
+        .expect("Config should serialize");
+
+    let ciphertext = wasm::encrypt(&config, "HELLO WORLD").expect("Encryption should succeed");
+    assert_eq!(clean_text(&ciphertext), "ILBDAAMTAZ");
+
+    let plaintext = wasm::decrypt(&config, &ciphertext).expect("Decryption should succeed");
+    assert_eq!(clean_text(&plaintext), "HELLOWORLD");
+
+    assert!(wasm::encrypt("{}", "HELLO").is_err());
+}